@@ -1,11 +1,14 @@
 use std::{fmt, string::ToString};
 
+use crossterm::event::KeyEvent;
 use serde::{
     de::{self, Deserializer, Visitor},
     Deserialize, Serialize,
 };
 use strum::Display;
 
+use crate::{broadcast::ServerResult, command::logs::LogLine, mode::Mode, session::Tab};
+
 // ANCHOR: action_enum
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Display, Deserialize)]
 pub enum Action {
@@ -18,16 +21,74 @@ pub enum Action {
     Refresh,
     Error(String),
     Help,
+    Apropos(String),
+    OpenPalette,
+    SetTheme(String),
     ToggleShowHelp,
+    ToggleChat,
+    FocusChat,
+    ToggleScoreboard,
+    ToggleKillFeed,
     Connect(String, String),
+    NewTab,
+    CloseTab,
+    NextTab,
+    PrevTab,
+    SelectTab(usize),
+    Tabs(Vec<Tab>, usize),
     Connected(bool),
     Command(String),
+    SendCommand(String),
+    SendBlock(String),
+    ConfirmCommand(String, String),
+    Broadcast(String, String),
+    SendBroadcast(String, String),
+    ConfirmBroadcast(String, String, String),
+    Match(String),
+    Report,
+    OfferCommands(String, Vec<String>),
+    RunCommands(Vec<String>),
+    BroadcastResults(String, String, Vec<ServerResult>),
+    Warning(String),
+    QueryConvar(String),
+    ConvarValue(String, String, Option<String>),
+    Log(Box<LogLine>),
+    /// The log lines of the current match in the front tab, to rebuild from.
+    Replay(Vec<LogLine>),
     Insert(String),
     InsertAll(Vec<String>),
     EnterNormal,
     EnterInsert,
     EnterProcessing,
     ExitProcessing,
+    ModeChanged(Mode),
+    PendingKeys(Vec<KeyEvent>),
+    Submit,
+    Cancel,
+    Discard,
+    Expand,
+    InsertNewline,
+    AcceptSuggestion,
+    HistoryPrev,
+    HistoryNext,
+    PrevItem,
+    NextItem,
+    PrevPage,
+    NextPage,
+    GoToPrevChar,
+    GoToNextChar,
+    GoToPrevWord,
+    GoToNextWord,
+    GoToPrevLine,
+    GoToNextLine,
+    GoToStart,
+    GoToEnd,
+    DeletePrevChar,
+    DeleteNextChar,
+    DeletePrevWord,
+    DeleteNextWord,
+    DeleteLine,
+    DeleteTillEnd,
     Update,
 }
 //// ANCHOR_END: action_enum
@@ -1,62 +1,263 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
 use color_eyre::eyre::Result;
-use crossterm::event::KeyEvent;
-use ratatui::prelude::Rect;
+use ratatui::prelude::{Constraint, Direction, Layout, Rect};
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use tokio::sync::mpsc::{self, UnboundedSender};
 
 use crate::{
     action::Action,
-    client::Client,
-    components::{fps::FpsCounter, home::Home, Component},
+    broadcast,
+    chord::{ChordEngine, Resolved},
+    command::validator::Validation,
+    components::{
+        fps::FpsCounter, killfeed::KillFeed, scoreboard::Scoreboard, tabs::TabBar, Component,
+    },
     config::Config,
+    layout,
     mode::Mode,
-    tui,
+    receiver::Receivers,
+    session::{Activity, Session},
+    theme::Theme,
+    tui::{self, Frame},
+    utils,
+    watcher::FileWatcher,
 };
 
+/// A component drawn in its own region of the layout, next to the output.
+pub struct Panel {
+    /// Key of its placement in `layout`.
+    pub name: &'static str,
+    /// Index of the component in [`App::components`].
+    pub component: usize,
+    pub toggle: Action,
+    pub visible: bool,
+}
+
 pub struct App {
     pub config: Config,
     pub tick_rate: f64,
     pub frame_rate: f64,
     pub components: Vec<Box<dyn Component>>,
+    pub panels: Vec<Panel>,
     pub should_quit: bool,
     pub should_suspend: bool,
     pub mode: Mode,
-    pub last_tick_key_events: Vec<KeyEvent>,
-    pub client: Client,
+    pub chords: ChordEngine,
+    pub sessions: Vec<Session>,
+    pub next_session: usize,
+    pub receivers: Receivers,
+    /// Index of the session in the front tab.
+    pub active: usize,
+    pub config_file: Option<PathBuf>,
+    pub config_watcher: FileWatcher,
+    pub theme: Option<String>,
 }
 
 impl App {
-    pub fn new(tick_rate: f64, frame_rate: f64) -> Result<Self> {
-        let home = Home::new();
+    pub fn new(tick_rate: f64, frame_rate: f64, config: Option<PathBuf>) -> Result<Self> {
+        let tabs = TabBar::new();
         let fps = FpsCounter::default();
-        let config = Config::new()?;
-        let mode = Mode::Home;
+        let scoreboard = Scoreboard::new();
+        let killfeed = KillFeed::new();
+        let config_file = config;
+        let config = Config::new(config_file.clone())?;
+        let config_watcher = FileWatcher::new(
+            Config::files(config_file.clone())
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect(),
+        );
+        let mode = Mode::default();
+        let chords = ChordEngine::new(Duration::from_millis(config.chord_timeout));
         Ok(Self {
             tick_rate,
             frame_rate,
-            components: vec![Box::new(home), Box::new(fps)],
+            components: vec![
+                Box::new(tabs),
+                Box::new(fps),
+                Box::new(scoreboard),
+                Box::new(killfeed),
+            ],
+            panels: vec![Panel {
+                name: "killfeed",
+                component: 3,
+                toggle: Action::ToggleKillFeed,
+                visible: config.placement("killfeed").visible,
+            }],
             should_quit: false,
             should_suspend: false,
             config,
             mode,
-            last_tick_key_events: Vec::new(),
-            client: Client::default(),
+            chords,
+            sessions: Vec::new(),
+            next_session: 0,
+            receivers: Receivers::default(),
+            active: 0,
+            config_file,
+            config_watcher,
+            theme: None,
         })
     }
 
+    /// Reload the config files if they changed, keeping the current config if they fail to
+    /// parse or apply. A failure is returned as an error to show, the TUI keeps running.
+    fn reload_config(&mut self) -> Option<Action> {
+        if !self.config_watcher.changed() {
+            return None;
+        }
+        let mut config = match Config::new(self.config_file.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                log::error!("Failed to reload config: {}", e);
+                return Some(Action::Error(format!("Failed to reload config: {}", e)));
+            }
+        };
+        if self.theme.is_some() {
+            config.theme = self.theme.clone();
+        }
+        if let Err(e) = self.apply_config(&config) {
+            log::error!("Failed to apply config: {}", e);
+            // Put back what the sessions and components had before.
+            let old = self.config.clone();
+            if let Err(e) = self.apply_config(&old) {
+                log::error!("Failed to restore config: {}", e);
+            }
+            return Some(Action::Error(format!("Failed to reload config: {}", e)));
+        }
+        log::info!("Reloaded config");
+        self.config = config;
+        None
+    }
+
+    fn apply_config(&mut self, config: &Config) -> Result<()> {
+        for session in self.sessions.iter_mut() {
+            session.register_config_handler(config)?;
+        }
+        self.chords
+            .set_timeout(Duration::from_millis(config.chord_timeout));
+        for component in self.components.iter_mut() {
+            component.register_config_handler(config.clone())?;
+        }
+        Ok(())
+    }
+
+    fn add_session(&mut self, action_tx: UnboundedSender<Action>) -> Result<()> {
+        let session = Session::new(self.next_session, &self.config, action_tx)?;
+        self.sessions.push(session);
+        self.next_session += 1;
+        Ok(())
+    }
+
+    fn tabs(&self) -> Action {
+        Action::Tabs(
+            self.sessions.iter().map(Session::tab).collect(),
+            self.active,
+        )
+    }
+
+    /// Bring the session at `index` to the front, picking up the input mode it was left in.
+    fn select_tab(&mut self, index: usize) {
+        self.active = index.min(self.sessions.len().saturating_sub(1));
+        let session = &mut self.sessions[self.active];
+        session.activity = Activity::None;
+        if let Some(step) = session.pending.take() {
+            if let Err(e) = session
+                .home
+                .update(Action::OfferCommands(step.to.to_string(), step.run))
+            {
+                log::error!("Failed to offer match commands: {}", e);
+            }
+        }
+        self.mode = session.home.mode;
+        self.chords.clear();
+        self.replay();
+    }
+
+    /// Rebuild what the components show of the match from the backlog of the front tab.
+    fn replay(&mut self) {
+        let Some(session) = self.sessions.get(self.active) else {
+            return;
+        };
+        let backlog = session.backlog();
+        for component in self.components.iter_mut() {
+            if let Err(e) = component.update(Action::Replay(backlog.clone())) {
+                log::error!("Failed to replay the log: {}", e);
+            }
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>) -> Result<()> {
+        let area = f.size();
+        // The first row belongs to the tab bar, the panels go around the output below it.
+        let body = Rect {
+            y: area.y + 1,
+            height: area.height.saturating_sub(1),
+            ..area
+        };
+        let shown = self
+            .panels
+            .iter()
+            .filter(|panel| panel.visible)
+            .collect::<Vec<_>>();
+        let placements = shown
+            .iter()
+            .map(|panel| self.config.placement(panel.name))
+            .collect::<Vec<_>>();
+        let (rects, rect) = layout::split(body, &placements);
+        let panel_rects = shown
+            .iter()
+            .zip(rects)
+            .map(|(panel, rect)| (panel.component, rect))
+            .collect::<HashMap<_, _>>();
+        let hidden = self
+            .panels
+            .iter()
+            .filter(|panel| !panel.visible)
+            .map(|panel| panel.component)
+            .collect::<Vec<_>>();
+        if let Some(session) = self.sessions.get_mut(self.active) {
+            match session.chat.visible() {
+                true => {
+                    let [home_rect, chat_rect] = *Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+                        .split(rect)
+                    else {
+                        panic!("Unable to split rects into a refutable pattern");
+                    };
+                    session.home.draw(f, home_rect)?;
+                    session.chat.draw(f, chat_rect)?;
+                }
+                false => session.home.draw(f, rect)?,
+            }
+        }
+        for (i, component) in self.components.iter_mut().enumerate() {
+            if hidden.contains(&i) {
+                continue;
+            }
+            component.draw(f, panel_rects.get(&i).copied().unwrap_or(area))?;
+        }
+        Ok(())
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         let (action_tx, mut action_rx) = mpsc::unbounded_channel();
 
         let mut tui = tui::Tui::new()?
             .tick_rate(self.tick_rate)
             .frame_rate(self.frame_rate)
-            .mouse(true);
+            .mouse(true)
+            .paste(true);
         tui.enter()?;
 
         for component in self.components.iter_mut() {
             component.register_action_handler(action_tx.clone())?;
         }
-        self.client.register_action_handler(action_tx.clone())?;
 
         for component in self.components.iter_mut() {
             component.register_config_handler(self.config.clone())?;
@@ -66,6 +267,10 @@ impl App {
             component.init(tui.size()?)?;
         }
 
+        self.add_session(action_tx.clone())?;
+        self.select_tab(0);
+
+        let mut shown_tabs = None;
         loop {
             if let Some(e) = tui.next().await {
                 match e {
@@ -73,73 +278,281 @@ impl App {
                     tui::Event::Tick => action_tx.send(Action::Tick)?,
                     tui::Event::Render => action_tx.send(Action::Render)?,
                     tui::Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
-                    tui::Event::Key(key) => {
-                        if let Some(keymap) = self.config.keybindings.get(&self.mode) {
-                            if let Some(action) = keymap.get(&vec![key]) {
-                                log::info!("Got action: {action:?}");
-                                action_tx.send(action.clone())?;
-                            } else {
-                                // If the key was not handled as a single key action,
-                                // then consider it for multi-key combinations.
-                                self.last_tick_key_events.push(key);
-
-                                // Check for multi-key combinations
-                                if let Some(action) = keymap.get(&self.last_tick_key_events) {
-                                    log::info!("Got action: {action:?}");
-                                    action_tx.send(action.clone())?;
+                    _ => {}
+                }
+                let pending = self.chords.pending().to_vec();
+                let unbound = HashMap::new();
+                let keymap = self.config.keybindings.get(&self.mode).unwrap_or(&unbound);
+                let resolved = match e {
+                    tui::Event::Key(key) => self.chords.feed(keymap, key, Instant::now()),
+                    _ => self.chords.expire(keymap, Instant::now()),
+                };
+                if self.chords.pending() != pending {
+                    action_tx.send(Action::PendingKeys(self.chords.pending().to_vec()))?;
+                }
+                // Keys only reach the components once they turned out not to be part of a binding.
+                let mut events = Vec::new();
+                for resolved in resolved {
+                    match resolved {
+                        Resolved::Action(action) => {
+                            log::info!("Got action: {action:?}");
+                            action_tx.send(action)?;
+                        }
+                        Resolved::Key(key) => events.push(tui::Event::Key(key)),
+                    }
+                }
+                if !matches!(e, tui::Event::Key(_)) {
+                    events.push(e);
+                }
+                for event in events {
+                    let session = &mut self.sessions[self.active];
+                    if let Some(action) = session.home.handle_events(Some(event.clone()))? {
+                        action_tx.send(action)?;
+                    }
+                    if let Some(action) = session.chat.handle_events(Some(event.clone()))? {
+                        action_tx.send(action)?;
+                    }
+                    for component in self.components.iter_mut() {
+                        if let Some(action) = component.handle_events(Some(event.clone()))? {
+                            action_tx.send(action)?;
+                        }
+                    }
+                }
+            }
+
+            // Whatever a client reports goes to its own session, wherever it is in the tabs.
+            for (i, session) in self.sessions.iter_mut().enumerate() {
+                for action in session.received() {
+                    session.observe(&action, i == self.active);
+                    if action == Action::Connected(true) {
+                        session
+                            .client
+                            .update(Action::QueryConvar("sv_cheats".to_string()));
+                        if let Err(e) = session
+                            .start_logs(&mut self.receivers, &self.config.logs)
+                            .await
+                        {
+                            session.home.update(Action::Error(e))?;
+                        }
+                        // Catch up on what was logged while the connection was down.
+                        if i == self.active {
+                            for component in self.components.iter_mut() {
+                                component.update(Action::Replay(session.backlog()))?;
+                            }
+                        }
+                    }
+                    if let Some(step) = session.controller.update(&action) {
+                        session.advance(step, i == self.active).await?;
+                    }
+                    session.ready_up(&action, i == self.active).await?;
+                    session.follow_series(&action, i == self.active).await?;
+                    session.report_game_over(&action, &reports_dir())?;
+                    if matches!(action, Action::Log(_)) {
+                        session.chat.update(action.clone())?;
+                        // Log events of the front tab also reach the other components.
+                        if i == self.active {
+                            for component in self.components.iter_mut() {
+                                if let Some(action) = component.update(action.clone())? {
+                                    action_tx.send(action)?;
                                 }
                             }
-                        };
+                        }
                     }
-                    _ => {}
-                }
-                for component in self.components.iter_mut() {
-                    if let Some(action) = component.handle_events(Some(e.clone()))? {
+                    if let Some(action) = session.home.update(action)? {
                         action_tx.send(action)?;
                     }
                 }
             }
+            let tabs = self.tabs();
+            if shown_tabs.as_ref() != Some(&tabs) {
+                shown_tabs = Some(tabs.clone());
+                action_tx.send(tabs)?;
+            }
 
             while let Ok(action) = action_rx.try_recv() {
                 if action != Action::Tick && action != Action::Render {
                     log::debug!("{action:?}");
                 }
+                self.sessions[self.active].validator.update(&action);
                 match action {
                     Action::Tick => {
-                        self.last_tick_key_events.drain(..);
-                        self.client.async_update(action.clone()).await;
+                        if let Some(action) = self.reload_config() {
+                            action_tx.send(action)?;
+                        }
+                        // Background tabs keep polling their server too, each client runs on
+                        // its own task so a slow server holds up no other tab.
+                        for session in self.sessions.iter_mut() {
+                            session.client.update(action.clone());
+                            session.home.update(action.clone())?;
+                        }
+                    }
+                    Action::ModeChanged(mode) => {
+                        self.mode = mode;
+                        if !self.chords.pending().is_empty() {
+                            self.chords.clear();
+                            action_tx.send(Action::PendingKeys(Vec::new()))?;
+                        }
+                    }
+                    Action::NewTab => {
+                        self.add_session(action_tx.clone())?;
+                        self.select_tab(self.sessions.len() - 1);
+                    }
+                    Action::CloseTab => {
+                        let mut session = self.sessions.remove(self.active);
+                        session.stop_logs(&self.receivers, &self.config.logs).await;
+                        if self.sessions.is_empty() {
+                            self.add_session(action_tx.clone())?;
+                        }
+                        self.select_tab(self.active);
+                    }
+                    Action::NextTab => {
+                        let next = (self.active + 1) % self.sessions.len();
+                        self.select_tab(next);
+                    }
+                    Action::PrevTab => {
+                        let prev = (self.active + self.sessions.len() - 1) % self.sessions.len();
+                        self.select_tab(prev);
+                    }
+                    Action::SelectTab(index) if index < self.sessions.len() => {
+                        self.select_tab(index);
+                    }
+                    Action::Quit => {
+                        for session in self.sessions.iter_mut() {
+                            session.stop_logs(&self.receivers, &self.config.logs).await;
+                        }
+                        // Give the servers a moment to take the log addresses off.
+                        futures::future::join_all(
+                            self.sessions
+                                .iter_mut()
+                                .map(|session| session.client.close(Duration::from_secs(2))),
+                        )
+                        .await;
+                        self.should_quit = true
+                    }
+                    Action::ToggleKillFeed => {
+                        for panel in self.panels.iter_mut() {
+                            if panel.toggle == action {
+                                panel.visible = !panel.visible;
+                            }
+                        }
                     }
-                    Action::Quit => self.should_quit = true,
                     Action::Suspend => self.should_suspend = true,
                     Action::Resume => self.should_suspend = false,
                     Action::Resize(w, h) => {
                         tui.resize(Rect::new(0, 0, w, h))?;
                         tui.draw(|f| {
-                            for component in self.components.iter_mut() {
-                                let r = component.draw(f, f.size());
-                                if let Err(e) = r {
-                                    action_tx
-                                        .send(Action::Error(format!("Failed to draw: {:?}", e)))
-                                        .unwrap();
-                                }
+                            if let Err(e) = self.draw(f) {
+                                action_tx
+                                    .send(Action::Error(format!("Failed to draw: {:?}", e)))
+                                    .unwrap();
                             }
                         })?;
                     }
                     Action::Render => {
                         tui.draw(|f| {
-                            for component in self.components.iter_mut() {
-                                let r = component.draw(f, f.size());
-                                if let Err(e) = r {
-                                    action_tx
-                                        .send(Action::Error(format!("Failed to draw: {:?}", e)))
-                                        .unwrap();
-                                }
+                            if let Err(e) = self.draw(f) {
+                                action_tx
+                                    .send(Action::Error(format!("Failed to draw: {:?}", e)))
+                                    .unwrap();
                             }
                         })?;
                     }
-                    Action::Command(_) => self.client.async_update(action.clone()).await,
+                    Action::Command(ref command) => {
+                        match self.sessions[self.active].validator.validate_line(command) {
+                            Validation::Ok => {
+                                action_tx.send(Action::SendCommand(command.clone()))?
+                            }
+                            Validation::Warn(warning) => {
+                                action_tx.send(Action::Warning(warning))?;
+                                action_tx.send(Action::SendCommand(command.clone()))?;
+                            }
+                            Validation::Confirm(reason) => {
+                                action_tx.send(Action::ConfirmCommand(command.clone(), reason))?
+                            }
+                            Validation::Block(reason) => {
+                                action_tx.send(Action::Error(format!("Blocked: {}", reason)))?
+                            }
+                        }
+                    }
+                    Action::Broadcast(ref group, ref command) => {
+                        let action = match (
+                            broadcast::group(&self.config.profiles, group).is_empty(),
+                            self.sessions[self.active].validator.validate_line(command),
+                        ) {
+                            (true, _) => Action::Error(format!("No profiles tagged `{}`", group)),
+                            (_, Validation::Ok) => {
+                                Action::SendBroadcast(group.clone(), command.clone())
+                            }
+                            (_, Validation::Warn(warning)) => {
+                                action_tx.send(Action::Warning(warning))?;
+                                Action::SendBroadcast(group.clone(), command.clone())
+                            }
+                            (_, Validation::Confirm(reason)) => {
+                                Action::ConfirmBroadcast(group.clone(), command.clone(), reason)
+                            }
+                            (_, Validation::Block(reason)) => {
+                                Action::Error(format!("Blocked: {}", reason))
+                            }
+                        };
+                        action_tx.send(action)?;
+                    }
+                    Action::SendBroadcast(ref group, ref command) => {
+                        // Every server gets its own connection, the results come back as an action.
+                        let targets = broadcast::group(&self.config.profiles, group);
+                        let (group, command) = (group.clone(), command.clone());
+                        let tx = action_tx.clone();
+                        tokio::spawn(async move {
+                            let results =
+                                broadcast::broadcast(targets, &command, broadcast::TIMEOUT).await;
+                            let _ = tx.send(Action::BroadcastResults(group, command, results));
+                        });
+                    }
+                    Action::Match(ref args) => {
+                        let session = &mut self.sessions[self.active];
+                        if let Err(e) = session.match_command(args, &self.config.matches).await {
+                            action_tx.send(Action::Error(e))?;
+                        }
+                    }
+                    Action::Report => {
+                        self.sessions[self.active].report(&reports_dir())?;
+                    }
+                    Action::RunCommands(ref commands) => {
+                        self.sessions[self.active].run_commands(commands).await;
+                    }
+                    Action::SendCommand(_)
+                    | Action::SendBlock(_)
+                    | Action::QueryConvar(_)
+                    | Action::Connect(_, _) => {
+                        let session = &mut self.sessions[self.active];
+                        if leaves_server(&action) {
+                            session.stop_logs(&self.receivers, &self.config.logs).await;
+                        }
+                        session.client.update(action.clone())
+                    }
+                    Action::SetTheme(ref name) => match Theme::load(name) {
+                        Ok(_) => {
+                            self.theme = Some(name.clone());
+                            self.config.theme = Some(name.clone());
+                            for component in self.components.iter_mut() {
+                                component.register_config_handler(self.config.clone())?;
+                            }
+                            for session in self.sessions.iter_mut() {
+                                session.register_config_handler(&self.config)?;
+                            }
+                        }
+                        Err(e) => action_tx.send(Action::Error(e))?,
+                    },
                     _ => {}
                 }
+                if action != Action::Tick {
+                    let session = &mut self.sessions[self.active];
+                    if let Some(action) = session.home.update(action.clone())? {
+                        action_tx.send(action)?;
+                    };
+                    if let Some(action) = session.chat.update(action.clone())? {
+                        action_tx.send(action)?;
+                    };
+                }
                 for component in self.components.iter_mut() {
                     if let Some(action) = component.update(action.clone())? {
                         action_tx.send(action)?;
@@ -152,7 +565,8 @@ impl App {
                 tui = tui::Tui::new()?
                     .tick_rate(self.tick_rate)
                     .frame_rate(self.frame_rate)
-                    .mouse(true);
+                    .mouse(true)
+                    .paste(true);
                 tui.enter()?;
             } else if self.should_quit {
                 tui.stop()?;
@@ -163,3 +577,73 @@ impl App {
         Ok(())
     }
 }
+
+/// Where match reports are written.
+fn reports_dir() -> PathBuf {
+    utils::get_data_dir().join("reports")
+}
+
+/// Whether the action drops the connection of the session it is sent in.
+fn leaves_server(action: &Action) -> bool {
+    match action {
+        Action::Connect(_, _) => true,
+        Action::SendCommand(command) => matches!(
+            command.split_whitespace().next(),
+            Some("connect" | "disconnect")
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leaves_server() {
+        assert!(leaves_server(&Action::SendCommand(
+            "disconnect".to_string()
+        )));
+        assert!(leaves_server(&Action::SendCommand(
+            "connect 10.0.0.2:27015 pw".to_string()
+        )));
+        assert!(leaves_server(&Action::Connect(
+            String::new(),
+            String::new()
+        )));
+        assert!(!leaves_server(&Action::SendCommand("status".to_string())));
+        assert!(!leaves_server(&Action::SendCommand(
+            "connected".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_reload_keeps_config_on_error() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rcon-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let file = dir.join("config.json5");
+        // Each write gets its own modification time, the clock may be too coarse to tell.
+        let write = |contents: &str, secs: u64| -> Result<()> {
+            std::fs::write(&file, contents)?;
+            std::fs::File::options()
+                .write(true)
+                .open(&file)?
+                .set_modified(std::time::UNIX_EPOCH + Duration::from_secs(secs))?;
+            Ok(())
+        };
+        write("{ chord_timeout: 700 }", 1)?;
+        let mut app = App::new(1.0, 4.0, Some(file.clone()))?;
+        assert_eq!(app.config.chord_timeout, 700);
+        assert_eq!(app.reload_config(), None);
+
+        write("{ chord_timeout: ", 2)?;
+        assert!(matches!(app.reload_config(), Some(Action::Error(_))));
+        assert_eq!(app.config.chord_timeout, 700);
+
+        write("{ chord_timeout: 900 }", 3)?;
+        assert_eq!(app.reload_config(), None);
+        assert_eq!(app.config.chord_timeout, 900);
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+use crate::{
+    command::cfg,
+    config::Profile,
+    headless::{self, Response},
+};
+
+/// How long to wait for connecting and for each response, unless told otherwise.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The outcome of a broadcast on one server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerResult {
+    pub server: String,
+    pub address: String,
+    pub response: String,
+    pub error: Option<String>,
+}
+
+impl ServerResult {
+    pub fn ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Profiles tagged with `group`, sorted by name.
+pub fn group(profiles: &HashMap<String, Profile>, group: &str) -> Vec<(String, Profile)> {
+    let mut members = profiles
+        .iter()
+        .filter(|(_, profile)| profile.tags.iter().any(|tag| tag == group))
+        .map(|(name, profile)| (name.clone(), profile.clone()))
+        .collect::<Vec<_>>();
+    members.sort_by(|a, b| a.0.cmp(&b.0));
+    members
+}
+
+/// Every tag used by a profile, sorted.
+pub fn groups(profiles: &HashMap<String, Profile>) -> Vec<String> {
+    let mut groups = profiles
+        .values()
+        .flat_map(|profile| profile.tags.iter().cloned())
+        .collect::<Vec<_>>();
+    groups.sort();
+    groups.dedup();
+    groups
+}
+
+/// Run `commands` on one server, giving up on it at the first command that times out as later
+/// responses could not be told apart. What was answered until then is kept.
+async fn run_on(
+    name: String,
+    profile: Profile,
+    commands: Vec<String>,
+    limit: Duration,
+) -> ServerResult {
+    let mut responses = Vec::new();
+    let mut errors = Vec::new();
+    match headless::connect(&profile, limit).await {
+        Ok(mut client) => {
+            for command in commands.iter() {
+                let response = headless::send(&mut client, command, limit).await;
+                let timed_out = response.timed_out;
+                responses.push(response);
+                if timed_out {
+                    break;
+                }
+            }
+        }
+        Err(e) => errors.push(e.to_string()),
+    }
+    errors.extend(responses.iter().filter_map(|r: &Response| r.error.clone()));
+    ServerResult {
+        server: name,
+        address: profile.address,
+        response: responses
+            .iter()
+            .filter_map(|r| r.response.clone())
+            .collect::<String>(),
+        error: errors.into_iter().reduce(|a, b| format!("{}\n{}", a, b)),
+    }
+}
+
+/// Send `command` to every server in `targets` at once, one client per server, waiting at
+/// most `limit` for each response.
+pub async fn broadcast(
+    targets: Vec<(String, Profile)>,
+    command: &str,
+    limit: Duration,
+) -> Vec<ServerResult> {
+    let commands = match cfg::expand(command) {
+        Ok(commands) => commands,
+        Err(e) => {
+            return targets
+                .into_iter()
+                .map(|(name, profile)| ServerResult {
+                    server: name,
+                    address: profile.address,
+                    response: String::new(),
+                    error: Some(e.clone()),
+                })
+                .collect()
+        }
+    };
+    join_all(
+        targets
+            .into_iter()
+            .map(|(name, profile)| run_on(name, profile, commands.clone(), limit)),
+    )
+    .await
+}
+
+fn cell(text: &str, width: usize) -> String {
+    let count = text.chars().count();
+    match count > width {
+        true => text
+            .chars()
+            .take(width.saturating_sub(1))
+            .chain(std::iter::once('…'))
+            .collect(),
+        false => format!("{}{}", text, " ".repeat(width - count)),
+    }
+}
+
+/// Lay the results out in one column per server, fitting into `width` characters.
+pub fn columns(results: &[ServerResult], width: usize) -> Vec<String> {
+    const SEPARATOR: &str = " │ ";
+    if results.is_empty() {
+        return Vec::new();
+    }
+    let gaps = (results.len() - 1) * SEPARATOR.chars().count();
+    let column = (width.saturating_sub(gaps) / results.len()).max(12);
+    let cells = results
+        .iter()
+        .map(|result| {
+            let mut lines = vec![
+                result.server.clone(),
+                match &result.error {
+                    None => "ok".to_string(),
+                    Some(_) => "FAILED".to_string(),
+                },
+                "─".repeat(column),
+            ];
+            lines.extend(result.response.lines().map(str::to_string));
+            if let Some(error) = &result.error {
+                lines.extend(error.lines().map(str::to_string));
+            }
+            lines
+        })
+        .collect::<Vec<_>>();
+    let height = cells.iter().map(Vec::len).max().unwrap_or_default();
+    (0..height)
+        .map(|row| {
+            cells
+                .iter()
+                .map(|lines| cell(lines.get(row).map(String::as_str).unwrap_or(""), column))
+                .collect::<Vec<_>>()
+                .join(SEPARATOR)
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::client::fake_server;
+
+    fn profiles() -> HashMap<String, Profile> {
+        HashMap::from([
+            (
+                "match-2".to_string(),
+                Profile {
+                    address: "10.0.0.3:27015".to_string(),
+                    tags: vec!["event".to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                "match-1".to_string(),
+                Profile {
+                    address: "10.0.0.2:27015".to_string(),
+                    tags: vec!["event".to_string(), "eu".to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                "local".to_string(),
+                Profile {
+                    address: "127.0.0.1:1".to_string(),
+                    ..Default::default()
+                },
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_group() {
+        let names = group(&profiles(), "event")
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["match-1", "match-2"]);
+        assert!(group(&profiles(), "missing").is_empty());
+        assert_eq!(groups(&profiles()), vec!["eu", "event"]);
+    }
+
+    #[test]
+    fn test_columns() {
+        let results = vec![
+            ServerResult {
+                server: "match-1".to_string(),
+                address: "10.0.0.2:27015".to_string(),
+                response: "hostname: one\nmap     : de_dust2\n".to_string(),
+                error: None,
+            },
+            ServerResult {
+                server: "match-2".to_string(),
+                address: "10.0.0.3:27015".to_string(),
+                response: String::new(),
+                error: Some("failed to connect to 10.0.0.3:27015".to_string()),
+            },
+        ];
+        assert_eq!(
+            columns(&results, 35),
+            vec![
+                "match-1          │ match-2",
+                "ok               │ FAILED",
+                "──────────────── │ ────────────────",
+                "hostname: one    │ failed to conne…",
+                "map     : de_du… │",
+            ]
+        );
+        assert!(columns(&[], 80).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_reports_each_server() {
+        let targets = vec![
+            ("a".to_string(), profiles()["local"].clone()),
+            ("b".to_string(), profiles()["local"].clone()),
+        ];
+        let results = broadcast(targets, "status", TIMEOUT).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].server, "a");
+        assert!(results.iter().all(|r| !r.ok()));
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_stops_at_timeout() {
+        let profile = Profile {
+            address: fake_server(2).await,
+            password: "secret".to_string(),
+            ..Default::default()
+        };
+        let commands = ["say one", "say two", "say three", "say four"]
+            .map(str::to_string)
+            .to_vec();
+        // Each command gets the time on its own, the answers before the timeout are kept.
+        let result = run_on(
+            "a".to_string(),
+            profile,
+            commands,
+            Duration::from_millis(200),
+        )
+        .await;
+        assert_eq!(result.response, "say onesay two");
+        assert_eq!(result.error, Some("no response after 0s".to_string()));
+    }
+}
@@ -0,0 +1,129 @@
+use ratatui::{prelude::*, widgets::*};
+use tui_input::{Input, InputRequest};
+
+use crate::{command::Command, popup::Popup, theme::Theme};
+
+/// Popup for searching the command catalog by name and description.
+#[derive(Default)]
+pub struct CatalogBrowser {
+    query: Input,
+    results: Vec<Command>,
+    selected: usize,
+    page_size: usize,
+}
+
+impl CatalogBrowser {
+    pub fn new(query: &str) -> Self {
+        Self {
+            query: Input::new(query.to_string()),
+            results: Vec::new(),
+            selected: 0,
+            page_size: 10,
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        self.query.value()
+    }
+
+    pub fn set_results(&mut self, results: Vec<Command>) {
+        self.results = results;
+        self.selected = 0;
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.results.len().saturating_sub(1));
+    }
+
+    pub fn prev_item(&mut self) {
+        self.select(self.selected.saturating_sub(1));
+    }
+
+    pub fn next_item(&mut self) {
+        self.select(self.selected + 1);
+    }
+
+    pub fn prev_page(&mut self) {
+        self.select(self.selected.saturating_sub(self.page_size));
+    }
+
+    pub fn next_page(&mut self) {
+        self.select(self.selected + self.page_size);
+    }
+
+    /// Name of the highlighted command.
+    pub fn selection(&self) -> Option<String> {
+        self.results.get(self.selected).map(|c| c.name.clone())
+    }
+
+    /// Edit the query, returns the new query if it changed.
+    pub fn edit(&mut self, request: InputRequest) -> Option<String> {
+        match self.query.handle(request) {
+            Some(state) if state.value => Some(self.query().to_string()),
+            _ => None,
+        }
+    }
+
+    pub fn widget(&mut self, area: Rect, theme: &Theme) -> Popup<'_> {
+        // 2 for borders, 2 for the query and 2 for the page indicator
+        self.page_size = (area.height.saturating_sub(6) as usize).max(1);
+        let page = self.selected / self.page_size;
+        let pages = self.results.len().div_ceil(self.page_size).max(1);
+        let width = area.width.saturating_sub(2) as usize;
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("> ", theme.get("hint")),
+                Span::raw(self.query.value()),
+            ]),
+            Line::from(""),
+        ];
+        for (i, command) in self
+            .results
+            .iter()
+            .enumerate()
+            .skip(page * self.page_size)
+            .take(self.page_size)
+        {
+            let style = match i == self.selected {
+                true => theme.get("selected"),
+                false => theme.get("popup"),
+            };
+            let description = command
+                .description
+                .chars()
+                .take(width.saturating_sub(command.name.len() + 3))
+                .collect::<String>();
+            lines.push(Line::from(vec![
+                Span::styled(command.name.as_str(), style.patch(theme.get("title"))),
+                Span::styled(" - ", style.patch(theme.get("hint"))),
+                Span::styled(description, style),
+            ]));
+        }
+        lines.resize(self.page_size + 2, Line::from(""));
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(Span::styled(
+                format!(
+                    "{} results, page {}/{} (Enter to insert, Esc to close)",
+                    self.results.len(),
+                    page + 1,
+                    pages
+                ),
+                theme.get("hint"),
+            ))
+            .alignment(Alignment::Right),
+        );
+
+        Popup::default()
+            .title("Command catalog")
+            .content(lines)
+            .style(theme.get("popup"))
+            .title_style(theme.get("title"))
+            .border_style(theme.get("popup_border"))
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.query.visual_cursor()
+    }
+}
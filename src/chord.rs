@@ -0,0 +1,228 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::KeyEvent;
+
+use crate::action::Action;
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// What a key press turned out to mean once the chord it belongs to is resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolved {
+    /// A binding of the keymap matched.
+    Action(Action),
+    /// The key is not part of any binding and goes to the components as typed.
+    Key(KeyEvent),
+}
+
+/// Matches key presses against multi-key bindings like `<g><g>`.
+///
+/// Keys that may still grow into a longer binding are held back until the next key
+/// arrives or the timeout runs out. When a sequence stops matching, the longest bound
+/// prefix fires and the remaining keys are matched again from the start.
+#[derive(Debug, Clone)]
+pub struct ChordEngine {
+    pending: Vec<KeyEvent>,
+    last_key: Option<Instant>,
+    timeout: Duration,
+}
+
+impl Default for ChordEngine {
+    fn default() -> Self {
+        Self::new(DEFAULT_TIMEOUT)
+    }
+}
+
+impl ChordEngine {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            pending: Vec::new(),
+            last_key: None,
+            timeout,
+        }
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Keys typed so far of a chord that is not complete yet.
+    pub fn pending(&self) -> &[KeyEvent] {
+        &self.pending
+    }
+
+    pub fn feed(
+        &mut self,
+        keymap: &HashMap<Vec<KeyEvent>, Action>,
+        key: KeyEvent,
+        now: Instant,
+    ) -> Vec<Resolved> {
+        let mut resolved = self.expire(keymap, now);
+        self.pending.push(key);
+        self.last_key = Some(now);
+        resolved.extend(self.resolve(keymap, true));
+        resolved
+    }
+
+    /// Give up on the pending chord if no key arrived within the timeout.
+    pub fn expire(
+        &mut self,
+        keymap: &HashMap<Vec<KeyEvent>, Action>,
+        now: Instant,
+    ) -> Vec<Resolved> {
+        match self.last_key {
+            Some(last_key) if !self.pending.is_empty() && now >= last_key + self.timeout => {
+                self.resolve(keymap, false)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Drop the pending keys, e.g. when the keymap changes under them.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.last_key = None;
+    }
+
+    fn resolve(&mut self, keymap: &HashMap<Vec<KeyEvent>, Action>, wait: bool) -> Vec<Resolved> {
+        let mut resolved = Vec::new();
+        while !self.pending.is_empty() {
+            if wait && Self::is_prefix(keymap, &self.pending) {
+                break;
+            }
+            let bound = (1..=self.pending.len())
+                .rev()
+                .find_map(|len| keymap.get(&self.pending[..len]).map(|a| (len, a.clone())));
+            match bound {
+                Some((len, action)) => {
+                    self.pending.drain(..len);
+                    resolved.push(Resolved::Action(action));
+                }
+                None => resolved.push(Resolved::Key(self.pending.remove(0))),
+            }
+        }
+        resolved
+    }
+
+    /// Whether `keys` is the start of a longer binding.
+    fn is_prefix(keymap: &HashMap<Vec<KeyEvent>, Action>, keys: &[KeyEvent]) -> bool {
+        keymap
+            .keys()
+            .any(|bound| bound.len() > keys.len() && bound.starts_with(keys))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::parse_key_sequence;
+
+    fn keymap(bindings: &[(&str, Action)]) -> HashMap<Vec<KeyEvent>, Action> {
+        bindings
+            .iter()
+            .map(|(keys, action)| (parse_key_sequence(keys).unwrap(), action.clone()))
+            .collect()
+    }
+
+    fn key(raw: &str) -> KeyEvent {
+        parse_key_sequence(raw).unwrap()[0]
+    }
+
+    #[test]
+    fn test_single_and_unbound_keys() {
+        let keymap = keymap(&[("<q>", Action::Quit)]);
+        let mut chords = ChordEngine::default();
+        let now = Instant::now();
+        assert_eq!(
+            chords.feed(&keymap, key("<q>"), now),
+            vec![Resolved::Action(Action::Quit)]
+        );
+        assert_eq!(
+            chords.feed(&keymap, key("<x>"), now),
+            vec![Resolved::Key(key("<x>"))]
+        );
+        assert!(chords.pending().is_empty());
+    }
+
+    #[test]
+    fn test_chord_is_independent_of_ticks() {
+        let keymap = keymap(&[("<g><g>", Action::Refresh)]);
+        let mut chords = ChordEngine::default();
+        let now = Instant::now();
+        assert_eq!(chords.feed(&keymap, key("<g>"), now), vec![]);
+        assert_eq!(chords.pending(), &[key("<g>")]);
+        assert_eq!(
+            chords.feed(&keymap, key("<g>"), now + Duration::from_millis(900)),
+            vec![Resolved::Action(Action::Refresh)]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_bindings_wait_for_next_key() {
+        let keymap = keymap(&[
+            ("<g>", Action::Help),
+            ("<g><g>", Action::Refresh),
+            ("<g><d>", Action::Quit),
+        ]);
+        let mut chords = ChordEngine::default();
+        let now = Instant::now();
+        assert_eq!(chords.feed(&keymap, key("<g>"), now), vec![]);
+        assert_eq!(
+            chords.feed(&keymap, key("<d>"), now),
+            vec![Resolved::Action(Action::Quit)]
+        );
+        // A key that continues no binding fires the shorter one and is matched on its own.
+        assert_eq!(chords.feed(&keymap, key("<g>"), now), vec![]);
+        assert_eq!(
+            chords.feed(&keymap, key("<x>"), now),
+            vec![Resolved::Action(Action::Help), Resolved::Key(key("<x>"))]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_binding_fires_after_timeout() {
+        let keymap = keymap(&[("<g>", Action::Help), ("<g><g>", Action::Refresh)]);
+        let mut chords = ChordEngine::new(Duration::from_millis(500));
+        let now = Instant::now();
+        chords.feed(&keymap, key("<g>"), now);
+        assert_eq!(
+            chords.expire(&keymap, now + Duration::from_millis(499)),
+            vec![]
+        );
+        assert_eq!(
+            chords.expire(&keymap, now + Duration::from_millis(500)),
+            vec![Resolved::Action(Action::Help)]
+        );
+        assert!(chords.pending().is_empty());
+        // The next key after a timeout starts a new chord.
+        chords.feed(&keymap, key("<g>"), now + Duration::from_secs(2));
+        assert_eq!(chords.pending(), &[key("<g>")]);
+    }
+
+    #[test]
+    fn test_broken_chord_replays_keys() {
+        let keymap = keymap(&[("<j><k>", Action::EnterNormal), ("<k>", Action::Help)]);
+        let mut chords = ChordEngine::default();
+        let now = Instant::now();
+        assert_eq!(chords.feed(&keymap, key("<j>"), now), vec![]);
+        assert_eq!(
+            chords.feed(&keymap, key("<j>"), now),
+            vec![Resolved::Key(key("<j>"))]
+        );
+        assert_eq!(
+            chords.feed(&keymap, key("<x>"), now),
+            vec![Resolved::Key(key("<j>")), Resolved::Key(key("<x>"))]
+        );
+        // Timing out without any bound prefix hands the keys back as typed.
+        chords.feed(&keymap, key("<j>"), now);
+        assert_eq!(
+            chords.expire(&keymap, now + DEFAULT_TIMEOUT),
+            vec![Resolved::Key(key("<j>"))]
+        );
+    }
+}
@@ -1,6 +1,6 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 pub struct Cli {
@@ -21,4 +21,101 @@ pub struct Cli {
         default_value_t = 4.0
     )]
     pub frame_rate: f64,
+
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "Config file layered on top of the system and user config"
+    )]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+/// Run against a server without the terminal UI, printing responses to stdout.
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Send a single command
+    Run {
+        #[command(flatten)]
+        server: ServerArgs,
+        /// The command to send, e.g. "mp_restartgame 1"
+        #[arg(required = true)]
+        command: Vec<String>,
+    },
+    /// Send every command in cfg/<FILE>.cfg
+    Exec {
+        #[command(flatten)]
+        server: ServerArgs,
+        file: String,
+    },
+    /// Print the server status
+    Status {
+        #[command(flatten)]
+        server: ServerArgs,
+    },
+    /// Read commands line by line from stdin, or interactively from the terminal
+    Repl {
+        #[command(flatten)]
+        server: ServerArgs,
+    },
+    /// Send a command to every profile tagged with GROUP at once
+    Broadcast {
+        group: String,
+        /// The command to send, e.g. "exec warmup"
+        #[arg(required = true)]
+        command: Vec<String>,
+        #[arg(long, help = "Print the results as json")]
+        json: bool,
+        #[arg(
+            long,
+            help = "Send commands validation asks to confirm, blocked ones are never sent"
+        )]
+        force: bool,
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            help = "Give up on connecting to a server or on a response after this long",
+            default_value_t = 5
+        )]
+        timeout: u64,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ServerArgs {
+    #[arg(short, long, help = "Saved profile to connect to")]
+    pub profile: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        value_name = "IP:PORT",
+        conflicts_with = "profile",
+        required_unless_present = "profile",
+        help = "Address to connect to instead of a profile"
+    )]
+    pub address: Option<String>,
+
+    #[arg(long, help = "Password when connecting by address")]
+    pub password: Option<String>,
+
+    #[arg(long, help = "Print the result as json")]
+    pub json: bool,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Give up on connecting or on a response after this long",
+        default_value_t = 5
+    )]
+    pub timeout: u64,
+}
+
+impl ServerArgs {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout)
+    }
 }
@@ -1,37 +1,48 @@
-use std::path::Path;
+use std::{
+    io::ErrorKind,
+    sync::{Arc, Mutex},
+};
 
-use futures::future::ok;
-use ratatui::{prelude::*, widgets::*};
 use rcon::{AsyncStdStream, Connection, Error};
 use tokio::{
-    sync::mpsc::UnboundedSender,
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
     time::{timeout, Duration},
 };
 
-use crate::{action::Action, command::status::Status};
+use crate::{
+    action::Action,
+    command::{cfg, convar, status::Status},
+};
 use log::{error, info};
 
+/// How long a command may take unless the client is told otherwise.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What the client knows of its server, shared with the [`ClientTask`] running it.
+#[derive(Debug, Default)]
+struct Shared {
+    address: String,
+    status: Status,
+}
+
 pub struct Client {
     connection: Option<Connection<AsyncStdStream>>,
-    address: String,
+    shared: Arc<Mutex<Shared>>,
     password: String,
     action_tx: Option<UnboundedSender<Action>>,
+    /// How long to wait for the server to connect or answer a command.
+    timeout: Duration,
     status_rate: usize,
     ticks: usize,
-    status: Status,
 }
 
 impl Client {
     pub async fn new(address: &str, password: &str) -> Self {
-        Self {
-            connection: None,
-            address: address.to_string(),
-            password: password.to_string(),
-            action_tx: None,
-            status_rate: 20,
-            ticks: 0,
-            status: Status::default(),
-        }
+        let mut client = Self::default();
+        client.set_address(address);
+        client.set_password(password);
+        client
     }
 
     pub fn register_action_handler(
@@ -43,17 +54,31 @@ impl Client {
     }
 
     pub fn set_address(&mut self, address: &str) {
-        self.address = address.to_string();
+        self.shared.lock().unwrap().address = address.to_string();
+    }
+
+    pub fn address(&self) -> String {
+        self.shared.lock().unwrap().address.clone()
+    }
+
+    /// What the last `status` said, polled every few ticks while connected.
+    pub fn status(&self) -> Status {
+        self.shared.lock().unwrap().status.clone()
     }
 
     pub fn set_password(&mut self, password: &str) {
         self.password = password.to_string();
     }
 
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
     pub async fn connect(&mut self) -> Result<(), Error> {
+        let address = self.address();
         self.connection = Some(
             <Connection<AsyncStdStream>>::builder()
-                .connect(&self.address, &self.password)
+                .connect(&address, &self.password)
                 .await?,
         );
         match self.is_connected() {
@@ -66,46 +91,91 @@ impl Client {
         self.connection.is_some()
     }
 
-    async fn send_command(&mut self, command: &str) -> Result<String, Error> {
-        match self.connection.as_mut() {
-            Some(connection) => {
-                log::info!("Sending command: {}", command);
-                let response = connection.cmd(command).await?;
-                log::info!("Response: {}", response);
-                self.send_action(Action::Insert(response.clone()));
-                Ok(response)
+    /// Send `command` and wait for the answer for as long as the timeout allows. A server that
+    /// does not answer in time is disconnected, as its late answer would otherwise be taken
+    /// for the answer to the next command.
+    async fn cmd(&mut self, command: &str) -> Result<String, Error> {
+        let Some(connection) = self.connection.as_mut() else {
+            return Err(Error::Io(std::io::Error::other("Not connected")));
+        };
+        match timeout(self.timeout, connection.cmd(command)).await {
+            Ok(response) => response,
+            Err(_) => {
+                self.connection = None;
+                self.send_action(Action::Connected(false));
+                Err(Error::Io(std::io::Error::new(
+                    ErrorKind::TimedOut,
+                    format!("No response after {}s", self.timeout.as_secs()),
+                )))
             }
-            None => {
-                if self.address.is_empty() {
-                    self.error("No address specified".to_owned()).await?;
-                    return Err(Error::Auth);
-                }
-                if self.password.is_empty() {
-                    self.error("No password specified".to_owned()).await?;
-                    Err(Error::Auth)
-                } else {
-                    self.error("Not connected".to_owned()).await?;
-                    Err(Error::Io(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Not connected",
-                    )))
+        }
+    }
+
+    pub async fn send_command(&mut self, command: &str) -> Result<String, Error> {
+        if self.connection.is_some() {
+            log::info!("Sending command: {}", command);
+            let response = match self.cmd(command).await {
+                Ok(response) => response,
+                Err(e) => {
+                    self.error(format!("Failed to send {}: {:?}", command, e))
+                        .await?;
+                    return Err(e);
                 }
-            }
+            };
+            log::info!("Response: {}", response);
+            self.send_action(Action::Insert(response.clone()));
+            return Ok(response);
+        }
+        if self.address().is_empty() {
+            self.error("No address specified".to_owned()).await?;
+            return Err(Error::Auth);
+        }
+        if self.password.is_empty() {
+            self.error("No password specified".to_owned()).await?;
+            Err(Error::Auth)
+        } else {
+            self.error("Not connected".to_owned()).await?;
+            Err(Error::Io(std::io::Error::other("Not connected")))
         }
     }
 
     async fn run_file(&mut self, file: &str) -> Result<Vec<String>, Error> {
-        let path = format!("cfg/{}.cfg", file);
+        let path = cfg::path(file);
         let contents = tokio::fs::read_to_string(path).await?;
+        self.run_lines(&contents).await
+    }
+
+    /// Send every command in a cfg formatted block, one line at a time.
+    async fn run_lines(&mut self, contents: &str) -> Result<Vec<String>, Error> {
         let mut responses = Vec::new();
-        for command in contents.split('\n') {
-            let response = self.send_command(command).await?;
+        for command in cfg::commands(contents) {
+            let response = self.send_command(&command).await?;
             log::info!("command {}:\n{}", command, response);
             responses.push(response);
         }
         Ok(responses)
     }
 
+    async fn connect_to(&mut self, address: &str, password: &str) -> Result<(), Error> {
+        log::info!("Connecting to {}", address);
+        self.set_address(address);
+        self.set_password(password);
+        match timeout(self.timeout, self.connect()).await {
+            Ok(Ok(_)) => {
+                self.send_action(Action::Connected(true));
+            }
+            Ok(Err(e)) => {
+                self.error(format!("Failed to connect: {:?}", e)).await?;
+            }
+            Err(_) => {
+                self.connection = None;
+                self.error(format!("Timed out connecting to {}", address))
+                    .await?;
+            }
+        };
+        Ok(())
+    }
+
     pub async fn run_command(&mut self, command: &str) -> Result<(), Error> {
         match command.split(' ').collect::<Vec<&str>>().first() {
             Some(&"connect") => {
@@ -124,17 +194,7 @@ impl Client {
                         return Ok(());
                     }
                 };
-                log::info!("Connecting to {}", address);
-                self.set_address(address);
-                self.set_password(password);
-                match self.connect().await {
-                    Ok(_) => {
-                        self.send_action(Action::Connected(true));
-                    }
-                    Err(e) => {
-                        self.error(format!("Failed to connect: {:?}", e)).await?;
-                    }
-                };
+                self.connect_to(address, password).await?;
             }
             Some(&"disconnect") => {
                 log::info!("Disconnecting");
@@ -159,30 +219,51 @@ impl Client {
         Ok(())
     }
 
+    /// Send a command the client needs for itself, without echoing the response to the output.
+    pub async fn send_quiet(&mut self, command: &str) -> Result<String, Error> {
+        self.cmd(command).await
+    }
+
+    /// Query the current value of a convar without echoing the response to the output.
+    async fn query_convar(&mut self, name: &str) {
+        if !self.is_connected() {
+            return;
+        }
+        match self.cmd(name).await {
+            Ok(response) => match convar::parse_response(name, &response) {
+                Some((value, default)) => {
+                    self.send_action(Action::ConvarValue(name.to_string(), value, default));
+                }
+                None => info!("Unable to parse value of {}: {}", name, response),
+            },
+            Err(e) => error!("Failed to query {}: {:?}", name, e),
+        }
+    }
+
     pub async fn async_update(&mut self, action: Action) {
         match action {
-            Action::Command(command) => {
+            Action::SendCommand(command) => {
                 let _ = self.run_command(&command).await;
             }
+            Action::SendBlock(contents) => {
+                let _ = self.run_lines(&contents).await;
+            }
+            Action::Connect(address, password) => {
+                let _ = self.connect_to(&address, &password).await;
+            }
+            Action::QueryConvar(name) => {
+                self.query_convar(&name).await;
+            }
             Action::Tick => {
                 self.ticks += 1;
                 info!("Ticks: {}", self.ticks);
-                if self.ticks % self.status_rate == 0 {
-                    info!("Updating status");
-                    if let Some(connection) = self.connection.as_mut() {
-                        info!("Sending status command");
-                        match timeout(Duration::from_secs(5), connection.cmd("status")).await {
-                            Ok(status) => match status {
-                                Ok(status) => {
-                                    self.status.update(status);
-                                }
-                                Err(_) => {
-                                    self.connection = None;
-                                    self.send_action(Action::Connected(false));
-                                }
-                            },
-                            Err(_) => {
-                                self.connection = None;
+                if self.ticks.is_multiple_of(self.status_rate) && self.is_connected() {
+                    info!("Polling status");
+                    match self.cmd("status").await {
+                        Ok(status) => self.shared.lock().unwrap().status.update(status),
+                        Err(e) => {
+                            error!("Failed to poll status: {:?}", e);
+                            if self.connection.take().is_some() {
                                 self.send_action(Action::Connected(false));
                             }
                         }
@@ -213,12 +294,198 @@ impl Default for Client {
     fn default() -> Self {
         Self {
             connection: None,
-            address: String::new(),
+            shared: Arc::new(Mutex::new(Shared::default())),
             password: String::new(),
             action_tx: None,
+            timeout: TIMEOUT,
             status_rate: 20,
             ticks: 0,
-            status: Status::default(),
         }
     }
 }
+
+/// Work for a [`ClientTask`], done in the order it was handed in.
+enum Job {
+    Update(Action),
+    /// A command sent with [`Client::send_quiet`], and what it is for should it fail.
+    Quiet(String, &'static str),
+}
+
+/// A client running on a task of its own, so a server that is slow to answer holds up neither
+/// the interface nor the other tabs. Everything the client has to say comes back as actions.
+pub struct ClientTask {
+    jobs: Option<UnboundedSender<Job>>,
+    /// The client and its queue of jobs, until the task is started on first use.
+    idle: Option<(Client, UnboundedReceiver<Job>)>,
+    task: Option<JoinHandle<()>>,
+    shared: Arc<Mutex<Shared>>,
+    action_tx: Option<UnboundedSender<Action>>,
+}
+
+impl ClientTask {
+    pub fn new(client: Client) -> Self {
+        let (jobs, rx) = mpsc::unbounded_channel();
+        Self {
+            jobs: Some(jobs),
+            shared: client.shared.clone(),
+            action_tx: client.action_tx.clone(),
+            idle: Some((client, rx)),
+            task: None,
+        }
+    }
+
+    pub fn address(&self) -> String {
+        self.shared.lock().unwrap().address.clone()
+    }
+
+    /// What the last `status` said, polled every few ticks while connected.
+    pub fn status(&self) -> Status {
+        self.shared.lock().unwrap().status.clone()
+    }
+
+    /// Hand `action` to the client, see [`Client::async_update`].
+    pub fn update(&mut self, action: Action) {
+        self.submit(Job::Update(action));
+    }
+
+    /// Send a command the client needs for itself, reporting an error saying `what` it was
+    /// for if it fails.
+    pub fn send_quiet(&mut self, command: &str, what: &'static str) {
+        self.submit(Job::Quiet(command.to_string(), what));
+    }
+
+    pub fn send_action(&mut self, action: Action) {
+        if let Some(sender) = &self.action_tx {
+            if let Err(e) = sender.send(action) {
+                error!("Failed to send action: {:?}", e);
+            }
+        }
+    }
+
+    fn submit(&mut self, job: Job) {
+        if let Some((client, jobs)) = self.idle.take() {
+            self.task = Some(tokio::spawn(Self::run(client, jobs)));
+        }
+        if let Some(jobs) = &self.jobs {
+            // The task only ends once the sender is gone.
+            let _ = jobs.send(job);
+        }
+    }
+
+    async fn run(mut client: Client, mut jobs: UnboundedReceiver<Job>) {
+        while let Some(job) = jobs.recv().await {
+            match job {
+                Job::Update(action) => client.async_update(action).await,
+                Job::Quiet(command, what) => {
+                    if let Err(e) = client.send_quiet(&command).await {
+                        let _ = client.error(format!("Failed to {}: {:?}", what, e)).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Let the client finish what it was handed so far, waiting at most `limit`.
+    pub async fn close(&mut self, limit: Duration) {
+        self.jobs = None;
+        if let Some(task) = self.task.take() {
+            if timeout(limit, task).await.is_err() {
+                log::warn!("Gave up waiting for {} to finish", self.address());
+            }
+        }
+    }
+}
+
+/// A server that lets anyone in, echoes the first `answers` commands and then never answers
+/// again.
+#[cfg(test)]
+pub(crate) async fn fake_server(answers: usize) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let (mut answered, mut answering) = (0, false);
+        loop {
+            let mut length = [0u8; 4];
+            if stream.read_exact(&mut length).await.is_err() {
+                return;
+            }
+            let mut packet = vec![0u8; i32::from_le_bytes(length) as usize];
+            stream.read_exact(&mut packet).await.unwrap();
+            let (id, kind, body) = (&packet[..4], packet[4], &packet[8..packet.len() - 2]);
+            // The auth request is answered with its id, commands are followed by an empty one
+            // marking the end of the response.
+            let (kind, body) = match (kind, body.is_empty()) {
+                (3, _) => (2i32, &[][..]),
+                (_, false) => {
+                    answering = answered < answers;
+                    if !answering {
+                        continue;
+                    }
+                    answered += 1;
+                    (0, body)
+                }
+                (_, true) if answering => (0, body),
+                _ => continue,
+            };
+            let mut response = ((10 + body.len()) as i32).to_le_bytes().to_vec();
+            response.extend_from_slice(id);
+            response.extend_from_slice(&kind.to_le_bytes());
+            response.extend_from_slice(body);
+            response.extend_from_slice(&[0, 0]);
+            stream.write_all(&response).await.unwrap();
+        }
+    });
+    address
+}
+
+/// A server that lets anyone in and then never answers a command.
+#[cfg(test)]
+pub(crate) async fn silent_server() -> String {
+    fake_server(0).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_timed_out_command_disconnects() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut client = Client::default();
+        client.register_action_handler(tx).unwrap();
+        client.set_address(&silent_server().await);
+        client.set_password("secret");
+        client.set_timeout(Duration::from_millis(200));
+        client.connect().await.unwrap();
+        let e = client.send_command("status").await.unwrap_err();
+        assert!(matches!(e, Error::Io(e) if e.kind() == ErrorKind::TimedOut));
+        // A late answer must not be read as the answer to the next command.
+        assert!(!client.is_connected());
+        assert_eq!(rx.recv().await, Some(Action::Connected(false)));
+    }
+
+    #[tokio::test]
+    async fn test_task_does_not_block() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut client = Client::default();
+        client.register_action_handler(tx).unwrap();
+        client.set_password("secret");
+        client.set_timeout(Duration::from_millis(200));
+        let mut task = ClientTask::new(client);
+        let address = silent_server().await;
+        task.update(Action::Connect(address.clone(), "secret".to_string()));
+        assert_eq!(rx.recv().await, Some(Action::Connected(true)));
+        assert_eq!(task.address(), address);
+        // The server says nothing, the task gives up on it without anyone waiting.
+        task.update(Action::SendCommand("status".to_string()));
+        task.update(Action::QueryConvar("sv_cheats".to_string()));
+        task.send_quiet("log on", "turn logging on");
+        assert_eq!(rx.recv().await, Some(Action::Connected(false)));
+        assert!(matches!(rx.recv().await, Some(Action::Error(_))));
+        task.close(Duration::from_secs(1)).await;
+        assert!(task.task.is_none());
+    }
+}
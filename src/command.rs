@@ -1,5 +1,6 @@
 use ratatui::{
     style::Color,
+    text::{Line, Text},
     widgets::{Block, Borders, Paragraph},
 };
 use tokio::{
@@ -8,29 +9,58 @@ use tokio::{
 };
 
 pub mod autocompleter;
+pub mod builtin;
+pub mod cfg;
+pub mod convar;
 pub mod history;
+pub mod logs;
 pub mod status;
-use crate::popup::Popup;
+pub mod validator;
+use crate::{popup::Popup, theme::Theme};
+
+use self::convar::ConvarValue;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CommandKind {
+    #[default]
+    Command,
+    Convar,
+    Builtin,
+}
 
 #[derive(Default, Clone)]
 pub struct Command {
     pub name: String,
     pub description: String,
     pub flags: Vec<String>,
+    pub kind: CommandKind,
 }
 
 impl Command {
-    pub fn new(name: &str, description: &str, flags: Vec<String>) -> Self {
+    pub fn new(name: &str, description: &str, flags: Vec<String>, kind: CommandKind) -> Self {
         Self {
             name: name.to_string(),
             description: description.to_string(),
             flags,
+            kind,
         }
     }
 
-    pub fn widget(&mut self) -> Popup<'_> {
+    pub fn is_convar(&self) -> bool {
+        self.kind == CommandKind::Convar
+    }
+
+    pub fn widget(&mut self, value: Option<&ConvarValue>, theme: &Theme) -> Popup<'_> {
+        let mut content = Text::default();
+        if let Some(value) = value {
+            content.lines.push(value.line(theme));
+        }
+        content.lines.push(Line::from(self.description.as_str()));
         Popup::default()
             .title(self.name.as_str())
-            .content(self.description.as_str())
+            .content(content)
+            .style(theme.get("popup"))
+            .title_style(theme.get("title"))
+            .border_style(theme.get("popup_border"))
     }
 }
@@ -1,4 +1,4 @@
-use crate::command::Command;
+use crate::command::{builtin::BUILTINS, Command, CommandKind};
 use std::{
     fs::File,
     io::{BufRead, BufReader},
@@ -20,15 +20,15 @@ impl AutoCompleter {
         self.commands.push(command);
     }
 
-    pub fn load_commands(&mut self, path: &str) {
+    pub fn load_commands(&mut self, path: &str, kind: CommandKind) {
         let file = File::open(path).unwrap();
         let lines = BufReader::new(file).lines();
-        for line in lines.into_iter().flatten() {
+        for line in lines.map_while(Result::ok) {
             let command: Vec<&str> = line.split('$').collect();
             let name = command[0];
             let description = command[1];
             if command.len() < 3 {
-                self.add_command(Command::new(name, description, Vec::new()));
+                self.add_command(Command::new(name, description, Vec::new(), kind));
                 continue;
             }
             let flags = command[2]
@@ -36,10 +36,24 @@ impl AutoCompleter {
                 .split(' ')
                 .map(|e| e.to_string())
                 .collect::<Vec<String>>();
-            self.add_command(Command::new(name, description, flags));
+            self.add_command(Command::new(name, description, flags, kind));
         }
     }
 
+    /// Load the bundled command and convar catalog.
+    pub fn load_catalog(&mut self) {
+        for builtin in BUILTINS.iter() {
+            self.add_command(Command::new(
+                builtin.name,
+                &format!("{} - {}", builtin.usage, builtin.description),
+                Vec::new(),
+                CommandKind::Builtin,
+            ));
+        }
+        self.load_commands(".config/commands.txt", CommandKind::Command);
+        self.load_commands(".config/convars.txt", CommandKind::Convar);
+    }
+
     pub fn get_suggestions(&self, partial_command: &str, count: usize) -> Vec<String> {
         let mut completions = Vec::new();
         for command in self.commands.iter() {
@@ -70,6 +84,53 @@ impl AutoCompleter {
         None
     }
 
+    /// Search the catalog for every word in `query`, ranking name matches above description matches.
+    pub fn search(&self, query: &str) -> Vec<Command> {
+        let words = query
+            .split_whitespace()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return Vec::new();
+        }
+        let mut results = self
+            .commands
+            .iter()
+            .filter_map(|command| {
+                let name = command.name.to_lowercase();
+                let description = command.description.to_lowercase();
+                let mut matched = 0;
+                let mut score = 0;
+                for word in words.iter() {
+                    let name_score = match name.as_str() {
+                        n if n == word => 8,
+                        n if n.starts_with(word.as_str()) => 5,
+                        n if n.contains(word.as_str()) => 3,
+                        _ => 0,
+                    };
+                    let description_score = match description.contains(word.as_str()) {
+                        true => 1,
+                        false => 0,
+                    };
+                    if name_score + description_score > 0 {
+                        matched += 1;
+                        score += name_score + description_score;
+                    }
+                }
+                (matched > 0).then_some((matched, score, command))
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then(b.1.cmp(&a.1))
+                .then(a.2.name.cmp(&b.2.name))
+        });
+        results
+            .into_iter()
+            .map(|(_, _, command)| command.clone())
+            .collect()
+    }
+
     pub fn get_command(&self, name: &str) -> Option<Command> {
         for command in self.commands.iter() {
             if command.name == name {
@@ -85,3 +146,44 @@ impl Default for AutoCompleter {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_search_ranking() {
+        let mut completer = AutoCompleter::new();
+        completer.add_command(Command::new(
+            "mp_buy_anywhere",
+            "When set, players can buy anywhere, not only in buyzones.",
+            Vec::new(),
+            CommandKind::Convar,
+        ));
+        completer.add_command(Command::new(
+            "mp_buytime",
+            "How many seconds after round start players can buy items for.",
+            Vec::new(),
+            CommandKind::Convar,
+        ));
+        completer.add_command(Command::new(
+            "mp_roundtime",
+            "How many minutes each round takes.",
+            Vec::new(),
+            CommandKind::Convar,
+        ));
+        let names = completer
+            .search("buy time")
+            .into_iter()
+            .map(|c| c.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["mp_buytime", "mp_buy_anywhere", "mp_roundtime"]);
+    }
+
+    #[test]
+    fn test_search_empty() {
+        assert!(AutoCompleter::new().search("  ").is_empty());
+    }
+}
@@ -0,0 +1,49 @@
+/// A command handled by the client itself rather than sent to the server.
+pub struct Builtin {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+}
+
+pub const BUILTINS: [Builtin; 8] = [
+    Builtin {
+        name: "connect",
+        usage: "connect <ip>:<port> [password]",
+        description: "Connect to a server",
+    },
+    Builtin {
+        name: "disconnect",
+        usage: "disconnect",
+        description: "Close the connection to the server",
+    },
+    Builtin {
+        name: "exec",
+        usage: "exec <file>",
+        description: "Send every command in cfg/<file>.cfg",
+    },
+    Builtin {
+        name: "broadcast",
+        usage: "broadcast <group> <command>",
+        description: "Send a command to every server tagged with the group",
+    },
+    Builtin {
+        name: "match",
+        usage: "match [start <type>|load <file>|ready|stay|switch|phase <phase>|stop]",
+        description: "Run a match through its phases, or show where it is at",
+    },
+    Builtin {
+        name: "report",
+        usage: "report",
+        description: "Write the teams, score, rounds and player stats of the match as JSON and CSV",
+    },
+    Builtin {
+        name: "apropos",
+        usage: "apropos <words>",
+        description: "Search the command catalog by name and description",
+    },
+    Builtin {
+        name: "theme",
+        usage: "theme [name]",
+        description: "Switch theme, or list the available themes",
+    },
+];
@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+
+/// Directory `exec` reads cfg files from.
+pub const CFG_DIR: &str = "cfg";
+
+pub fn path(name: &str) -> PathBuf {
+    PathBuf::from(CFG_DIR).join(format!("{}.cfg", name))
+}
+
+/// Names of the cfg files that can be run with `exec`, sorted.
+pub fn files() -> Vec<String> {
+    let mut names = std::fs::read_dir(CFG_DIR)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|e| e == "cfg"))
+                .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Split the contents of a cfg file (or a pasted block) into the commands it contains.
+///
+/// Strips `//` comments and blank lines, and splits `;` separated commands on
+/// a line, leaving anything inside quotes untouched.
+pub fn commands(contents: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    for line in contents.lines() {
+        let mut current = String::new();
+        let mut quoted = false;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    quoted = !quoted;
+                    current.push(c);
+                }
+                '/' if !quoted && chars.peek() == Some(&'/') => break,
+                ';' if !quoted => commands.push(std::mem::take(&mut current)),
+                c => current.push(c),
+            }
+        }
+        commands.push(current);
+    }
+    commands
+        .into_iter()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+/// The commands `command` stands for, reading the file of an `exec <file>` like the
+/// client does.
+pub fn expand(command: &str) -> Result<Vec<String>, String> {
+    match command.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["exec", file] => std::fs::read_to_string(path(file))
+            .map(|contents| commands(&contents))
+            .map_err(|e| format!("{}: {}", path(file).display(), e)),
+        _ => Ok(commands(command)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_commands() {
+        let contents = "\n// warmup\nmp_warmup_start\nbot_kick; bot_quota 0 // no bots\r\n\n";
+        assert_eq!(
+            commands(contents),
+            vec!["mp_warmup_start", "bot_kick", "bot_quota 0"]
+        );
+    }
+
+    #[test]
+    fn test_files() {
+        let files = files();
+        assert!(files.contains(&"warmup".to_string()));
+        assert!(files.contains(&"wingman_live".to_string()));
+        assert!(files.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_commands_quoted() {
+        assert_eq!(
+            commands("hostname \"pug; //1\"; sv_password \"\""),
+            vec!["hostname \"pug; //1\"", "sv_password \"\""]
+        );
+    }
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(
+            expand("sv_password x; mp_restartgame 1").unwrap(),
+            vec!["sv_password x", "mp_restartgame 1"]
+        );
+        assert!(expand("exec warmup")
+            .unwrap()
+            .contains(&"mp_warmup_start".to_string()));
+        assert!(expand("exec missing").is_err());
+    }
+}
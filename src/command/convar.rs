@@ -0,0 +1,219 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use ratatui::prelude::*;
+
+use crate::theme::Theme;
+
+const DEBOUNCE: Duration = Duration::from_millis(500);
+const TTL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct ConvarValue {
+    pub value: String,
+    pub default: Option<String>,
+    fetched: Instant,
+    invalidated: bool,
+}
+
+impl ConvarValue {
+    pub fn new(value: String, default: Option<String>) -> Self {
+        Self {
+            value,
+            default,
+            fetched: Instant::now(),
+            invalidated: false,
+        }
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.invalidated || self.fetched.elapsed() >= TTL
+    }
+
+    pub fn line(&self, theme: &Theme) -> Line<'static> {
+        let mut text = format!("current = {}", self.value);
+        if let Some(default) = &self.default {
+            text.push_str(&format!(" (default {})", default));
+        }
+        match self.is_stale() {
+            true => Line::from(vec![
+                Span::styled(text, theme.get("stale")),
+                Span::styled(" (stale)", theme.get("stale")),
+            ]),
+            false => Line::from(Span::styled(text, theme.get("value"))),
+        }
+    }
+}
+
+/// Caches convar values queried from the server while the user is typing.
+///
+/// Queries are debounced so that the server is only asked once the input has
+/// settled on a convar name, and cached values are refetched once they go stale.
+#[derive(Debug)]
+pub struct ConvarCache {
+    values: HashMap<String, ConvarValue>,
+    watching: Option<String>,
+    changed: Instant,
+    requested: Option<(String, Instant)>,
+}
+
+impl ConvarCache {
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+            watching: None,
+            changed: Instant::now(),
+            requested: None,
+        }
+    }
+
+    /// Set the convar currently under the cursor, restarting the debounce timer when it changes.
+    pub fn watch(&mut self, name: Option<&str>) {
+        if self.watching.as_deref() != name {
+            self.watching = name.map(|n| n.to_string());
+            self.changed = Instant::now();
+        }
+    }
+
+    /// Returns the name of the watched convar if it should be queried now.
+    pub fn due(&mut self) -> Option<String> {
+        let name = self.watching.clone()?;
+        if self.changed.elapsed() < DEBOUNCE {
+            return None;
+        }
+        if let Some(value) = self.values.get(&name) {
+            if !value.is_stale() {
+                return None;
+            }
+        }
+        if let Some((requested, at)) = &self.requested {
+            if *requested == name && at.elapsed() < TTL {
+                return None;
+            }
+        }
+        self.requested = Some((name.clone(), Instant::now()));
+        Some(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConvarValue> {
+        self.values.get(name)
+    }
+
+    pub fn insert(&mut self, name: String, value: String, default: Option<String>) {
+        if matches!(&self.requested, Some((requested, _)) if *requested == name) {
+            self.requested = None;
+        }
+        self.values.insert(name, ConvarValue::new(value, default));
+    }
+
+    pub fn invalidate(&mut self, name: &str) {
+        if let Some(value) = self.values.get_mut(name) {
+            value.invalidated = true;
+        }
+        self.requested = None;
+    }
+
+    pub fn invalidate_all(&mut self) {
+        for value in self.values.values_mut() {
+            value.invalidated = true;
+        }
+        self.requested = None;
+    }
+}
+
+impl Default for ConvarCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse the response of querying a convar by name.
+///
+/// Handles both the CS2 format (`mp_roundtime = 1.92`) and the classic Source
+/// format (`"mp_roundtime" = "1.92" ( def. "1.75" ) min. 1.000000 ...`).
+pub fn parse_response(name: &str, response: &str) -> Option<(String, Option<String>)> {
+    for line in response.lines() {
+        let line = line.trim();
+        let rest = if let Some(rest) = line.strip_prefix(&format!("\"{}\"", name)) {
+            rest
+        } else if let Some(rest) = line.strip_prefix(name) {
+            rest
+        } else {
+            continue;
+        };
+        let Some(rest) = rest.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let value = match rest.strip_prefix('"') {
+            Some(quoted) => quoted.split('"').next().unwrap_or_default().to_string(),
+            None => rest
+                .split(" ( def.")
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        };
+        let default = rest
+            .split("def. ")
+            .nth(1)
+            .map(|d| d.trim_start_matches('"'))
+            .and_then(|d| d.split('"').next())
+            .map(|d| d.to_string());
+        return Some((value, default));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_response_cs2() {
+        assert_eq!(
+            parse_response("mp_roundtime", "mp_roundtime = 1.92"),
+            Some(("1.92".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn test_parse_response_source() {
+        assert_eq!(
+            parse_response(
+                "mp_roundtime",
+                "\"mp_roundtime\" = \"1.92\" ( def. \"1.75\" ) min. 1.000000 max. 60.000000\n game replicated\n - How many minutes each round takes."
+            ),
+            Some(("1.92".to_string(), Some("1.75".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_parse_response_prefix_name() {
+        assert_eq!(
+            parse_response("mp_roundtime", "mp_roundtime_defuse = 0"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_response_unknown() {
+        assert_eq!(
+            parse_response("mp_roundtime", "Unknown command 'mp_roundtime'"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cache_invalidate() {
+        let mut cache = ConvarCache::new();
+        cache.insert("mp_roundtime".to_string(), "1.92".to_string(), None);
+        assert!(!cache.get("mp_roundtime").unwrap().is_stale());
+        cache.invalidate("mp_roundtime");
+        assert!(cache.get("mp_roundtime").unwrap().is_stale());
+    }
+}
@@ -0,0 +1,1028 @@
+use serde::{Deserialize, Serialize};
+
+/// A player as written in log lines, e.g. `"Name<2><[U:1:12345]><CT>"`.
+///
+/// Some lines leave out the team, like `"Name<2><[U:1:12345]>" switched from team ...`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogPlayer {
+    pub name: String,
+    pub userid: i32,
+    pub steamid: String,
+    pub team: String,
+}
+
+impl LogPlayer {
+    /// Parse the part between the quotes, reading the `<..>` fields from the end as
+    /// names may contain `<` themselves.
+    pub fn parse(s: &str) -> Option<Self> {
+        if let Some((name, fields)) = trailing_fields(s, 3) {
+            let (team, steamid, userid) = (fields[0], fields[1], fields[2]);
+            if let (Ok(userid), Err(_)) = (userid.parse(), steamid.parse::<i32>()) {
+                return Some(Self {
+                    name: name.to_string(),
+                    userid,
+                    steamid: steamid.to_string(),
+                    team: team.to_string(),
+                });
+            }
+        }
+        let (name, fields) = trailing_fields(s, 2)?;
+        Some(Self {
+            name: name.to_string(),
+            userid: fields[1].parse().ok()?,
+            steamid: fields[0].to_string(),
+            team: String::new(),
+        })
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.steamid == "BOT"
+    }
+
+    /// What tells the player apart across renames, the SteamID, or the name for bots
+    /// as they all share `BOT`.
+    pub fn key(&self) -> String {
+        match self.is_bot() || self.steamid.is_empty() {
+            true => format!("name:{}", self.name.to_lowercase()),
+            false => self.steamid.clone(),
+        }
+    }
+
+    /// `CT` or `T` while the player is on one of the playing teams.
+    pub fn side(&self) -> Option<&'static str> {
+        side(&self.team)
+    }
+}
+
+/// A SteamID in the `[U:1:<account>]` form CS2 logs, from that form, the
+/// `STEAM_X:Y:Z` form CS:GO logs or a SteamID64.
+pub fn steam3(id: &str) -> Option<String> {
+    let id = id.trim();
+    let account = if let Some(account) = id
+        .strip_prefix("[U:1:")
+        .and_then(|rest| rest.strip_suffix(']'))
+    {
+        account.parse::<u64>().ok()?
+    } else if let Some(rest) = id.strip_prefix("STEAM_") {
+        let mut parts = rest.split(':').skip(1);
+        let y = parts.next()?.parse::<u64>().ok()?;
+        let z = parts.next()?.parse::<u64>().ok()?;
+        z * 2 + y
+    } else {
+        id.parse::<u64>().ok()?.checked_sub(76561197960265728)?
+    };
+    Some(format!("[U:1:{}]", account))
+}
+
+/// `CT` or `T` for a team as named in the log, `None` for spectators and the unassigned.
+pub fn side(team: &str) -> Option<&'static str> {
+    match team {
+        "CT" => Some("CT"),
+        "TERRORIST" | "T" => Some("T"),
+        _ => None,
+    }
+}
+
+/// The last `count` `<..>` fields of `s`, last first, and what comes before them.
+fn trailing_fields(s: &str, count: usize) -> Option<(&str, Vec<&str>)> {
+    let mut rest = s;
+    let mut fields = Vec::new();
+    for _ in 0..count {
+        let (before, field) = rest.strip_suffix('>')?.rsplit_once('<')?;
+        fields.push(field);
+        rest = before;
+    }
+    Some((rest, fields))
+}
+
+/// A position on the map, `[x y z]` in the log.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Position {
+    fn parse(s: &str) -> Option<Self> {
+        let coordinates = s
+            .split_whitespace()
+            .map(|c| c.parse::<f32>().ok().map(|c| c.round() as i32))
+            .collect::<Option<Vec<_>>>()?;
+        match coordinates.as_slice() {
+            [x, y, z] => Some(Self {
+                x: *x,
+                y: *y,
+                z: *z,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Why a team won a round.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WinReason {
+    /// The bomb exploded.
+    Bomb,
+    Defuse,
+    /// Every player of the other team died.
+    Elimination,
+    /// The round timer ran out.
+    Time,
+    HostagesRescued,
+    Surrender,
+    /// A reason not known yet, as the server names it.
+    Other(String),
+}
+
+impl WinReason {
+    fn parse(notice: &str) -> Self {
+        match notice.trim_start_matches("SFUI_Notice_") {
+            "Target_Bombed" => WinReason::Bomb,
+            "Bomb_Defused" => WinReason::Defuse,
+            "CTs_Win" | "Terrorists_Win" => WinReason::Elimination,
+            "Target_Saved" | "Hostages_Not_Rescued" => WinReason::Time,
+            "All_Hostages_Rescued" => WinReason::HostagesRescued,
+            "Terrorists_Surrender" | "CTs_Surrender" => WinReason::Surrender,
+            _ => WinReason::Other(notice.to_string()),
+        }
+    }
+
+    /// A short name for the reason, the server's own for unknown ones.
+    pub fn name(&self) -> &str {
+        match self {
+            WinReason::Bomb => "bomb",
+            WinReason::Defuse => "defuse",
+            WinReason::Elimination => "elimination",
+            WinReason::Time => "time",
+            WinReason::HostagesRescued => "hostages rescued",
+            WinReason::Surrender => "surrender",
+            WinReason::Other(notice) => notice,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogEvent {
+    Connected {
+        player: LogPlayer,
+        address: String,
+    },
+    Disconnected {
+        player: LogPlayer,
+        reason: String,
+    },
+    EnteredGame {
+        player: LogPlayer,
+    },
+    SwitchedTeam {
+        player: LogPlayer,
+        from: String,
+        to: String,
+    },
+    Say {
+        player: LogPlayer,
+        message: String,
+        team_only: bool,
+    },
+    Killed {
+        attacker: LogPlayer,
+        attacker_position: Option<Position>,
+        victim: LogPlayer,
+        victim_position: Option<Position>,
+        weapon: String,
+        headshot: bool,
+        /// Shot through a wall or another object.
+        penetrated: bool,
+        noscope: bool,
+        through_smoke: bool,
+        attacker_blind: bool,
+    },
+    /// A hit, `damage` may be more than the health the victim had left.
+    Attacked {
+        attacker: LogPlayer,
+        victim: LogPlayer,
+        weapon: String,
+        damage: u32,
+        damage_armor: u32,
+        /// Health of the victim after the hit.
+        health: u32,
+        armor: u32,
+        hitgroup: String,
+    },
+    Suicide {
+        player: LogPlayer,
+        weapon: String,
+    },
+    Assist {
+        assister: LogPlayer,
+        victim: LogPlayer,
+        /// The victim was blinded by the assister's flashbang.
+        flash: bool,
+    },
+    BombPlanted {
+        player: LogPlayer,
+        site: Option<String>,
+    },
+    BombDefused {
+        player: LogPlayer,
+    },
+    MatchStart {
+        map: String,
+    },
+    /// The server finished loading a map, after `changelevel` or `map`.
+    MapStarted {
+        map: String,
+    },
+    RoundStart,
+    RoundEnd,
+    /// A team won the round, with the scores after it.
+    RoundWon {
+        team: String,
+        reason: WinReason,
+        ct_score: u32,
+        t_score: u32,
+    },
+    /// Something else the world triggered, like `Restart_Round_(1_second)`.
+    WorldTriggered(String),
+    MatchStatus {
+        ct_score: u32,
+        t_score: u32,
+        map: String,
+        rounds_played: u32,
+    },
+    TeamPlaying {
+        team: String,
+        name: String,
+    },
+    GameOver {
+        mode: String,
+        map: String,
+        /// The score as the server prints it, CT first.
+        score: (u32, u32),
+        minutes: u32,
+    },
+    Cvar {
+        name: String,
+        value: String,
+    },
+    /// A line that is not understood, kept as it was sent.
+    Raw(String),
+}
+
+/// One line of the server log with the time the server wrote it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogLine {
+    /// `MM/DD/YYYY`
+    pub date: String,
+    /// `HH:MM:SS`, without the milliseconds CS2 adds.
+    pub time: String,
+    pub event: LogEvent,
+}
+
+/// Split off the timestamp of both the classic `L 10/18/2026 - 20:15:01: ...` format
+/// and the CS2 http format `10/18/2026 - 20:15:01.123 - ...`.
+fn split_timestamp(line: &str) -> Option<(&str, &str, &str)> {
+    let line = line.strip_prefix("L ").unwrap_or(line);
+    let (date, rest) = line.split_once(" - ")?;
+    let end = rest.find(": ").filter(|i| *i <= 12);
+    let (time, rest) = match end {
+        Some(i) => (&rest[..i], &rest[i + 2..]),
+        None => rest.split_once(" - ")?,
+    };
+    let time = time.split('.').next().unwrap_or(time);
+    match (date.len(), time.len()) {
+        (10, 8) => Some((date, time, rest)),
+        _ => None,
+    }
+}
+
+/// The quoted player at the start of `s` and whatever follows it.
+fn split_player(s: &str) -> Option<(LogPlayer, &str)> {
+    let s = s.strip_prefix('"')?;
+    let end = s.find(">\"")?;
+    Some((LogPlayer::parse(&s[..end + 1])?, &s[end + 2..]))
+}
+
+/// The quoted string at the start of `s` and whatever follows it.
+fn split_quoted(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start().strip_prefix('"')?;
+    let end = s.find('"')?;
+    Some((&s[..end], &s[end + 1..]))
+}
+
+/// An optional `[x y z]` at the start of `s` and whatever follows it.
+fn split_position(s: &str) -> (Option<Position>, &str) {
+    let trimmed = s.trim_start();
+    let Some(inner) = trimmed.strip_prefix('[') else {
+        return (None, s);
+    };
+    match inner.split_once(']') {
+        Some((position, rest)) => (Position::parse(position), rest),
+        None => (None, s),
+    }
+}
+
+/// Messages are quoted, but may contain quotes themselves, so only the outer ones go.
+fn unquote(s: &str) -> &str {
+    let s = s.trim();
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+/// `a:b` as two numbers.
+fn parse_score(s: &str) -> Option<(u32, u32)> {
+    let (a, b) = s.split_once(':')?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+/// The value of `(key "value")` in `s`.
+fn property<'a>(s: &'a str, key: &str) -> Option<&'a str> {
+    let start = s.find(&format!("({} \"", key))? + key.len() + 3;
+    let end = s[start..].find('"')?;
+    Some(&s[start..start + end])
+}
+
+fn parse_cvar(s: &str) -> Option<LogEvent> {
+    // CS2: `server cvar "mp_maxrounds" = "24"`, CS:GO: `server_cvar: "mp_maxrounds" "24"`
+    let rest = s
+        .strip_prefix("server cvar ")
+        .or_else(|| s.strip_prefix("server_cvar: "))?;
+    let (name, rest) = split_quoted(rest)?;
+    let rest = rest.trim_start();
+    let (value, _) = split_quoted(rest.strip_prefix('=').unwrap_or(rest))?;
+    Some(LogEvent::Cvar {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn parse_world(s: &str) -> Option<LogEvent> {
+    let (event, rest) = split_quoted(s.strip_prefix("World triggered ")?)?;
+    Some(match event {
+        "Round_Start" => LogEvent::RoundStart,
+        "Round_End" => LogEvent::RoundEnd,
+        "Match_Start" => LogEvent::MatchStart {
+            map: rest
+                .trim_start()
+                .strip_prefix("on ")
+                .and_then(split_quoted)
+                .map(|(map, _)| map.to_string())
+                .unwrap_or_default(),
+        },
+        event => LogEvent::WorldTriggered(event.to_string()),
+    })
+}
+
+fn parse_map(s: &str) -> Option<LogEvent> {
+    // `Started map "de_inferno" (CRC "-1817424410")`
+    let (map, _) = split_quoted(s.strip_prefix("Started map ")?)?;
+    Some(LogEvent::MapStarted {
+        map: map.to_string(),
+    })
+}
+
+fn parse_team(s: &str) -> Option<LogEvent> {
+    // `Team "CT" triggered "SFUI_Notice_CTs_Win" (CT "1") (T "0")`
+    let (team, rest) = split_quoted(s.strip_prefix("Team ")?)?;
+    let (notice, rest) = split_quoted(rest.trim_start().strip_prefix("triggered ")?)?;
+    Some(LogEvent::RoundWon {
+        team: team.to_string(),
+        reason: WinReason::parse(notice),
+        ct_score: property(rest, "CT")?.parse().ok()?,
+        t_score: property(rest, "T")?.parse().ok()?,
+    })
+}
+
+fn parse_match_status(s: &str) -> Option<LogEvent> {
+    let rest = s.strip_prefix("MatchStatus: ")?;
+    if let Some(rest) = rest.strip_prefix("Score: ") {
+        // `Score: 1:0 on map "de_inferno" RoundsPlayed: 1`
+        let (score, rest) = rest.split_once(" on map ")?;
+        let (ct_score, t_score) = parse_score(score)?;
+        let (map, rest) = split_quoted(rest)?;
+        return Some(LogEvent::MatchStatus {
+            ct_score,
+            t_score,
+            map: map.to_string(),
+            rounds_played: rest
+                .trim()
+                .strip_prefix("RoundsPlayed: ")?
+                .trim()
+                .parse()
+                .ok()?,
+        });
+    }
+    // `Team playing "CT": Natus Vincere`
+    let (team, rest) = split_quoted(rest.strip_prefix("Team playing ")?)?;
+    Some(LogEvent::TeamPlaying {
+        team: team.to_string(),
+        name: rest.trim_start_matches(':').trim().to_string(),
+    })
+}
+
+fn parse_game_over(s: &str) -> Option<LogEvent> {
+    // `Game Over: competitive 131399785 de_inferno score 16:14 after 45 min`
+    let words = s
+        .strip_prefix("Game Over: ")?
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    let score_at = words.iter().position(|w| *w == "score")?;
+    let mode = words.first()?;
+    let map = words.get(score_at.checked_sub(1)?)?;
+    Some(LogEvent::GameOver {
+        mode: mode.to_string(),
+        map: map.to_string(),
+        score: parse_score(words.get(score_at + 1)?)?,
+        minutes: words
+            .get(score_at + 3)
+            .and_then(|m| m.parse().ok())
+            .unwrap_or_default(),
+    })
+}
+
+fn parse_kill(attacker: LogPlayer, rest: &str) -> Option<LogEvent> {
+    let (attacker_position, rest) = split_position(rest);
+    let rest = rest.trim_start().strip_prefix("killed ")?;
+    let (victim, rest) = split_player(rest)?;
+    let (victim_position, rest) = split_position(rest);
+    let (weapon, rest) = split_quoted(rest.trim_start().strip_prefix("with ")?)?;
+    let flags = rest
+        .trim()
+        .strip_prefix('(')
+        .and_then(|f| f.strip_suffix(')'))
+        .unwrap_or("")
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    Some(LogEvent::Killed {
+        attacker,
+        attacker_position,
+        victim,
+        victim_position,
+        weapon: weapon.to_string(),
+        headshot: flags.contains(&"headshot"),
+        penetrated: flags.contains(&"penetrated"),
+        noscope: flags.contains(&"noscope"),
+        through_smoke: flags.contains(&"throughsmoke"),
+        attacker_blind: flags.contains(&"attackerblind"),
+    })
+}
+
+fn parse_attack(attacker: LogPlayer, rest: &str) -> Option<LogEvent> {
+    let rest = rest.trim_start().strip_prefix("attacked ")?;
+    let (victim, rest) = split_player(rest)?;
+    let (_, rest) = split_position(rest);
+    let (weapon, rest) = split_quoted(rest.trim_start().strip_prefix("with ")?)?;
+    let number = |key| property(rest, key).and_then(|v| v.parse().ok());
+    Some(LogEvent::Attacked {
+        attacker,
+        victim,
+        weapon: weapon.to_string(),
+        damage: number("damage")?,
+        damage_armor: number("damage_armor").unwrap_or_default(),
+        health: number("health")?,
+        armor: number("armor").unwrap_or_default(),
+        hitgroup: property(rest, "hitgroup").unwrap_or_default().to_string(),
+    })
+}
+
+fn parse_player_event(player: LogPlayer, rest: &str) -> Option<LogEvent> {
+    if let Some(message) = rest.strip_prefix(" say_team ") {
+        return Some(LogEvent::Say {
+            player,
+            message: unquote(message).to_string(),
+            team_only: true,
+        });
+    }
+    if let Some(message) = rest.strip_prefix(" say ") {
+        return Some(LogEvent::Say {
+            player,
+            message: unquote(message).to_string(),
+            team_only: false,
+        });
+    }
+    if let Some(rest) = rest.strip_prefix(" connected, address ") {
+        return Some(LogEvent::Connected {
+            player,
+            address: split_quoted(rest)?.0.to_string(),
+        });
+    }
+    if rest.starts_with(" entered the game") {
+        return Some(LogEvent::EnteredGame { player });
+    }
+    if let Some(rest) = rest.strip_prefix(" disconnected") {
+        return Some(LogEvent::Disconnected {
+            player,
+            reason: property(rest, "reason").unwrap_or_default().to_string(),
+        });
+    }
+    if let Some(rest) = rest.strip_prefix(" switched from team <") {
+        let (from, rest) = rest.split_once("> to <")?;
+        return Some(LogEvent::SwitchedTeam {
+            player,
+            from: from.to_string(),
+            to: rest.strip_suffix('>')?.to_string(),
+        });
+    }
+    if let Some(rest) = rest
+        .strip_prefix(" assisted killing ")
+        .map(|rest| (rest, false))
+        .or_else(|| {
+            rest.strip_prefix(" flash-assisted killing ")
+                .map(|rest| (rest, true))
+        })
+    {
+        let (rest, flash) = rest;
+        return Some(LogEvent::Assist {
+            assister: player,
+            victim: split_player(rest)?.0,
+            flash,
+        });
+    }
+    if let Some(rest) = rest.strip_prefix(" triggered ") {
+        let (event, rest) = split_quoted(rest)?;
+        return match event {
+            "Planted_The_Bomb" => Some(LogEvent::BombPlanted {
+                player,
+                site: rest
+                    .trim()
+                    .strip_prefix("at bombsite ")
+                    .map(|site| site.trim().to_string()),
+            }),
+            "Defused_The_Bomb" => Some(LogEvent::BombDefused { player }),
+            _ => None,
+        };
+    }
+    let (_, after_position) = split_position(rest);
+    if let Some(rest) = after_position
+        .trim_start()
+        .strip_prefix("committed suicide with ")
+    {
+        return Some(LogEvent::Suicide {
+            player,
+            weapon: split_quoted(rest)?.0.to_string(),
+        });
+    }
+    if after_position.trim_start().starts_with("attacked ") {
+        return parse_attack(player, after_position);
+    }
+    parse_kill(player, rest)
+}
+
+fn parse_event(s: &str) -> LogEvent {
+    let event = match split_player(s) {
+        Some((player, rest)) => parse_player_event(player, rest),
+        None => parse_cvar(s)
+            .or_else(|| parse_world(s))
+            .or_else(|| parse_map(s))
+            .or_else(|| parse_team(s))
+            .or_else(|| parse_match_status(s))
+            .or_else(|| parse_game_over(s)),
+    };
+    event.unwrap_or_else(|| LogEvent::Raw(s.to_string()))
+}
+
+/// Parse one line of the server log, `None` if it has no timestamp.
+pub fn parse_line(line: &str) -> Option<LogLine> {
+    let (date, time, rest) = split_timestamp(line.trim_end())?;
+    Some(LogLine {
+        date: date.to_string(),
+        time: time.to_string(),
+        event: parse_event(rest),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const FIXTURES: [&str; 3] = [
+        include_str!("../../fixtures/logs/cs2_http.log"),
+        include_str!("../../fixtures/logs/classic_udp.log"),
+        include_str!("../../fixtures/logs/cs2_match.log"),
+    ];
+
+    fn player(name: &str, userid: i32, steamid: &str, team: &str) -> LogPlayer {
+        LogPlayer {
+            name: name.to_string(),
+            userid,
+            steamid: steamid.to_string(),
+            team: team.to_string(),
+        }
+    }
+
+    fn event(line: &str) -> LogEvent {
+        parse_line(&format!("10/18/2026 - 20:15:01.000 - {}", line))
+            .unwrap()
+            .event
+    }
+
+    fn events(fixture: &str) -> Vec<LogEvent> {
+        fixture
+            .lines()
+            .map(|line| parse_line(line).unwrap().event)
+            .collect()
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let classic = parse_line("L 10/18/2026 - 20:15:01: World triggered \"Round_Start\"");
+        let http = parse_line("10/18/2026 - 20:15:01.123 - World triggered \"Round_Start\"");
+        assert_eq!(classic, http);
+        let line = classic.unwrap();
+        assert_eq!(line.date, "10/18/2026");
+        assert_eq!(line.time, "20:15:01");
+        assert_eq!(parse_line("hello"), None);
+        assert_eq!(parse_line(""), None);
+        assert_eq!(parse_line("L 10/18/2026 - : nothing"), None);
+    }
+
+    #[test]
+    fn test_every_fixture_line_has_a_timestamp() {
+        for fixture in FIXTURES {
+            for line in fixture.lines() {
+                assert!(parse_line(line).is_some(), "{}", line);
+            }
+        }
+    }
+
+    #[test]
+    fn test_players() {
+        assert_eq!(
+            LogPlayer::parse("Alice<2><[U:1:1001]><CT>"),
+            Some(player("Alice", 2, "[U:1:1001]", "CT"))
+        );
+        assert_eq!(
+            LogPlayer::parse("Alice<2><[U:1:1001]>"),
+            Some(player("Alice", 2, "[U:1:1001]", ""))
+        );
+        assert_eq!(
+            LogPlayer::parse("Alice<2><[U:1:1001]><>"),
+            Some(player("Alice", 2, "[U:1:1001]", ""))
+        );
+        // Names may look like the fields that follow them.
+        assert_eq!(
+            LogPlayer::parse("x<5><2><[U:1:7]>"),
+            Some(player("x<5>", 2, "[U:1:7]", ""))
+        );
+        assert_eq!(
+            LogPlayer::parse("<3><>< ><1><STEAM_1:0:9><TERRORIST>"),
+            Some(player("<3><>< >", 1, "STEAM_1:0:9", "TERRORIST"))
+        );
+        let bot = LogPlayer::parse("Bot Ava<12><BOT><CT>").unwrap();
+        assert!(bot.is_bot());
+        assert_eq!(bot.key(), "name:bot ava");
+        assert_eq!(bot.side(), Some("CT"));
+        assert_eq!(player("Alice", 2, "[U:1:1001]", "Spectator").side(), None);
+        assert_eq!(player("Alice", 2, "[U:1:1001]", "").key(), "[U:1:1001]");
+        assert_eq!(
+            LogPlayer::parse("Console<0><Console><Console>"),
+            Some(player("Console", 0, "Console", "Console"))
+        );
+        assert_eq!(LogPlayer::parse("Alice"), None);
+        assert_eq!(LogPlayer::parse("Alice<x><y>"), None);
+    }
+
+    #[test]
+    fn test_connections() {
+        let alice = player("Alice", 2, "[U:1:1001]", "");
+        assert_eq!(
+            event(r#""Alice<2><[U:1:1001]><>" connected, address "198.51.100.7:27005""#),
+            LogEvent::Connected {
+                player: alice.clone(),
+                address: "198.51.100.7:27005".to_string(),
+            }
+        );
+        assert_eq!(
+            event(r#""Alice<2><[U:1:1001]><>" entered the game"#),
+            LogEvent::EnteredGame {
+                player: alice.clone()
+            }
+        );
+        assert_eq!(
+            event(r#""Alice<2><[U:1:1001]>" switched from team <Unassigned> to <CT>"#),
+            LogEvent::SwitchedTeam {
+                player: alice,
+                from: "Unassigned".to_string(),
+                to: "CT".to_string(),
+            }
+        );
+        assert_eq!(
+            event(
+                r#""Bob<3><[U:1:1002]><TERRORIST>" disconnected (reason "NETWORK_DISCONNECT_DISCONNECT_BY_USER")"#
+            ),
+            LogEvent::Disconnected {
+                player: player("Bob", 3, "[U:1:1002]", "TERRORIST"),
+                reason: "NETWORK_DISCONNECT_DISCONNECT_BY_USER".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_say() {
+        assert_eq!(
+            event(r#""a<b> c<3><[U:1:42]><TERRORIST>" say_team "gl hf""#),
+            LogEvent::Say {
+                player: player("a<b> c", 3, "[U:1:42]", "TERRORIST"),
+                message: "gl hf".to_string(),
+                team_only: true,
+            }
+        );
+        // Quotes in messages are not escaped.
+        assert_eq!(
+            event(r#""Bob<3><[U:1:1002]><CT>" say "he said "rush b" again""#),
+            LogEvent::Say {
+                player: player("Bob", 3, "[U:1:1002]", "CT"),
+                message: r#"he said "rush b" again"#.to_string(),
+                team_only: false,
+            }
+        );
+        assert_eq!(
+            event(r#""Bob<3><[U:1:1002]><CT>" say """#),
+            LogEvent::Say {
+                player: player("Bob", 3, "[U:1:1002]", "CT"),
+                message: String::new(),
+                team_only: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_kills() {
+        assert_eq!(
+            event(
+                r#""Alice<2><[U:1:1001]><CT>" [-1117 2465 -56] killed "Bob<3><[U:1:1002]><TERRORIST>" [-1026 1766 -120] with "ak47" (headshot penetrated)"#
+            ),
+            LogEvent::Killed {
+                attacker: player("Alice", 2, "[U:1:1001]", "CT"),
+                attacker_position: Some(Position {
+                    x: -1117,
+                    y: 2465,
+                    z: -56
+                }),
+                victim: player("Bob", 3, "[U:1:1002]", "TERRORIST"),
+                victim_position: Some(Position {
+                    x: -1026,
+                    y: 1766,
+                    z: -120
+                }),
+                weapon: "ak47".to_string(),
+                headshot: true,
+                penetrated: true,
+                noscope: false,
+                through_smoke: false,
+                attacker_blind: false,
+            }
+        );
+        let LogEvent::Killed {
+            noscope,
+            through_smoke,
+            attacker_blind,
+            headshot,
+            attacker_position,
+            ..
+        } = event(
+            r#""Alice<2><[U:1:1001]><CT>" killed "Bob<3><[U:1:1002]><TERRORIST>" with "awp" (noscope throughsmoke attackerblind)"#,
+        )
+        else {
+            panic!("not a kill");
+        };
+        assert!(noscope && through_smoke && attacker_blind && !headshot);
+        assert_eq!(attacker_position, None);
+        assert_eq!(
+            event(
+                r#""Bob<3><[U:1:1002]><TERRORIST>" [-1026 1766 -120] committed suicide with "world""#
+            ),
+            LogEvent::Suicide {
+                player: player("Bob", 3, "[U:1:1002]", "TERRORIST"),
+                weapon: "world".to_string(),
+            }
+        );
+        assert_eq!(
+            event(
+                r#""Alice<2><[U:1:1001]><CT>" [-400 -1200 -416] attacked "Bob<3><[U:1:1002]><TERRORIST>" [-300 -900 -416] with "m4a1_silencer" (damage "112") (damage_armor "0") (health "0") (armor "100") (hitgroup "head")"#
+            ),
+            LogEvent::Attacked {
+                attacker: player("Alice", 2, "[U:1:1001]", "CT"),
+                victim: player("Bob", 3, "[U:1:1002]", "TERRORIST"),
+                weapon: "m4a1_silencer".to_string(),
+                damage: 112,
+                damage_armor: 0,
+                health: 0,
+                armor: 100,
+                hitgroup: "head".to_string(),
+            }
+        );
+        assert_eq!(
+            event(
+                r#""Carol<4><[U:1:1003]><CT>" flash-assisted killing "Bob<3><[U:1:1002]><TERRORIST>""#
+            ),
+            LogEvent::Assist {
+                assister: player("Carol", 4, "[U:1:1003]", "CT"),
+                victim: player("Bob", 3, "[U:1:1002]", "TERRORIST"),
+                flash: true,
+            }
+        );
+        // Chickens are not players.
+        assert!(matches!(
+            event(
+                r#""Alice<2><[U:1:1001]><CT>" [0 0 0] killed other "chicken<152>" [1 1 1] with "knife""#
+            ),
+            LogEvent::Raw(_)
+        ));
+    }
+
+    #[test]
+    fn test_bomb() {
+        assert_eq!(
+            event(r#""Bob<3><[U:1:1002]><TERRORIST>" triggered "Planted_The_Bomb" at bombsite B"#),
+            LogEvent::BombPlanted {
+                player: player("Bob", 3, "[U:1:1002]", "TERRORIST"),
+                site: Some("B".to_string()),
+            }
+        );
+        assert_eq!(
+            event(r#""Alice<2><[U:1:1001]><CT>" triggered "Defused_The_Bomb""#),
+            LogEvent::BombDefused {
+                player: player("Alice", 2, "[U:1:1001]", "CT"),
+            }
+        );
+        assert!(matches!(
+            event(r#""Alice<2><[U:1:1001]><CT>" triggered "Begin_Bomb_Defuse_With_Kit""#),
+            LogEvent::Raw(_)
+        ));
+    }
+
+    #[test]
+    fn test_rounds() {
+        assert_eq!(
+            event(r#"World triggered "Round_Start""#),
+            LogEvent::RoundStart
+        );
+        assert_eq!(event(r#"World triggered "Round_End""#), LogEvent::RoundEnd);
+        assert_eq!(
+            event(r#"World triggered "Match_Start" on "de_inferno""#),
+            LogEvent::MatchStart {
+                map: "de_inferno".to_string()
+            }
+        );
+        assert_eq!(
+            event(r#"World triggered "Restart_Round_(1_second)""#),
+            LogEvent::WorldTriggered("Restart_Round_(1_second)".to_string())
+        );
+        assert_eq!(
+            event(r#"Team "TERRORIST" triggered "SFUI_Notice_Target_Bombed" (CT "3") (T "5")"#),
+            LogEvent::RoundWon {
+                team: "TERRORIST".to_string(),
+                reason: WinReason::Bomb,
+                ct_score: 3,
+                t_score: 5,
+            }
+        );
+        for (notice, reason) in [
+            ("SFUI_Notice_Bomb_Defused", WinReason::Defuse),
+            ("SFUI_Notice_CTs_Win", WinReason::Elimination),
+            ("SFUI_Notice_Terrorists_Win", WinReason::Elimination),
+            ("SFUI_Notice_Target_Saved", WinReason::Time),
+            ("SFUI_Notice_Terrorists_Surrender", WinReason::Surrender),
+            (
+                "SFUI_Notice_Something_New",
+                WinReason::Other("SFUI_Notice_Something_New".to_string()),
+            ),
+        ] {
+            assert_eq!(WinReason::parse(notice), reason);
+        }
+    }
+
+    #[test]
+    fn test_steam3() {
+        for id in [
+            "[U:1:1001]",
+            "STEAM_1:1:500",
+            "STEAM_0:1:500",
+            "76561197960266729",
+        ] {
+            assert_eq!(steam3(id), Some("[U:1:1001]".to_string()), "{}", id);
+        }
+        assert_eq!(steam3("BOT"), None);
+        assert_eq!(steam3("12"), None);
+    }
+
+    #[test]
+    fn test_match_status() {
+        assert_eq!(
+            event(r#"MatchStatus: Score: 9:6 on map "de_inferno" RoundsPlayed: 15"#),
+            LogEvent::MatchStatus {
+                ct_score: 9,
+                t_score: 6,
+                map: "de_inferno".to_string(),
+                rounds_played: 15,
+            }
+        );
+        assert_eq!(
+            event(r#"MatchStatus: Team playing "CT": Team Alpha"#),
+            LogEvent::TeamPlaying {
+                team: "CT".to_string(),
+                name: "Team Alpha".to_string(),
+            }
+        );
+        assert_eq!(
+            event(r#"Started map "de_inferno" (CRC "-1817424410")"#),
+            LogEvent::MapStarted {
+                map: "de_inferno".to_string(),
+            }
+        );
+        assert_eq!(
+            event("Game Over: competitive mg_active de_inferno score 13:11 after 41 min"),
+            LogEvent::GameOver {
+                mode: "competitive".to_string(),
+                map: "de_inferno".to_string(),
+                score: (13, 11),
+                minutes: 41,
+            }
+        );
+    }
+
+    #[test]
+    fn test_cvars() {
+        let expected = LogEvent::Cvar {
+            name: "mp_maxrounds".to_string(),
+            value: "24".to_string(),
+        };
+        assert_eq!(event(r#"server cvar "mp_maxrounds" = "24""#), expected);
+        assert_eq!(event(r#"server_cvar: "mp_maxrounds" "24""#), expected);
+    }
+
+    #[test]
+    fn test_unknown_lines_are_kept() {
+        for line in [
+            r#"Log file started (file "logs/L000.log") (game "/home/csgo") (version "8012")"#,
+            r#"rcon from "203.0.113.5:51234": command "status""#,
+            r#""Alice<2><[U:1:1001]><CT>" purchased "m4a1_silencer""#,
+            r#"Team "CT" scored "5" with "5" players"#,
+            "MatchStatus: something new",
+            "Game Over: nonsense",
+            r#""broken player" say "hi""#,
+        ] {
+            assert_eq!(event(line), LogEvent::Raw(line.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_match_fixture() {
+        let events = events(FIXTURES[2]);
+        let count = |f: fn(&LogEvent) -> bool| events.iter().filter(|e| f(e)).count();
+        assert_eq!(count(|e| matches!(e, LogEvent::Connected { .. })), 4);
+        assert_eq!(count(|e| matches!(e, LogEvent::RoundStart)), 3);
+        assert_eq!(count(|e| matches!(e, LogEvent::Killed { .. })), 10);
+        assert_eq!(count(|e| matches!(e, LogEvent::Assist { .. })), 2);
+        assert_eq!(count(|e| matches!(e, LogEvent::Attacked { .. })), 10);
+        assert_eq!(
+            count(|e| matches!(e, LogEvent::Killed { headshot: true, .. })),
+            4
+        );
+        let wins = events
+            .iter()
+            .filter_map(|e| match e {
+                LogEvent::RoundWon {
+                    reason,
+                    ct_score,
+                    t_score,
+                    ..
+                } => Some((reason.clone(), *ct_score, *t_score)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            wins,
+            vec![
+                (WinReason::Elimination, 1, 0),
+                (WinReason::Bomb, 1, 1),
+                (WinReason::Defuse, 2, 1),
+            ]
+        );
+        assert!(matches!(
+            events.last(),
+            Some(LogEvent::GameOver { score: (2, 1), .. })
+        ));
+        assert_eq!(count(|e| matches!(e, LogEvent::Raw(_))), 2);
+    }
+
+    #[test]
+    fn test_classic_fixture() {
+        let events = events(FIXTURES[1]);
+        assert!(matches!(events[0], LogEvent::Raw(_)));
+        assert_eq!(
+            events[1],
+            LogEvent::Cvar {
+                name: "sv_cheats".to_string(),
+                value: "0".to_string()
+            }
+        );
+        assert!(matches!(events[5], LogEvent::Disconnected { .. }));
+    }
+}
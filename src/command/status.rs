@@ -1,22 +1,23 @@
 use log::{info, log};
+use serde::Serialize;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct Player {
-    id: u16,
-    time: String,
-    ping: u16,
-    loss: u16,
-    state: String,
-    rate: u32,
-    adr: String,
-    name: String,
+    pub id: u16,
+    pub time: String,
+    pub ping: u16,
+    pub loss: u16,
+    pub state: String,
+    pub rate: u32,
+    pub adr: String,
+    pub name: String,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct Status {
-    servername: String,
-    map: String,
-    players: Vec<Player>,
+    pub servername: String,
+    pub map: String,
+    pub players: Vec<Player>,
 }
 
 #[derive(Debug)]
@@ -32,6 +33,7 @@ impl Status {
         let mut lines = st.split('\n').collect::<Vec<&str>>();
         lines.reverse();
         info!("Updating status");
+        self.players.clear();
         let mut mode = ParsingMode::None;
         let mut spawn = "[0".to_string();
         while let Some(line) = lines.pop() {
@@ -62,7 +64,6 @@ impl Status {
                     line if line.contains(&spawn) => {
                         self.map = line.split(':').collect::<Vec<&str>>()[3]
                             .trim()
-                            .to_string()
                             .split(' ')
                             .collect::<Vec<&str>>()[0]
                             .to_string();
@@ -80,10 +81,13 @@ impl Status {
                         let mut player = Player::default();
                         let mut parts: Vec<String> =
                             line.split_whitespace().map(|e| e.to_string()).collect();
-                        player.id = parts.remove(0).parse::<u16>().unwrap();
+                        if parts.len() < 6 {
+                            continue;
+                        }
+                        player.id = parts.remove(0).parse::<u16>().unwrap_or_default();
                         player.time = parts.remove(0);
-                        player.ping = parts.remove(0).parse::<u16>().unwrap();
-                        player.loss = parts.remove(0).parse::<u16>().unwrap();
+                        player.ping = parts.remove(0).parse::<u16>().unwrap_or_default();
+                        player.loss = parts.remove(0).parse::<u16>().unwrap_or_default();
                         player.state = parts.remove(0);
                         parts.reverse();
                         player.name = parts.remove(0);
@@ -91,7 +95,7 @@ impl Status {
                         player.rate = 0;
                         if parts.len() == 2 {
                             player.adr = parts.remove(0);
-                            player.rate = parts.remove(0).parse::<u32>().unwrap();
+                            player.rate = parts.remove(0).parse::<u32>().unwrap_or_default();
                         } else if parts.len() == 1 {
                             player.adr = parts.remove(0).chars().skip(1).collect();
                         }
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    action::Action,
+    command::{autocompleter::AutoCompleter, cfg, Command},
+};
+
+/// What to do when a submitted command fails a validation check.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Policy {
+    Ignore,
+    #[default]
+    Warn,
+    Confirm,
+    Block,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationPolicy {
+    #[serde(default = "ValidationPolicy::default_arguments")]
+    pub arguments: Policy,
+    #[serde(default = "ValidationPolicy::default_cheat")]
+    pub cheat: Policy,
+    #[serde(default)]
+    pub development: Policy,
+    #[serde(default)]
+    pub read_only: Policy,
+}
+
+impl ValidationPolicy {
+    fn default_arguments() -> Policy {
+        Policy::Confirm
+    }
+
+    fn default_cheat() -> Policy {
+        Policy::Confirm
+    }
+}
+
+impl Default for ValidationPolicy {
+    fn default() -> Self {
+        Self {
+            arguments: Self::default_arguments(),
+            cheat: Self::default_cheat(),
+            development: Policy::Warn,
+            read_only: Policy::Warn,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Validation {
+    Ok,
+    Warn(String),
+    Confirm(String),
+    Block(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ValueKind {
+    Numeric,
+    Text,
+}
+
+impl ValueKind {
+    fn of(value: &str) -> Self {
+        // The engine takes `true` and `false` for 1 and 0.
+        match value.parse::<f64>().is_ok() || value == "true" || value == "false" {
+            true => ValueKind::Numeric,
+            false => ValueKind::Text,
+        }
+    }
+}
+
+/// Checks commands against the catalog before they are sent to the server.
+pub struct Validator {
+    catalog: AutoCompleter,
+    policy: ValidationPolicy,
+    sv_cheats: Option<bool>,
+    kinds: HashMap<String, ValueKind>,
+}
+
+impl Validator {
+    pub fn new(policy: ValidationPolicy) -> Self {
+        let mut catalog = AutoCompleter::new();
+        catalog.load_catalog();
+        Self::with_catalog(catalog, policy)
+    }
+
+    fn with_catalog(catalog: AutoCompleter, policy: ValidationPolicy) -> Self {
+        Self {
+            catalog,
+            policy,
+            sv_cheats: None,
+            kinds: HashMap::new(),
+        }
+    }
+
+    pub fn set_policy(&mut self, policy: ValidationPolicy) {
+        self.policy = policy;
+    }
+
+    /// Keep track of server state the checks depend on.
+    pub fn update(&mut self, action: &Action) {
+        match action {
+            Action::ConvarValue(name, value, _) => {
+                if name == "sv_cheats" {
+                    self.sv_cheats = Some(value != "0" && value != "false");
+                }
+                self.kinds.insert(name.clone(), ValueKind::of(value));
+            }
+            Action::SendCommand(command) => {
+                let args = split_args(command);
+                if let [name, value] = args.as_slice() {
+                    if name == "sv_cheats" {
+                        self.sv_cheats = Some(value != "0" && value != "false");
+                    }
+                }
+            }
+            Action::Connected(_) => self.sv_cheats = None,
+            _ => {}
+        }
+    }
+
+    pub fn validate(&self, command: &str) -> Validation {
+        let args = split_args(command);
+        let Some(name) = args.first() else {
+            return Validation::Ok;
+        };
+        let Some(entry) = self.catalog.get_command(name) else {
+            return Validation::Ok;
+        };
+        let mut issues = Vec::new();
+        if let Some(issue) = self.check_arguments(&entry, &args[1..]) {
+            issues.push((self.policy.arguments, issue));
+        }
+        // Only setting a convar can be restricted, querying it is always fine.
+        let setting = !entry.is_convar() || args.len() > 1;
+        if setting && entry.flags.iter().any(|f| f == "cheat") && self.sv_cheats != Some(true) {
+            let issue = match self.sv_cheats {
+                Some(_) => format!("{} is cheat protected and sv_cheats is 0", entry.name),
+                None => format!("{} is cheat protected and sv_cheats is unknown", entry.name),
+            };
+            issues.push((self.policy.cheat, issue));
+        }
+        if setting && entry.flags.iter().any(|f| f == "developmentonly") {
+            issues.push((
+                self.policy.development,
+                format!("{} is development only", entry.name),
+            ));
+        }
+        if entry.is_convar() && args.len() > 1 && is_read_only(&entry) {
+            issues.push((
+                self.policy.read_only,
+                format!("{} can not be set on the server", entry.name),
+            ));
+        }
+        verdict(issues)
+    }
+
+    /// Check every command `line` runs, split on `;` and with the file of an `exec` read,
+    /// and answer for the worst of them, as the server runs them all.
+    pub fn validate_line(&self, line: &str) -> Validation {
+        let commands = match cfg::expand(line) {
+            Ok(commands) => commands,
+            Err(e) => return Validation::Block(e),
+        };
+        let issues = commands
+            .iter()
+            .filter_map(|command| match self.validate(command) {
+                Validation::Ok => None,
+                Validation::Warn(issue) => Some((Policy::Warn, issue)),
+                Validation::Confirm(issue) => Some((Policy::Confirm, issue)),
+                Validation::Block(issue) => Some((Policy::Block, issue)),
+            })
+            .collect();
+        verdict(issues)
+    }
+
+    fn check_arguments(&self, entry: &Command, args: &[String]) -> Option<String> {
+        if !entry.is_convar() {
+            return None;
+        }
+        if args.len() > 1 {
+            return Some(format!(
+                "{} takes one value but got {}",
+                entry.name,
+                args.len()
+            ));
+        }
+        match (args.first(), self.kinds.get(&entry.name)) {
+            (Some(value), Some(ValueKind::Numeric))
+                if ValueKind::of(value) != ValueKind::Numeric =>
+            {
+                Some(format!(
+                    "{} expects a number but got `{}`",
+                    entry.name, value
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What to do about `issues` found with a command, following the strictest policy among them.
+fn verdict(issues: Vec<(Policy, String)>) -> Validation {
+    let Some(policy) = issues.iter().map(|(policy, _)| *policy).max() else {
+        return Validation::Ok;
+    };
+    let message = issues
+        .into_iter()
+        .filter(|(policy, _)| *policy != Policy::Ignore)
+        .map(|(_, issue)| issue)
+        .collect::<Vec<_>>()
+        .join(", ");
+    match policy {
+        Policy::Ignore => Validation::Ok,
+        Policy::Warn => Validation::Warn(message),
+        Policy::Confirm => Validation::Confirm(message),
+        Policy::Block => Validation::Block(message),
+    }
+}
+
+/// Convars that are client side only or can not be changed while a server is running.
+fn is_read_only(entry: &Command) -> bool {
+    let has = |flag: &str| entry.flags.iter().any(|f| f == flag);
+    has("readonly")
+        || has("notconnected")
+        || (has("clientdll") && !has("gamedll") && !has("replicated"))
+}
+
+/// Split a command into its arguments, keeping quoted arguments together.
+pub fn split_args(command: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in command.trim().chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::command::CommandKind;
+
+    fn validator() -> Validator {
+        let mut catalog = AutoCompleter::new();
+        catalog.add_command(Command::new(
+            "mp_maxrounds",
+            "",
+            vec!["gamedll".to_string()],
+            CommandKind::Convar,
+        ));
+        catalog.add_command(Command::new(
+            "sv_infinite_ammo",
+            "",
+            vec!["gamedll".to_string(), "cheat".to_string()],
+            CommandKind::Convar,
+        ));
+        Validator::with_catalog(catalog, ValidationPolicy::default())
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(
+            split_args("hostname \"my server\"  1"),
+            vec!["hostname", "my server", "1"]
+        );
+    }
+
+    #[test]
+    fn test_validate_argument_type() {
+        let mut validator = validator();
+        assert_eq!(validator.validate("mp_maxrounds abc"), Validation::Ok);
+        validator.update(&Action::ConvarValue(
+            "mp_maxrounds".to_string(),
+            "24".to_string(),
+            None,
+        ));
+        assert!(matches!(
+            validator.validate("mp_maxrounds abc"),
+            Validation::Confirm(_)
+        ));
+        assert_eq!(validator.validate("mp_maxrounds 30"), Validation::Ok);
+    }
+
+    #[test]
+    fn test_kind_not_guessed() {
+        let mut validator = Validator::new(ValidationPolicy::default());
+        // The catalog has no types, values go unchecked until the server tells.
+        assert_eq!(validator.validate("sv_cheats true"), Validation::Ok);
+        assert_eq!(validator.validate("sv_cheats abc"), Validation::Ok);
+        assert_eq!(validator.validate("mp_maxrounds abc"), Validation::Ok);
+        validator.update(&Action::ConvarValue(
+            "sv_cheats".to_string(),
+            "0".to_string(),
+            None,
+        ));
+        assert_eq!(validator.validate("sv_cheats true"), Validation::Ok);
+        assert_eq!(validator.validate("sv_cheats 1"), Validation::Ok);
+        assert!(matches!(
+            validator.validate("sv_cheats abc"),
+            Validation::Confirm(_)
+        ));
+    }
+
+    #[test]
+    fn test_validate_argument_count() {
+        assert!(matches!(
+            validator().validate("mp_maxrounds 1 2"),
+            Validation::Confirm(_)
+        ));
+    }
+
+    #[test]
+    fn test_validate_cheat() {
+        let mut validator = validator();
+        assert_eq!(validator.validate("sv_infinite_ammo"), Validation::Ok);
+        assert!(matches!(
+            validator.validate("sv_infinite_ammo 1"),
+            Validation::Confirm(_)
+        ));
+        validator.update(&Action::SendCommand("sv_cheats 1".to_string()));
+        assert_eq!(validator.validate("sv_infinite_ammo 1"), Validation::Ok);
+    }
+
+    #[test]
+    fn test_validate_policy() {
+        let mut validator = validator();
+        validator.set_policy(ValidationPolicy {
+            cheat: Policy::Block,
+            ..Default::default()
+        });
+        assert!(matches!(
+            validator.validate("sv_infinite_ammo 1"),
+            Validation::Block(_)
+        ));
+    }
+
+    #[test]
+    fn test_validate_line() {
+        let validator = validator();
+        // The server runs every command on the line, not just the first.
+        assert!(matches!(
+            validator.validate_line("say hi; sv_infinite_ammo 1"),
+            Validation::Confirm(_)
+        ));
+        assert_eq!(
+            validator.validate_line("mp_maxrounds 1 2; sv_infinite_ammo 1"),
+            Validation::Confirm(
+                "mp_maxrounds takes one value but got 2, sv_infinite_ammo is cheat protected \
+                 and sv_cheats is unknown"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            validator.validate_line("say hi; mp_maxrounds 30"),
+            Validation::Ok
+        );
+        assert!(matches!(
+            validator.validate_line("exec missing"),
+            Validation::Block(_)
+        ));
+    }
+}
@@ -9,8 +9,12 @@ use crate::{
     tui::{Event, Frame},
 };
 
+pub mod chat;
 pub mod fps;
 pub mod home;
+pub mod killfeed;
+pub mod scoreboard;
+pub mod tabs;
 
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
 /// Implementors of this trait can be registered with the main application loop and will be able to receive events,
@@ -67,6 +71,7 @@ pub trait Component {
         let r = match event {
             Some(Event::Key(key_event)) => self.handle_key_events(key_event)?,
             Some(Event::Mouse(mouse_event)) => self.handle_mouse_events(mouse_event)?,
+            Some(Event::Paste(text)) => self.handle_paste_events(text)?,
             _ => None,
         };
         Ok(r)
@@ -97,6 +102,19 @@ pub trait Component {
     fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
         Ok(None)
     }
+    /// Handle pasted text and produce actions if necessary.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text pasted into the terminal.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<Action>>` - An action to be processed or none.
+    #[allow(unused_variables)]
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        Ok(None)
+    }
     /// Update the state of the component based on a received action. (REQUIRED)
     ///
     /// # Arguments
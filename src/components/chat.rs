@@ -0,0 +1,460 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    widgets::{block::Title, *},
+};
+use tui_input::{Input, InputRequest};
+
+use super::Component;
+use crate::{
+    action::Action,
+    command::logs::{side, LogEvent, LogPlayer},
+    config::Config,
+    inputwrapper::input_request,
+    mode::Mode,
+    theme::Theme,
+    tui::Frame,
+};
+
+/// Older messages are dropped once there are this many.
+const MAX_MESSAGES: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub time: String,
+    pub player: LogPlayer,
+    pub message: String,
+    pub team_only: bool,
+}
+
+/// The in-game chat of a session, read from `say` and `say_team` in the server log,
+/// with an input line that answers with `say`.
+///
+/// Lines starting with `/` are for the pane itself: `/mute <name>` and `/unmute [name]`
+/// hide players here only, `/filter [text]` shows only matching messages.
+#[derive(Default)]
+pub struct Chat {
+    messages: VecDeque<ChatMessage>,
+    /// Muted players by SteamID, or by name for bots, with the name they were muted as.
+    muted: BTreeMap<String, String>,
+    filter: String,
+    input: Input,
+    /// Result of the last pane command.
+    notice: Option<String>,
+    /// How many messages from the bottom the view is scrolled up.
+    scroll: usize,
+    page_size: usize,
+    visible: bool,
+    focused: bool,
+    theme: Theme,
+}
+
+impl Chat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn push(&mut self, message: ChatMessage) {
+        if self.messages.len() == MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+        // Keep the view where it was when scrolled up.
+        if self.scroll > 0 {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn is_muted(&self, player: &LogPlayer) -> bool {
+        self.muted.contains_key(&player.key())
+    }
+
+    /// The messages shown with the current mutes and filter, oldest first.
+    pub fn shown(&self) -> Vec<&ChatMessage> {
+        let filter = self.filter.to_lowercase();
+        self.messages
+            .iter()
+            .filter(|m| !self.is_muted(&m.player))
+            .filter(|m| {
+                m.message.to_lowercase().contains(&filter)
+                    || m.player.name.to_lowercase().contains(&filter)
+            })
+            .collect()
+    }
+
+    fn mute(&mut self, name: &str) -> String {
+        // Prefer the SteamID of whoever last wrote under that name.
+        let player = self
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.player.name.eq_ignore_ascii_case(name))
+            .map(|m| m.player.clone())
+            .unwrap_or_else(|| LogPlayer {
+                name: name.to_string(),
+                ..Default::default()
+            });
+        self.muted.insert(player.key(), player.name.clone());
+        format!("Muted {}", player.name)
+    }
+
+    fn unmute(&mut self, name: &str) -> String {
+        if name.is_empty() {
+            self.muted.clear();
+            return "Unmuted everyone".to_string();
+        }
+        let before = self.muted.len();
+        self.muted
+            .retain(|_, muted| !muted.eq_ignore_ascii_case(name));
+        match self.muted.len() < before {
+            true => format!("Unmuted {}", name),
+            false => format!("{} is not muted", name),
+        }
+    }
+
+    /// Handle a pane command or send the input line to the server.
+    fn submit(&mut self) -> Option<Action> {
+        let text = self.input.value().trim().to_string();
+        self.input.reset();
+        if text.is_empty() {
+            return None;
+        }
+        let Some(command) = text.strip_prefix('/') else {
+            self.notice = None;
+            self.scroll = 0;
+            return Some(Action::Command(format!("say {}", text)));
+        };
+        let (name, argument) = command
+            .split_once(' ')
+            .map(|(name, argument)| (name, argument.trim()))
+            .unwrap_or((command, ""));
+        self.notice = Some(match (name, argument) {
+            ("mute", "") => "Usage: /mute <name>".to_string(),
+            ("mute", name) => self.mute(name),
+            ("unmute", name) => self.unmute(name),
+            ("filter", filter) => {
+                self.filter = filter.to_string();
+                self.scroll = 0;
+                match filter.is_empty() {
+                    true => "Filter cleared".to_string(),
+                    false => format!("Showing messages with `{}`", filter),
+                }
+            }
+            _ => format!("Unknown command /{}, try /mute, /unmute or /filter", name),
+        });
+        None
+    }
+
+    fn team_style(&self, team: &str) -> Style {
+        match side(team) {
+            Some("CT") => self.theme.get("team_ct"),
+            Some(_) => self.theme.get("team_t"),
+            None => self.theme.get("hint"),
+        }
+    }
+
+    fn title(&self) -> Line<'_> {
+        let mut spans = vec![Span::styled("chat", self.theme.get("title"))];
+        if !self.muted.is_empty() {
+            spans.push(Span::styled(
+                format!(" {} muted", self.muted.len()),
+                self.theme.get("hint"),
+            ));
+        }
+        if !self.filter.is_empty() {
+            spans.push(Span::styled(" filter: ", self.theme.get("hint")));
+            spans.push(Span::styled(
+                self.filter.as_str(),
+                self.theme.get("argument"),
+            ));
+        }
+        if self.scroll > 0 {
+            spans.push(Span::styled(
+                format!(" +{}", self.scroll),
+                self.theme.get("hint"),
+            ));
+        }
+        Line::from(spans)
+    }
+
+    fn message_line<'a>(&self, message: &'a ChatMessage) -> Line<'a> {
+        let mut spans = vec![Span::styled(
+            format!("{} ", message.time),
+            self.theme.get("hint"),
+        )];
+        if message.team_only {
+            spans.push(Span::styled("(team) ", self.theme.get("hint")));
+        }
+        spans.push(Span::styled(
+            message.player.name.as_str(),
+            self.team_style(&message.player.team),
+        ));
+        spans.push(Span::styled(": ", self.theme.get("hint")));
+        spans.push(Span::styled(
+            message.message.as_str(),
+            self.theme.get("output"),
+        ));
+        Line::from(spans)
+    }
+}
+
+impl Component for Chat {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.theme = config.theme(Mode::Chat);
+        Ok(())
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        match key.code {
+            KeyCode::Char(c)
+                if self.focused
+                    && !key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.input.handle(InputRequest::InsertChar(c));
+                Ok(Some(Action::Update))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        if !self.focused {
+            return Ok(None);
+        }
+        // Chat messages are a single line.
+        for c in text.lines().next().unwrap_or_default().chars() {
+            self.input.handle(InputRequest::InsertChar(c));
+        }
+        Ok(Some(Action::Update))
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Log(line) => {
+                if let LogEvent::Say {
+                    player,
+                    message,
+                    team_only,
+                } = line.event
+                {
+                    self.push(ChatMessage {
+                        time: line.time,
+                        player,
+                        message,
+                        team_only,
+                    });
+                }
+            }
+            Action::ToggleChat => {
+                self.visible = !self.visible;
+                // A hidden pane cannot be typed into.
+                if !self.visible && self.focused {
+                    return Ok(Some(Action::EnterNormal));
+                }
+            }
+            Action::FocusChat => self.visible = true,
+            Action::ModeChanged(mode) => self.focused = mode == Mode::Chat,
+            Action::Submit if self.focused => return Ok(self.submit()),
+            Action::PrevPage if self.focused => {
+                let last = self.shown().len().saturating_sub(1);
+                self.scroll = (self.scroll + self.page_size.max(1)).min(last);
+            }
+            Action::NextPage if self.focused => {
+                self.scroll = self.scroll.saturating_sub(self.page_size.max(1));
+            }
+            action if self.focused => {
+                if let Some(request) = input_request(&action) {
+                    self.input.handle(request);
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, rect: Rect) -> Result<()> {
+        let mut block = Block::default()
+            .title(self.title())
+            .borders(Borders::ALL)
+            .border_style(match self.focused {
+                true => self.theme.get("border_active"),
+                false => self.theme.get("border"),
+            });
+        if let Some(notice) = &self.notice {
+            block = block.title(
+                Title::from(Span::styled(notice.as_str(), self.theme.get("hint")))
+                    .position(block::Position::Bottom),
+            );
+        }
+        let inner = block.inner(rect);
+        f.render_widget(block, rect);
+        if inner.height == 0 {
+            return Ok(());
+        }
+
+        // The last row is the input line.
+        let messages_rect = Rect {
+            height: inner.height - 1,
+            ..inner
+        };
+        let input_rect = Rect {
+            y: inner.y + inner.height - 1,
+            height: 1,
+            ..inner
+        };
+        self.page_size = messages_rect.height as usize;
+
+        // Fill the rows from the bottom, long messages wrap over several.
+        let shown = self.shown();
+        let end = shown.len().saturating_sub(self.scroll);
+        let width = messages_rect.width.max(1) as usize;
+        let mut rows = 0;
+        let mut lines = Vec::new();
+        for message in shown[..end].iter().rev() {
+            let line = self.message_line(message);
+            rows += line.width().max(1).div_ceil(width);
+            if rows > self.page_size {
+                break;
+            }
+            lines.insert(0, line);
+        }
+        f.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: false }),
+            messages_rect,
+        );
+
+        let width = input_rect.width.saturating_sub(3) as usize;
+        let scroll = self.input.visual_scroll(width);
+        let prompt = match self.focused {
+            true => Span::styled("> ", self.theme.get("input_active")),
+            false => Span::styled("> ", self.theme.get("hint")),
+        };
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                prompt,
+                Span::styled(
+                    self.input.value().chars().skip(scroll).collect::<String>(),
+                    self.theme.get("input"),
+                ),
+            ])),
+            input_rect,
+        );
+        if self.focused {
+            f.set_cursor(
+                (input_rect.x + 2 + (self.input.visual_cursor() - scroll) as u16)
+                    .min(input_rect.x + input_rect.width.saturating_sub(1)),
+                input_rect.y,
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::command::logs::parse_line;
+
+    fn say(chat: &mut Chat, player: &str, message: &str) -> Result<()> {
+        let line = parse_line(&format!(
+            "10/18/2026 - 20:15:04.001 - \"{}\" say \"{}\"",
+            player, message
+        ))
+        .unwrap();
+        chat.update(Action::Log(Box::new(line)))?;
+        Ok(())
+    }
+
+    fn submit(chat: &mut Chat, text: &str) -> Result<Option<Action>> {
+        chat.input = Input::new(text.to_string());
+        chat.update(Action::Submit)
+    }
+
+    fn shown(chat: &Chat) -> Vec<String> {
+        chat.shown()
+            .iter()
+            .map(|m| format!("{}: {}", m.player.name, m.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_messages_come_from_say_events() -> Result<()> {
+        let mut chat = Chat::new();
+        say(&mut chat, "Bob<3><[U:1:1002]><TERRORIST>", "gl hf")?;
+        let line = parse_line("10/18/2026 - 20:15:05.000 - World triggered \"Round_Start\"");
+        chat.update(Action::Log(Box::new(line.unwrap())))?;
+        assert_eq!(shown(&chat), vec!["Bob: gl hf"]);
+        assert_eq!(chat.shown()[0].time, "20:15:04");
+
+        for i in 0..MAX_MESSAGES {
+            say(&mut chat, "Bob<3><[U:1:1002]><TERRORIST>", &i.to_string())?;
+        }
+        assert_eq!(chat.shown().len(), MAX_MESSAGES);
+        assert_eq!(chat.shown()[0].message, "0");
+        Ok(())
+    }
+
+    #[test]
+    fn test_mute_and_filter() -> Result<()> {
+        let mut chat = Chat::new();
+        chat.update(Action::ModeChanged(Mode::Chat))?;
+        say(&mut chat, "Bob<3><[U:1:1002]><TERRORIST>", "rush b")?;
+        say(&mut chat, "Alice<2><[U:1:1001]><CT>", "gl hf")?;
+        say(&mut chat, "Bot Ava<12><BOT><CT>", "hello")?;
+
+        assert_eq!(submit(&mut chat, "/mute bob")?, None);
+        assert_eq!(chat.notice.as_deref(), Some("Muted Bob"));
+        // A new name does not get around the mute.
+        say(&mut chat, "B0b<3><[U:1:1002]><TERRORIST>", "still here")?;
+        submit(&mut chat, "/mute Bot Ava")?;
+        assert_eq!(shown(&chat), vec!["Alice: gl hf"]);
+
+        submit(&mut chat, "/unmute bob")?;
+        submit(&mut chat, "/filter HERE")?;
+        assert_eq!(shown(&chat), vec!["B0b: still here"]);
+        submit(&mut chat, "/filter")?;
+        submit(&mut chat, "/unmute")?;
+        assert_eq!(chat.shown().len(), 4);
+
+        submit(&mut chat, "/nope")?;
+        assert!(chat
+            .notice
+            .as_deref()
+            .unwrap()
+            .starts_with("Unknown command"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reply_sends_say() -> Result<()> {
+        let mut chat = Chat::new();
+        // Only the focused pane takes the submit.
+        assert_eq!(submit(&mut chat, "hello")?, None);
+        chat.input.reset();
+        chat.update(Action::ModeChanged(Mode::Chat))?;
+        chat.handle_key_events(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::empty()))?;
+        chat.handle_key_events(KeyEvent::new(KeyCode::Char('i'), KeyModifiers::empty()))?;
+        assert_eq!(
+            chat.update(Action::Submit)?,
+            Some(Action::Command("say hi".to_string()))
+        );
+        assert_eq!(chat.input.value(), "");
+        assert_eq!(submit(&mut chat, "   ")?, None);
+
+        chat.update(Action::ModeChanged(Mode::Normal))?;
+        chat.handle_key_events(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::empty()))?;
+        assert_eq!(chat.input.value(), "");
+        Ok(())
+    }
+}
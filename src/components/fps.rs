@@ -4,7 +4,7 @@ use color_eyre::eyre::Result;
 use ratatui::{prelude::*, widgets::*};
 
 use super::Component;
-use crate::{action::Action, tui::Frame};
+use crate::{action::Action, config::Config, mode::Mode, theme::Theme, tui::Frame};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Ticker {
@@ -21,6 +21,8 @@ pub struct FpsCounter {
     render_start_time: Instant,
     render_frames: u32,
     render_fps: f64,
+
+    theme: Theme,
 }
 
 impl Default for FpsCounter {
@@ -38,6 +40,7 @@ impl FpsCounter {
             render_start_time: Instant::now(),
             render_frames: 0,
             render_fps: 0.0,
+            theme: Theme::default(),
         }
     }
 
@@ -67,6 +70,11 @@ impl FpsCounter {
 }
 
 impl Component for FpsCounter {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.theme = config.theme(Mode::Normal);
+        Ok(())
+    }
+
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         if let Action::Tick = action {
             self.app_tick()?
@@ -81,7 +89,7 @@ impl Component for FpsCounter {
         let rects = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
-                Constraint::Length(3), // skip first 3 rows
+                Constraint::Length(4), // skip the tab bar and the input
                 Constraint::Length(1), // first row
                 Constraint::Min(0),
             ])
@@ -93,7 +101,9 @@ impl Component for FpsCounter {
             "{:.2} fps (app) {:.2} fps (render)",
             self.app_fps, self.render_fps
         );
-        let block = Block::default().title(block::Title::from(s.dim()).alignment(Alignment::Right));
+        let block = Block::default().title(
+            block::Title::from(Span::styled(s, self.theme.get("hint"))).alignment(Alignment::Right),
+        );
         f.render_widget(block, rect);
         Ok(())
     }
@@ -1,7 +1,7 @@
 use std::{collections::HashMap, process::Command, time::Duration};
 
 use color_eyre::{eyre::Result, owo_colors::OwoColorize};
-use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use log::error;
 use ratatui::{
     prelude::*,
@@ -9,18 +9,28 @@ use ratatui::{
 };
 use tokio::sync::mpsc::UnboundedSender;
 use tracing::trace;
-use tui_input::backend::crossterm::EventHandler;
+use tui_input::InputRequest;
 
 use super::{Component, Frame};
-use crate::{action::Action, config::key_event_to_string, inputwrapper::Inputwrapper};
+use crate::{
+    action::Action,
+    broadcast::ServerResult,
+    browser::CatalogBrowser,
+    command::{builtin::BUILTINS, cfg},
+    config::{key_event_to_string, Config, KeyBindings, Profile},
+    editor::Editor,
+    inputwrapper::{input_request, Inputwrapper},
+    mode::Mode,
+    palette::{self, Choice, Entry, EntryKind, Palette},
+    popup::Popup,
+    theme::Theme,
+};
 
-#[derive(Default, Copy, Clone, PartialEq, Eq)]
-pub enum Mode {
-    #[default]
-    Insert,
-    Normal,
-    Processing,
-    Help,
+pub struct Confirmation {
+    pub action: Action,
+    pub title: String,
+    pub lines: Vec<String>,
+    pub prompt: String,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,6 +44,9 @@ pub enum ButtonState {
 #[derive(Default)]
 pub struct Home {
     pub show_help: bool,
+    pub help_filter: String,
+    pub help_scroll: usize,
+    pub keybindings: KeyBindings,
     pub app_ticker: usize,
     pub render_ticker: usize,
     pub mode: Mode,
@@ -43,7 +56,19 @@ pub struct Home {
     pub text: Vec<String>,
     pub connected: bool,
     pub error: Option<String>,
+    pub warning: Option<String>,
+    pub confirm: Option<Confirmation>,
+    pub editor: Option<Editor>,
+    pub theme: Theme,
+    pub themes: HashMap<Mode, Theme>,
+    pub browser: Option<CatalogBrowser>,
+    pub palette: Option<Palette>,
+    /// The group and command of the last broadcast, with the results once they arrived.
+    pub broadcast: Option<(String, String, Option<Vec<ServerResult>>)>,
+    pub profiles: HashMap<String, Profile>,
+    pub previous_mode: Mode,
     pub last_events: Vec<KeyEvent>,
+    pub pending_keys: Vec<KeyEvent>,
     pub main_rect: Rect,
     pub input_rect: Rect,
 }
@@ -82,6 +107,34 @@ impl Home {
         self.text.pop();
     }
 
+    /// Switch input state, picking up the styles of the new mode and telling the app
+    /// which keymap to use.
+    fn set_mode(&mut self, mode: Mode) {
+        if self.mode == mode {
+            return;
+        }
+        self.mode = mode;
+        if let Some(theme) = self.themes.get(&mode) {
+            self.theme = theme.clone();
+        }
+        if let Some(sender) = &self.action_tx {
+            if let Err(e) = sender.send(Action::ModeChanged(mode)) {
+                error!("Failed to send action: {:?}", e);
+            }
+        }
+    }
+
+    /// Open an overlay mode, remembering where to return to when it closes.
+    fn push_mode(&mut self, mode: Mode) {
+        if !matches!(
+            self.mode,
+            Mode::Browse | Mode::Palette | Mode::Confirm | Mode::Help
+        ) {
+            self.previous_mode = self.mode;
+        }
+        self.set_mode(mode);
+    }
+
     pub fn main_widget(&mut self) -> Paragraph<'_> {
         let mut text: Vec<Line> = self
             .text
@@ -92,34 +145,41 @@ impl Home {
         text.insert(0, "".into());
         text.insert(
             0,
-            match self.error.clone() {
-                Some(e) => Line::from(vec![Span::styled(
+            match (self.error.clone(), self.warning.clone()) {
+                (Some(e), _) => Line::from(vec![Span::styled(
                     format!("Error: {}", e),
-                    Style::default().fg(Color::Red),
+                    self.theme.get("error"),
+                )]),
+                (None, Some(w)) => Line::from(vec![Span::styled(
+                    format!("Warning: {}", w),
+                    self.theme.get("warning"),
                 )]),
-                None => Line::from(vec!["".into()]),
+                (None, None) => Line::from(vec!["".into()]),
             },
         );
         text.insert(0, "".into());
-        text.insert(0, "Type commands under and hit enter".dim().into());
+        text.insert(
+            0,
+            Span::styled("Type commands under and hit enter", self.theme.get("hint")).into(),
+        );
         text.insert(0, "".into());
         text.insert(
             0,
             Line::from(vec![
                 "Connect with the command: ".into(),
-                Span::styled("connect <ip>", Style::default().fg(Color::Red)),
+                Span::styled("connect <ip>", self.theme.get("argument")),
                 ":".into(),
-                Span::styled("<port>", Style::default().fg(Color::Red)),
+                Span::styled("<port>", self.theme.get("argument")),
                 " (".into(),
-                Span::styled("<password>", Style::default().fg(Color::Yellow)),
+                Span::styled("<password>", self.theme.get("optional_argument")),
                 ")".into(),
             ]),
         );
         text.insert(
             0,
             Line::from(vec![match self.connected {
-                true => Span::styled("Connected", Style::default().fg(Color::Green)),
-                false => Span::styled("Not Connected", Style::default().fg(Color::Red)),
+                true => Span::styled("Connected", self.theme.get("connected")),
+                false => Span::styled("Not Connected", self.theme.get("disconnected")),
             }]),
         );
         text.insert(0, "".into());
@@ -128,12 +188,12 @@ impl Home {
                 Block::default()
                     .borders(Borders::ALL)
                     .border_style(match self.mode {
-                        Mode::Processing => Style::default().fg(Color::Yellow),
-                        _ => Style::default(),
+                        Mode::Processing => self.theme.get("border_active"),
+                        _ => self.theme.get("border"),
                     })
                     .border_type(BorderType::Rounded),
             )
-            .style(Style::default().fg(Color::Cyan))
+            .style(self.theme.get("output"))
             .alignment(Alignment::Center)
             .wrap(Wrap { trim: false })
     }
@@ -150,7 +210,7 @@ impl Home {
                         (s.clone().drain(command_offset..).collect::<String>()).to_string();
                     Line::from(vec![
                         Span::raw(self.input.value()),
-                        Span::from(suggestion).dim(),
+                        Span::styled(suggestion, self.theme.get("suggestion")),
                     ])
                 }
                 _ => Line::from(self.input.value()),
@@ -158,85 +218,612 @@ impl Home {
         };
         Paragraph::new(text)
             .style(match self.mode {
-                Mode::Insert => Style::default().fg(Color::Yellow),
-                _ => Style::default(),
+                Mode::Insert => self.theme.get("input_active"),
+                _ => self.theme.get("input"),
             })
             .scroll((0, scroll as u16))
             .block(
                 Block::default()
                     .borders(Borders::ALL)
+                    .border_style(self.theme.get("border"))
                     .title_alignment(Alignment::Right)
                     .title_position(block::Position::Bottom)
-                    .title(Line::from(vec![
-                        Span::raw("Enter Input Mode "),
-                        Span::styled("(Press ", Style::default().fg(Color::DarkGray)),
-                        Span::styled(
-                            "/",
-                            Style::default()
-                                .add_modifier(Modifier::BOLD)
-                                .fg(Color::Gray),
-                        ),
-                        Span::styled(" to start, ", Style::default().fg(Color::DarkGray)),
-                        Span::styled(
-                            "Enter",
-                            Style::default()
-                                .add_modifier(Modifier::BOLD)
-                                .fg(Color::Gray),
-                        ),
-                        Span::styled(" to send, ", Style::default().fg(Color::DarkGray)),
-                        Span::styled(
-                            "ESC",
-                            Style::default()
-                                .add_modifier(Modifier::BOLD)
-                                .fg(Color::Gray),
-                        ),
-                        Span::styled(" to finish)", Style::default().fg(Color::DarkGray)),
-                    ])),
+                    .title(self.hint_title(
+                        "Enter Input Mode ",
+                        &[
+                            (Mode::Normal, Action::EnterInsert, "start"),
+                            (Mode::Insert, Action::Submit, "send"),
+                            (Mode::Insert, Action::EnterNormal, "finish"),
+                        ],
+                    )),
             )
     }
 
-    fn help_widget(&mut self) -> (Block<'_>, Table<'_>) {
+    /// The key bound to `action` in `mode`, preferring the shortest binding.
+    fn key_hint(&self, mode: Mode, action: &Action) -> Option<String> {
+        self.keybindings
+            .get(&mode)?
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(keys, _)| {
+                keys.iter()
+                    .map(key_event_to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .min_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)))
+    }
+
+    /// A title like `label (Press / to start, enter to send)` styled from the theme,
+    /// showing the keys currently bound to each action.
+    fn hint_title(&self, label: &str, hints: &[(Mode, Action, &str)]) -> Line<'static> {
+        let keys = hints
+            .iter()
+            .filter_map(|(mode, action, description)| {
+                Some((self.key_hint(*mode, action)?, *description))
+            })
+            .collect::<Vec<_>>();
+        let mut spans = vec![Span::raw(label.to_string())];
+        if keys.is_empty() {
+            return Line::from(spans);
+        }
+        spans.push(Span::styled("(Press ", self.theme.get("hint")));
+        for (i, (key, description)) in keys.iter().enumerate() {
+            let separator = match i + 1 == keys.len() {
+                true => ")",
+                false => ", ",
+            };
+            spans.push(Span::styled(key.to_string(), self.theme.get("key")));
+            spans.push(Span::styled(
+                format!(" to {}{}", description, separator),
+                self.theme.get("hint"),
+            ));
+        }
+        Line::from(spans)
+    }
+
+    /// Sections of the help screen, generated from the active keybindings and the built-in commands.
+    fn help_sections(&self) -> Vec<(String, Vec<(String, String)>)> {
+        let mut sections = Vec::new();
+        let mut modes = self.keybindings.keys().collect::<Vec<_>>();
+        modes.sort_by_key(|mode| format!("{:?}", mode));
+        for mode in modes {
+            let mut rows = self.keybindings[mode]
+                .iter()
+                .map(|(keys, action)| {
+                    let keys = keys
+                        .iter()
+                        .map(|key| format!("<{}>", key_event_to_string(key)))
+                        .collect::<String>();
+                    (keys, action.to_string())
+                })
+                .collect::<Vec<_>>();
+            rows.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+            sections.push((format!("{:?} keys", mode), rows));
+        }
+        sections.push((
+            "Commands".to_string(),
+            BUILTINS
+                .iter()
+                .map(|b| (b.usage.to_string(), b.description.to_string()))
+                .collect(),
+        ));
+
+        let filter = self.help_filter.to_lowercase();
+        sections
+            .into_iter()
+            .map(|(title, rows)| {
+                let rows = rows
+                    .into_iter()
+                    .filter(|(key, description)| {
+                        key.to_lowercase().contains(&filter)
+                            || description.to_lowercase().contains(&filter)
+                    })
+                    .collect::<Vec<_>>();
+                (title, rows)
+            })
+            .filter(|(_, rows)| !rows.is_empty())
+            .collect()
+    }
+
+    fn help_widget(&mut self, height: u16) -> (Block<'_>, Table<'_>) {
+        let title = match self.help_filter.is_empty() {
+            true => Line::from(vec![
+                Span::styled("Help ", self.theme.get("title")),
+                Span::styled("(type to filter)", self.theme.get("hint")),
+            ]),
+            false => Line::from(vec![
+                Span::styled("Help ", self.theme.get("title")),
+                Span::styled("filter: ", self.theme.get("hint")),
+                Span::raw(self.help_filter.clone()),
+            ]),
+        };
         let block = Block::default()
-            .title(Line::from(vec![Span::styled(
-                "Key Bindings",
-                Style::default().add_modifier(Modifier::BOLD),
-            )]))
+            .title(title)
+            .title(
+                Title::from(Span::styled(
+                    "Up/Down to scroll, Esc to close",
+                    self.theme.get("hint"),
+                ))
+                .position(block::Position::Bottom)
+                .alignment(Alignment::Right),
+            )
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Yellow));
-        let rows = vec![
-            Row::new(vec!["/", "Enter Input"]),
-            Row::new(vec!["ESC", "Exit Input"]),
-            Row::new(vec!["Enter", "Submit Input"]),
-            Row::new(vec!["Crtl + c", "Quit"]),
-            Row::new(vec!["?", "Open Help"]),
-        ];
+            .border_style(self.theme.get("popup_border"))
+            .style(self.theme.get("popup"));
+        let mut rows = Vec::new();
+        for (title, section) in self.help_sections() {
+            if !rows.is_empty() {
+                rows.push(Row::new(vec![String::new(), String::new()]));
+            }
+            rows.push(Row::new(vec![title, String::new()]).style(self.theme.get("title")));
+            for (key, description) in section {
+                rows.push(Row::new(vec![key, description]));
+            }
+        }
+        // 2 rows are taken by the header
+        let visible = height.saturating_sub(2) as usize;
+        self.help_scroll = self.help_scroll.min(rows.len().saturating_sub(visible));
+        let rows = rows.into_iter().skip(self.help_scroll).collect::<Vec<_>>();
         let table = Table::new(
             rows,
-            [Constraint::Percentage(10), Constraint::Percentage(90)],
+            [Constraint::Percentage(35), Constraint::Percentage(65)],
         )
         .header(
             Row::new(vec!["Key", "Action"])
                 .bottom_margin(1)
-                .style(Style::default().add_modifier(Modifier::BOLD)),
+                .style(self.theme.get("title")),
         )
         .column_spacing(1);
         (block, table)
     }
 
-    fn title_widget(&mut self) -> Block<'_> {
-        Block::default()
-            .title(
-                Title::from(format!(
-                    "{:?}",
-                    &self
-                        .last_events
+    fn open_browser(&mut self, query: &str) {
+        let mut browser = CatalogBrowser::new(query);
+        browser.set_results(self.input.search(query));
+        self.browser = Some(browser);
+        self.push_mode(Mode::Browse);
+    }
+
+    fn close_browser(&mut self) {
+        self.browser = None;
+        self.set_mode(self.previous_mode);
+    }
+
+    fn select_browser(&mut self) {
+        if let Some(name) = self.browser.as_ref().and_then(|b| b.selection()) {
+            self.browser = None;
+            self.input.set_value(format!("{} ", name));
+            self.set_mode(Mode::Insert);
+        }
+    }
+
+    /// Everything the palette can run: actions, client commands, profiles and cfg files.
+    fn palette_entries(&self) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        for (action, description) in palette::actions() {
+            let key = self
+                .key_hint(Mode::Normal, &action)
+                .or_else(|| self.key_hint(Mode::Insert, &action));
+            entries.push(
+                Entry::new(
+                    EntryKind::Action,
+                    &action.to_string(),
+                    description,
+                    Choice::Run(action),
+                )
+                .key(key),
+            );
+        }
+        for builtin in BUILTINS.iter() {
+            let choice = match builtin.usage == builtin.name {
+                true => Choice::Run(Action::Command(builtin.name.to_string())),
+                false => Choice::Edit(format!("{} ", builtin.name)),
+            };
+            entries.push(Entry::new(
+                EntryKind::Command,
+                builtin.usage,
+                builtin.description,
+                choice,
+            ));
+        }
+        for theme in Theme::names() {
+            entries.push(Entry::new(
+                EntryKind::Command,
+                &format!("theme {}", theme),
+                &format!("Switch to the {} theme", theme),
+                Choice::Run(Action::SetTheme(theme.clone())),
+            ));
+        }
+        let mut profiles = self.profiles.iter().collect::<Vec<_>>();
+        profiles.sort_by_key(|(name, _)| name.as_str());
+        for (name, profile) in profiles {
+            entries.push(Entry::new(
+                EntryKind::Profile,
+                name,
+                &format!("Connect to {}", profile.address),
+                Choice::Run(Action::Connect(
+                    profile.address.clone(),
+                    profile.password.clone(),
+                )),
+            ));
+        }
+        for file in cfg::files() {
+            entries.push(Entry::new(
+                EntryKind::Cfg,
+                &file,
+                &format!("exec {}", file),
+                Choice::Run(Action::Command(format!("exec {}", file))),
+            ));
+        }
+        entries
+    }
+
+    fn open_palette(&mut self) {
+        self.palette = Some(Palette::new(self.palette_entries()));
+        self.push_mode(Mode::Palette);
+    }
+
+    fn close_palette(&mut self) {
+        self.palette = None;
+        self.set_mode(self.previous_mode);
+    }
+
+    fn select_palette(&mut self) -> Option<Action> {
+        let entry = self.palette.as_ref()?.selection()?.clone();
+        self.close_palette();
+        match entry.choice {
+            Choice::Run(action) => {
+                // Actions are bound in normal mode, so run them from there.
+                if entry.kind == EntryKind::Action {
+                    self.set_mode(Mode::Normal);
+                }
+                Some(action)
+            }
+            Choice::Edit(text) => {
+                self.input.set_value(text);
+                self.set_mode(Mode::Insert);
+                None
+            }
+        }
+    }
+
+    fn expand_editor(&mut self, text: &str) {
+        let mut editor = self.editor.take().unwrap_or_default();
+        editor.insert_str(self.input.value());
+        editor.insert_str(text);
+        self.input.reset();
+        self.editor = Some(editor);
+        self.set_mode(Mode::Multiline);
+    }
+
+    fn submit_editor(&mut self) -> Action {
+        let Some(editor) = self.editor.as_ref() else {
+            return Action::Update;
+        };
+        let text = editor.text();
+        match editor.line_count() {
+            0 => Action::Update,
+            1 => {
+                self.editor = None;
+                self.set_mode(Mode::Insert);
+                Action::Command(text.trim().to_string())
+            }
+            count => {
+                let mut lines = vec![format!("Send {} lines to the server?", count)];
+                lines.extend(
+                    editor
+                        .lines()
                         .iter()
-                        .map(key_event_to_string)
-                        .collect::<Vec<_>>()
+                        .filter(|l| !l.trim().is_empty())
+                        .take(3)
+                        .cloned(),
+                );
+                if count > 3 {
+                    lines.push("...".to_string());
+                }
+                self.ask(Confirmation {
+                    action: Action::SendBlock(text),
+                    title: "Send block".to_string(),
+                    lines,
+                    prompt: "Send? ".to_string(),
+                });
+                Action::Update
+            }
+        }
+    }
+
+    fn ask(&mut self, confirmation: Confirmation) {
+        self.confirm = Some(confirmation);
+        self.push_mode(Mode::Confirm);
+    }
+
+    fn answer(&mut self, accepted: bool) -> Option<Action> {
+        let confirmation = self.confirm.take()?;
+        if !accepted {
+            self.set_mode(self.previous_mode);
+            return None;
+        }
+        match confirmation.action {
+            Action::SendBlock(_) => {
+                self.editor = None;
+                self.set_mode(Mode::Insert);
+            }
+            _ => self.set_mode(self.previous_mode),
+        }
+        Some(confirmation.action)
+    }
+
+    /// Send the command in the input line, handling the commands that only affect the UI.
+    fn submit_input(&mut self) -> Option<Action> {
+        let command = self.input.value().to_string();
+        self.input.push_history(command.clone());
+        self.input.reset();
+        match command.split(' ').next() {
+            Some("theme") => Some(match command.split_whitespace().nth(1) {
+                Some(name) => Action::SetTheme(name.to_string()),
+                None => Action::Insert(format!("Themes: {}", Theme::names().join(", "))),
+            }),
+            Some("apropos") => {
+                self.open_browser(command.trim_start_matches("apropos").trim());
+                None
+            }
+            Some("match") => {
+                self.error = None;
+                self.warning = None;
+                Some(Action::Match(
+                    command.trim_start_matches("match").trim().to_string(),
                 ))
-                .alignment(Alignment::Right),
+            }
+            Some("report") => {
+                self.error = None;
+                self.warning = None;
+                Some(Action::Report)
+            }
+            Some("broadcast") => {
+                self.error = None;
+                self.warning = None;
+                let mut words = command.splitn(3, ' ').skip(1);
+                Some(match (words.next(), words.next().map(str::trim)) {
+                    (Some(group), Some(command)) if !group.is_empty() && !command.is_empty() => {
+                        Action::Broadcast(group.to_string(), command.to_string())
+                    }
+                    _ => Action::Error("Usage: broadcast <group> <command>".to_string()),
+                })
+            }
+            _ => {
+                self.error = None;
+                self.warning = None;
+                self.broadcast = None;
+                self.input.invalidate_convar(&command);
+                log::info!("Sending action: {:?}", Action::Command(command.clone()));
+                Some(Action::Command(command))
+            }
+        }
+    }
+
+    /// Type a character into whatever text field the current mode is editing.
+    fn insert_char(&mut self, c: char) {
+        self.edit(InputRequest::InsertChar(c));
+    }
+
+    fn edit(&mut self, request: InputRequest) {
+        match self.mode {
+            Mode::Insert => self.input.handle(request),
+            Mode::Multiline => {
+                if let Some(editor) = self.editor.as_mut() {
+                    editor.handle(request);
+                }
+            }
+            Mode::Browse => {
+                if let Some(browser) = self.browser.as_mut() {
+                    if let Some(query) = browser.edit(request) {
+                        let results = self.input.search(&query);
+                        browser.set_results(results);
+                    }
+                }
+            }
+            Mode::Palette => {
+                if let Some(palette) = self.palette.as_mut() {
+                    palette.edit(request);
+                }
+            }
+            Mode::Help => {
+                match request {
+                    InputRequest::InsertChar(c) => self.help_filter.push(c),
+                    InputRequest::DeletePrevChar => {
+                        self.help_filter.pop();
+                    }
+                    InputRequest::DeleteLine => self.help_filter.clear(),
+                    _ => return,
+                }
+                self.help_scroll = 0;
+            }
+            // The chat pane edits its own input line.
+            Mode::Normal | Mode::Confirm | Mode::Processing | Mode::Chat => {}
+        }
+    }
+
+    /// Move through the list shown by the current mode.
+    fn scroll(&mut self, action: &Action) {
+        match (self.mode, self.browser.as_mut()) {
+            (Mode::Browse, Some(browser)) => match action {
+                Action::PrevItem => browser.prev_item(),
+                Action::NextItem => browser.next_item(),
+                Action::PrevPage => browser.prev_page(),
+                Action::NextPage => browser.next_page(),
+                _ => {}
+            },
+            (Mode::Palette, _) => {
+                if let Some(palette) = self.palette.as_mut() {
+                    match action {
+                        Action::PrevItem => palette.prev_item(),
+                        Action::NextItem => palette.next_item(),
+                        Action::PrevPage => palette.prev_page(),
+                        Action::NextPage => palette.next_page(),
+                        _ => {}
+                    }
+                }
+            }
+            (Mode::Help, _) => {
+                self.help_scroll = match action {
+                    Action::PrevItem => self.help_scroll.saturating_sub(1),
+                    Action::NextItem => self.help_scroll.saturating_add(1),
+                    Action::PrevPage => self.help_scroll.saturating_sub(10),
+                    Action::NextPage => self.help_scroll.saturating_add(10),
+                    _ => self.help_scroll,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn editor_widget(&self, height: u16) -> Paragraph<'_> {
+        let (lines, row) = match &self.editor {
+            Some(editor) => (editor.lines().to_vec(), editor.cursor().1),
+            None => (Vec::new(), 0),
+        };
+        let count = self.editor.as_ref().map(|e| e.line_count()).unwrap_or(0);
+        let scroll = row.saturating_sub(height.saturating_sub(3) as usize);
+        Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+            .style(match self.mode {
+                Mode::Multiline => self.theme.get("input_active"),
+                _ => self.theme.get("input"),
+            })
+            .scroll((scroll as u16, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.get("border"))
+                    .title_alignment(Alignment::Right)
+                    .title_position(block::Position::Bottom)
+                    .title(self.hint_title(
+                        &format!("{} lines ", count),
+                        &[
+                            (Mode::Multiline, Action::Submit, "send"),
+                            (Mode::Multiline, Action::Discard, "discard"),
+                            (Mode::Multiline, Action::EnterNormal, "finish"),
+                        ],
+                    )),
             )
-            .title_style(Style::default().add_modifier(Modifier::BOLD))
+    }
+
+    /// The results of the last broadcast, one column per server.
+    fn draw_broadcast(&self, f: &mut Frame<'_>, area: Rect) {
+        let Some((group, command, results)) = &self.broadcast else {
+            return;
+        };
+        let block = Block::default()
+            .title(Line::from(vec![
+                Span::styled("broadcast ", self.theme.get("title")),
+                Span::styled(group.as_str(), self.theme.get("argument")),
+                Span::styled(format!(": {}", command), self.theme.get("title")),
+            ]))
+            .borders(Borders::ALL)
+            .border_style(self.theme.get("border"));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let Some(results) = results else {
+            f.render_widget(
+                Paragraph::new(Span::styled(
+                    "Waiting for the servers...",
+                    self.theme.get("hint"),
+                )),
+                inner,
+            );
+            return;
+        };
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                results
+                    .iter()
+                    .map(|_| Constraint::Ratio(1, results.len() as u32))
+                    .collect::<Vec<_>>(),
+            )
+            .split(inner);
+        for (result, column) in results.iter().zip(columns.iter()) {
+            let (mark, style) = match result.ok() {
+                true => ("✓ ", self.theme.get("connected")),
+                false => ("✗ ", self.theme.get("error")),
+            };
+            let mut lines = result
+                .response
+                .lines()
+                .map(|l| Line::styled(l.to_string(), self.theme.get("output")))
+                .collect::<Vec<_>>();
+            if let Some(error) = &result.error {
+                lines.extend(error.lines().map(|l| Line::styled(l.to_string(), style)));
+            }
+            let paragraph = Paragraph::new(lines).block(
+                Block::default()
+                    .title(Line::from(vec![
+                        Span::styled(mark, style),
+                        Span::styled(result.server.as_str(), style),
+                    ]))
+                    .title(
+                        Title::from(Span::styled(
+                            result.address.as_str(),
+                            self.theme.get("hint"),
+                        ))
+                        .position(block::Position::Bottom),
+                    )
+                    .borders(Borders::ALL)
+                    .border_style(style),
+            );
+            f.render_widget(paragraph, *column);
+        }
+    }
+
+    fn confirm_widget(&self) -> Option<Popup<'_>> {
+        let confirmation = self.confirm.as_ref()?;
+        let mut lines = confirmation
+            .lines
+            .iter()
+            .map(|l| Line::from(l.as_str()))
+            .collect::<Vec<_>>();
+        if let Some(first) = lines.first_mut() {
+            first.patch_style(self.theme.get("title"));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::raw(confirmation.prompt.as_str()),
+            Span::styled("(y/n)", self.theme.get("hint")),
+        ]));
+        Some(
+            Popup::default()
+                .title(confirmation.title.as_str())
+                .content(lines)
+                .style(self.theme.get("popup"))
+                .border_style(self.theme.get("popup_border")),
+        )
+    }
+
+    /// Recent keys, or the keys of a chord waiting for its next key.
+    fn title_widget(&mut self) -> Block<'_> {
+        let title = match self.pending_keys.is_empty() {
+            true => Line::from(format!(
+                "{:?}",
+                &self
+                    .last_events
+                    .iter()
+                    .map(key_event_to_string)
+                    .collect::<Vec<_>>()
+            )),
+            false => Line::from(vec![
+                Span::styled(
+                    self.pending_keys
+                        .iter()
+                        .map(|key| format!("<{}>", key_event_to_string(key)))
+                        .collect::<String>(),
+                    self.theme.get("key"),
+                ),
+                Span::styled(" ...", self.theme.get("hint")),
+            ]),
+        };
+        Block::default()
+            .title(Title::from(title).alignment(Alignment::Right))
+            .title_style(self.theme.get("title"))
     }
 }
 
@@ -246,118 +833,287 @@ impl Component for Home {
         Ok(())
     }
 
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.themes = config
+            .keybindings
+            .keys()
+            .map(|mode| (*mode, config.theme(*mode)))
+            .collect();
+        self.theme = config.theme(self.mode);
+        self.keybindings = config.keybindings;
+        self.profiles = config.profiles;
+        Ok(())
+    }
+
     fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        // Bound keys reach us as actions from the keymap of the current mode,
+        // so anything left is typed.
         self.last_events.push(key);
-        let action = match self.mode {
-            Mode::Normal | Mode::Processing | Mode::Help => return Ok(None),
-            Mode::Insert => match key.code {
-                KeyCode::Esc => Action::EnterNormal,
-                KeyCode::Enter => {
-                    self.error = None;
-                    self.input.push_history(self.input.value().to_string());
-                    if let Some(sender) = &self.action_tx {
-                        log::info!(
-                            "Sending action: {:?}",
-                            Action::Command(self.input.value().to_string())
-                        );
-                        if let Err(e) = sender.send(Action::Command(self.input.value().to_string()))
-                        {
-                            error!("Failed to send action: {:?}", e);
-                        }
-                        self.input.reset();
-                    }
-                    Action::Update
+        match key.code {
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.insert_char(c);
+                Ok(Some(Action::Update))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        match self.mode {
+            Mode::Multiline => {
+                if let Some(editor) = self.editor.as_mut() {
+                    editor.insert_str(&text);
                 }
-                _ => {
-                    self.input.handle_event(&crossterm::event::Event::Key(key));
-                    Action::Update
+            }
+            Mode::Insert | Mode::Normal => {
+                let text = text.trim_end_matches(['\r', '\n']);
+                if text.contains('\n') || self.editor.is_some() {
+                    self.expand_editor(text);
+                } else {
+                    self.input.insert_str(text);
+                    self.set_mode(Mode::Insert);
                 }
-            },
-        };
-        Ok(Some(action))
+            }
+            Mode::Processing
+            | Mode::Help
+            | Mode::Browse
+            | Mode::Palette
+            | Mode::Confirm
+            | Mode::Chat => return Ok(None),
+        }
+        Ok(Some(Action::Update))
     }
 
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         match action {
             Action::Tick => self.tick(),
-            Action::Render => self.render_tick(),
-            Action::ToggleShowHelp if self.mode != Mode::Insert => {
+            Action::Render => {
+                self.render_tick();
+                if self.connected && self.mode == Mode::Insert {
+                    if let Some(name) = self.input.convar_query_due() {
+                        return Ok(Some(Action::QueryConvar(name)));
+                    }
+                }
+            }
+            Action::ConvarValue(name, value, default) => {
+                self.input.set_convar_value(name, value, default);
+            }
+            Action::ToggleShowHelp if matches!(self.mode, Mode::Normal | Mode::Help) => {
                 self.show_help = !self.show_help;
+                self.help_filter.clear();
+                self.help_scroll = 0;
                 if self.show_help {
-                    self.mode = Mode::Help;
+                    self.set_mode(Mode::Help);
                 } else {
-                    self.mode = Mode::Normal;
+                    self.set_mode(Mode::Normal);
                 }
             }
             Action::Insert(s) => self.insert(s),
-            Action::EnterNormal => {
-                self.mode = Mode::Normal;
+            Action::PendingKeys(keys) => self.pending_keys = keys,
+            Action::Apropos(query) if self.mode != Mode::Browse => self.open_browser(&query),
+            Action::OpenPalette if !matches!(self.mode, Mode::Palette | Mode::Confirm) => {
+                self.open_palette()
+            }
+            Action::EnterNormal
+                if matches!(self.mode, Mode::Insert | Mode::Multiline | Mode::Chat) =>
+            {
+                self.set_mode(Mode::Normal);
+            }
+            Action::FocusChat if matches!(self.mode, Mode::Normal | Mode::Insert) => {
+                self.set_mode(Mode::Chat);
             }
-            Action::EnterInsert => {
-                self.mode = Mode::Insert;
+            Action::EnterInsert if matches!(self.mode, Mode::Normal | Mode::Processing) => {
+                self.set_mode(match self.editor {
+                    Some(_) => Mode::Multiline,
+                    None => Mode::Insert,
+                });
             }
             Action::EnterProcessing => {
-                self.mode = Mode::Processing;
+                self.set_mode(Mode::Processing);
             }
             Action::ExitProcessing => {
                 // TODO: Make this go to previous mode instead
-                self.mode = Mode::Normal;
+                self.set_mode(Mode::Normal);
+            }
+            Action::Submit => {
+                return Ok(match self.mode {
+                    Mode::Insert => self.submit_input(),
+                    Mode::Multiline => Some(self.submit_editor()),
+                    Mode::Browse => {
+                        self.select_browser();
+                        None
+                    }
+                    Mode::Palette => self.select_palette(),
+                    Mode::Confirm => self.answer(true),
+                    _ => None,
+                })
+            }
+            Action::Cancel => match self.mode {
+                Mode::Browse => self.close_browser(),
+                Mode::Palette => self.close_palette(),
+                Mode::Help if !self.help_filter.is_empty() => self.help_filter.clear(),
+                Mode::Help => {
+                    self.show_help = false;
+                    self.set_mode(Mode::Normal);
+                }
+                Mode::Confirm => return Ok(self.answer(false)),
+                _ => {}
+            },
+            Action::Discard if self.mode == Mode::Multiline => {
+                self.editor = None;
+                self.set_mode(Mode::Insert);
+            }
+            Action::Expand if self.mode == Mode::Insert => self.expand_editor("\n"),
+            Action::InsertNewline => {
+                if let (Mode::Multiline, Some(editor)) = (self.mode, self.editor.as_mut()) {
+                    editor.insert_newline();
+                }
+            }
+            Action::AcceptSuggestion if self.mode == Mode::Insert => self.input.accept_suggestion(),
+            Action::HistoryPrev if self.mode == Mode::Insert => self.input.forwards(),
+            Action::HistoryNext if self.mode == Mode::Insert => self.input.backwards(),
+            Action::PrevItem | Action::NextItem | Action::PrevPage | Action::NextPage => {
+                self.scroll(&action)
+            }
+            Action::GoToPrevLine | Action::GoToNextLine => {
+                if let (Mode::Multiline, Some(editor)) = (self.mode, self.editor.as_mut()) {
+                    match action {
+                        Action::GoToPrevLine => editor.up(),
+                        _ => editor.down(),
+                    }
+                }
             }
             Action::Connected(connected) => {
                 self.connected = connected;
+                self.input.invalidate_convars();
             }
             Action::Error(e) => {
                 self.error = Some(e);
             }
-            _ => (),
+            Action::Warning(w) => {
+                self.warning = Some(w);
+            }
+            Action::ConfirmCommand(command, reason) => {
+                self.ask(Confirmation {
+                    action: Action::SendCommand(command.clone()),
+                    title: "Confirm command".to_string(),
+                    lines: vec![command, reason],
+                    prompt: "Send anyway? ".to_string(),
+                });
+            }
+            Action::ConfirmBroadcast(group, command, reason) => {
+                self.ask(Confirmation {
+                    action: Action::SendBroadcast(group.clone(), command.clone()),
+                    title: "Confirm broadcast".to_string(),
+                    lines: vec![format!("{} to {}", command, group), reason],
+                    prompt: "Send to every server anyway? ".to_string(),
+                });
+            }
+            Action::OfferCommands(phase, commands) => {
+                let mut lines = vec![format!("The match moved on to {}", phase)];
+                lines.extend(commands.iter().cloned());
+                self.ask(Confirmation {
+                    action: Action::RunCommands(commands),
+                    title: "Match".to_string(),
+                    lines,
+                    prompt: "Run these commands? ".to_string(),
+                });
+            }
+            Action::SendBroadcast(group, command) => {
+                self.broadcast = Some((group, command, None));
+            }
+            Action::BroadcastResults(group, command, results) => {
+                self.broadcast = Some((group, command, Some(results)));
+            }
+            action => {
+                if let Some(request) = input_request(&action) {
+                    self.edit(request);
+                }
+            }
         }
         Ok(None)
     }
 
     fn draw(&mut self, f: &mut Frame<'_>, rect: Rect) -> Result<()> {
+        let input_constraint = match &self.editor {
+            Some(editor) => Constraint::Length(
+                (editor.lines().len() as u16 + 2)
+                    .min(rect.height / 2)
+                    .max(3),
+            ),
+            None => Constraint::Min(3),
+        };
         let [input_rect, main_rect] = *Layout::default()
-            .constraints([Constraint::Min(3), Constraint::Percentage(100)].as_ref())
+            .constraints([input_constraint, Constraint::Percentage(100)].as_ref())
             .split(rect)
         else {
             panic!("Unable to split rects into a refutable pattern");
         };
 
-        f.render_widget(self.main_widget(), main_rect);
+        match self.broadcast.is_some() {
+            true => {
+                let [broadcast_rect, output_rect] = *Layout::default()
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .split(main_rect)
+                else {
+                    panic!("Unable to split rects into a refutable pattern");
+                };
+                self.draw_broadcast(f, broadcast_rect);
+                f.render_widget(self.main_widget(), output_rect);
+            }
+            false => f.render_widget(self.main_widget(), main_rect),
+        }
         self.main_rect = main_rect;
 
-        f.render_widget(self.input_widget(), input_rect);
+        match &self.editor {
+            Some(_) => f.render_widget(self.editor_widget(input_rect.height), input_rect),
+            None => f.render_widget(self.input_widget(), input_rect),
+        }
         self.input_rect = input_rect;
 
         if self.mode == Mode::Insert {
             f.set_cursor(
                 (input_rect.x + 1 + self.input.cursor() as u16)
-                    .min(input_rect.x + input_rect.width - 2),
+                    .min(input_rect.x + input_rect.width.saturating_sub(2)),
                 input_rect.y + 1,
             )
         }
 
+        if let (Mode::Multiline, Some(editor)) = (self.mode, &self.editor) {
+            let (col, row) = editor.cursor();
+            let visible = input_rect.height.saturating_sub(2) as usize;
+            let row = row.min(visible.saturating_sub(1));
+            f.set_cursor(
+                (input_rect.x + 1 + col as u16)
+                    .min(input_rect.x + input_rect.width.saturating_sub(2)),
+                input_rect.y + 1 + row as u16,
+            )
+        }
+
         if self.show_help {
             let rect = rect.inner(&Margin {
                 horizontal: 4,
                 vertical: 2,
             });
             f.render_widget(Clear, rect);
-            let (block, table) = self.help_widget();
+            let table_rect = rect.inner(&Margin {
+                vertical: 2,
+                horizontal: 2,
+            });
+            let (block, table) = self.help_widget(table_rect.height);
             f.render_widget(block, rect);
-            f.render_widget(
-                table,
-                rect.inner(&Margin {
-                    vertical: 4,
-                    horizontal: 2,
-                }),
-            );
+            f.render_widget(table, table_rect);
         };
 
         f.render_widget(
             self.title_widget(),
             Rect {
                 x: rect.x + 1,
-                y: rect.height.saturating_sub(1),
+                y: (rect.y + rect.height).saturating_sub(1),
                 width: rect.width.saturating_sub(2),
                 height: 1,
             },
@@ -368,21 +1124,174 @@ impl Component for Home {
             Block::new()
                 .title("rcon-client")
                 .title_alignment(Alignment::Center),
-            f.size(),
+            rect,
         );
 
         // draw command descriptions
         if let Some(mut command) = self.input.get_current_command() {
+            let value = self.input.convar_value(&command.name);
+            f.render_widget(
+                command.widget(value, &self.theme),
+                Rect {
+                    x: rect.x,
+                    y: rect.y + 3,
+                    width: 40.min(rect.width),
+                    height: 10.min(rect.height.saturating_sub(3)),
+                },
+            );
+        }
+
+        if let Some(browser) = self.browser.as_mut() {
+            let area = rect.inner(&Margin {
+                horizontal: 4,
+                vertical: 2,
+            });
+            f.render_widget(browser.widget(area, &self.theme), area);
+            f.set_cursor(
+                (area.x + 3 + browser.cursor() as u16).min(area.x + area.width.saturating_sub(2)),
+                area.y + 1,
+            );
+        }
+
+        if let Some(palette) = self.palette.as_mut() {
+            let area = rect.inner(&Margin {
+                horizontal: 4,
+                vertical: 2,
+            });
+            f.render_widget(palette.widget(area, &self.theme), area);
+            f.set_cursor(
+                (area.x + 3 + palette.cursor() as u16).min(area.x + area.width.saturating_sub(2)),
+                area.y + 1,
+            );
+        }
+
+        if let Some(popup) = self.confirm_widget() {
+            let width = rect.width.min(60);
+            let height = self
+                .confirm
+                .as_ref()
+                .map(|c| c.lines.len() as u16 + 4)
+                .unwrap_or_default()
+                .min(rect.height);
             f.render_widget(
-                command.widget(),
+                popup,
                 Rect {
-                    x: 0,
-                    y: 3,
-                    width: 40,
-                    height: 10,
+                    x: rect.x + (rect.width - width) / 2,
+                    y: rect.y + (rect.height - height) / 2,
+                    width,
+                    height,
                 },
             );
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::empty())
+    }
+
+    #[test]
+    fn test_keys_follow_mode_keymap() -> Result<()> {
+        let mut home = Home::new();
+        home.register_config_handler(Config::default_config())?;
+        for c in "bot_kick x".chars() {
+            home.handle_key_events(key(KeyCode::Char(c)))?;
+        }
+        // Bound keys are left to the keymap.
+        assert_eq!(home.handle_key_events(key(KeyCode::Enter))?, None);
+        home.update(Action::DeletePrevWord)?;
+        assert_eq!(
+            home.update(Action::Submit)?,
+            Some(Action::Command("bot_kick ".to_string()))
+        );
+
+        home.update(Action::EnterNormal)?;
+        assert_eq!(home.mode, Mode::Normal);
+        home.handle_key_events(key(KeyCode::Char('x')))?;
+        home.update(Action::EnterInsert)?;
+        assert_eq!(home.input.value(), "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_editor() -> Result<()> {
+        let mut home = Home::new();
+        home.register_config_handler(Config::default_config())?;
+        home.update(Action::Expand)?;
+        assert_eq!(home.mode, Mode::Multiline);
+        assert_eq!(home.editor.as_ref().unwrap().lines(), ["", ""]);
+
+        let mut home = Home::new();
+        home.register_config_handler(Config::default_config())?;
+        home.input.set_value("say hi".to_string());
+        home.handle_paste_events("mp_warmup_end\nmp_restartgame 1\n".to_string())?;
+        assert_eq!(
+            home.editor.as_ref().unwrap().text(),
+            "say himp_warmup_end\nmp_restartgame 1"
+        );
+        assert_eq!(home.input.value(), "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_palette_runs_choice() -> Result<()> {
+        let mut home = Home::new();
+        home.register_config_handler(Config::default_config())?;
+        home.update(Action::OpenPalette)?;
+        assert_eq!(home.mode, Mode::Palette);
+        for c in "disconnect".chars() {
+            home.handle_key_events(key(KeyCode::Char(c)))?;
+        }
+        assert_eq!(
+            home.update(Action::Submit)?,
+            Some(Action::Command("disconnect".to_string()))
+        );
+        assert_eq!(home.mode, Mode::Insert);
+
+        home.update(Action::OpenPalette)?;
+        for c in "exec <".chars() {
+            home.handle_key_events(key(KeyCode::Char(c)))?;
+        }
+        assert_eq!(home.update(Action::Submit)?, None);
+        assert_eq!(home.input.value(), "exec ");
+        Ok(())
+    }
+
+    #[test]
+    fn test_broadcast_command() -> Result<()> {
+        let mut home = Home::new();
+        home.register_config_handler(Config::default_config())?;
+        home.input
+            .set_value("broadcast event mp_restartgame 1".to_string());
+        assert_eq!(
+            home.update(Action::Submit)?,
+            Some(Action::Broadcast(
+                "event".to_string(),
+                "mp_restartgame 1".to_string()
+            ))
+        );
+        home.input.set_value("broadcast event".to_string());
+        assert!(matches!(
+            home.update(Action::Submit)?,
+            Some(Action::Error(_))
+        ));
+
+        home.update(Action::SendBroadcast("event".into(), "status".into()))?;
+        assert_eq!(
+            home.broadcast,
+            Some(("event".to_string(), "status".to_string(), None))
+        );
+        // The next plain command takes the output area back.
+        home.input.set_value("status".to_string());
+        home.update(Action::Submit)?;
+        assert_eq!(home.broadcast, None);
+        Ok(())
+    }
+}
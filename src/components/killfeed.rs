@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+
+use color_eyre::eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+use serde::Deserialize;
+
+use super::Component;
+use crate::{
+    action::Action,
+    command::logs::{side, LogEvent, LogLine, LogPlayer},
+    config::Config,
+    mode::Mode,
+    theme::Theme,
+    tui::Frame,
+};
+
+/// The kill feed pane, placed with `layout.killfeed`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct KillFeedConfig {
+    /// Kills kept, older ones scroll out.
+    pub history: usize,
+}
+
+impl Default for KillFeedConfig {
+    fn default() -> Self {
+        Self { history: 50 }
+    }
+}
+
+/// A kill, or a suicide without an attacker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kill {
+    pub time: String,
+    pub attacker: Option<LogPlayer>,
+    pub victim: LogPlayer,
+    pub weapon: String,
+    pub headshot: bool,
+    /// Through a wall or another object.
+    pub wallbang: bool,
+    pub noscope: bool,
+    pub through_smoke: bool,
+    pub attacker_blind: bool,
+}
+
+impl Kill {
+    fn from_line(line: &LogLine) -> Option<Self> {
+        match &line.event {
+            LogEvent::Killed {
+                attacker,
+                victim,
+                weapon,
+                headshot,
+                penetrated,
+                noscope,
+                through_smoke,
+                attacker_blind,
+                ..
+            } => Some(Self {
+                time: line.time.clone(),
+                attacker: Some(attacker.clone()),
+                victim: victim.clone(),
+                weapon: weapon.clone(),
+                headshot: *headshot,
+                wallbang: *penetrated,
+                noscope: *noscope,
+                through_smoke: *through_smoke,
+                attacker_blind: *attacker_blind,
+            }),
+            LogEvent::Suicide { player, weapon } => Some(Self {
+                time: line.time.clone(),
+                attacker: None,
+                victim: player.clone(),
+                weapon: weapon.clone(),
+                headshot: false,
+                wallbang: false,
+                noscope: false,
+                through_smoke: false,
+                attacker_blind: false,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Short markers for how the kill was made, like the icons in game.
+    pub fn flags(&self) -> Vec<&'static str> {
+        [
+            (self.attacker_blind, "blind"),
+            (self.noscope, "noscope"),
+            (self.through_smoke, "smoke"),
+            (self.wallbang, "wallbang"),
+            (self.headshot, "hs"),
+        ]
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, flag)| flag)
+        .collect()
+    }
+}
+
+/// A compact feed of the kills in the front tab like the one in game, newest at the
+/// bottom, coloured by team.
+#[derive(Default)]
+pub struct KillFeed {
+    kills: VecDeque<Kill>,
+    history: usize,
+    theme: Theme,
+}
+
+impl KillFeed {
+    pub fn new() -> Self {
+        Self {
+            history: KillFeedConfig::default().history,
+            ..Self::default()
+        }
+    }
+
+    pub fn kills(&self) -> &VecDeque<Kill> {
+        &self.kills
+    }
+
+    fn push(&mut self, kill: Kill) {
+        self.kills.push_back(kill);
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.kills.len() > self.history {
+            self.kills.pop_front();
+        }
+    }
+
+    fn player_span<'a>(&self, player: &'a LogPlayer) -> Span<'a> {
+        let style = match side(&player.team) {
+            Some("CT") => self.theme.get("team_ct"),
+            Some(_) => self.theme.get("team_t"),
+            None => self.theme.get("hint"),
+        };
+        Span::styled(player.name.as_str(), style)
+    }
+
+    fn kill_line<'a>(&self, kill: &'a Kill) -> Line<'a> {
+        let mut spans = Vec::new();
+        if let Some(attacker) = &kill.attacker {
+            spans.push(self.player_span(attacker));
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(
+            kill.weapon.trim_start_matches("weapon_"),
+            self.theme.get("value"),
+        ));
+        for flag in kill.flags() {
+            spans.push(Span::styled(
+                format!(" {}", flag),
+                self.theme.get("warning"),
+            ));
+        }
+        spans.push(Span::raw(" "));
+        spans.push(self.player_span(&kill.victim));
+        Line::from(spans)
+    }
+}
+
+impl Component for KillFeed {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.theme = config.theme(Mode::Normal);
+        self.history = config.killfeed.history;
+        self.trim();
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Log(line) => {
+                if let Some(kill) = Kill::from_line(&line) {
+                    self.push(kill);
+                }
+            }
+            Action::Replay(lines) => {
+                self.kills.clear();
+                for kill in lines.iter().filter_map(Kill::from_line) {
+                    self.push(kill);
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, rect: Rect) -> Result<()> {
+        let block = Block::default()
+            .title(Span::styled("kills", self.theme.get("title")))
+            .borders(Borders::ALL)
+            .border_style(self.theme.get("border"));
+        let inner = block.inner(rect);
+        f.render_widget(block, rect);
+        // The newest kills that fit, at the bottom.
+        let shown = self.kills.len().min(inner.height as usize);
+        let lines = self
+            .kills
+            .iter()
+            .skip(self.kills.len() - shown)
+            .map(|kill| self.kill_line(kill))
+            .collect::<Vec<_>>();
+        let top = inner.height.saturating_sub(shown as u16);
+        f.render_widget(
+            Paragraph::new(lines),
+            Rect {
+                y: inner.y + top,
+                height: inner.height - top,
+                ..inner
+            },
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::command::logs::parse_line;
+
+    const FIXTURE: &str = include_str!("../../fixtures/logs/cs2_match.log");
+
+    fn text(feed: &KillFeed) -> Vec<String> {
+        feed.kills()
+            .iter()
+            .map(|kill| {
+                feed.kill_line(kill)
+                    .spans
+                    .iter()
+                    .map(|s| s.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_feed() -> Result<()> {
+        let mut feed = KillFeed::new();
+        feed.register_config_handler(Config {
+            killfeed: KillFeedConfig { history: 4 },
+            ..Config::default()
+        })?;
+        for line in FIXTURE.lines().filter_map(parse_line) {
+            feed.update(Action::Log(Box::new(line)))?;
+        }
+        assert_eq!(
+            text(&feed),
+            vec![
+                "world Bob",
+                "Carol hegrenade Dave",
+                "Alice knife Bob",
+                "Dave deagle Alice",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_flags_and_replay() -> Result<()> {
+        let mut feed = KillFeed::new();
+        let lines = FIXTURE.lines().filter_map(parse_line).collect::<Vec<_>>();
+        feed.update(Action::Replay(lines))?;
+        assert_eq!(feed.kills().len(), 11);
+        assert_eq!(
+            text(&feed)[..3],
+            [
+                "Alice m4a1_silencer hs Bob",
+                "Carol famas Dave",
+                "Bob ak47 wallbang hs Alice",
+            ]
+        );
+        assert_eq!(feed.kills()[3].flags(), vec!["noscope"]);
+        feed.update(Action::Replay(Vec::new()))?;
+        assert!(feed.kills().is_empty());
+        Ok(())
+    }
+}
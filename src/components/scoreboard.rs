@@ -0,0 +1,261 @@
+use color_eyre::eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+
+use super::Component;
+use crate::{
+    action::Action,
+    command::logs::WinReason,
+    config::Config,
+    mode::Mode,
+    stats::{MatchStats, PlayerStats},
+    theme::Theme,
+    tui::Frame,
+};
+
+/// Width of the player name column.
+const NAME_WIDTH: usize = 20;
+
+/// The score, round and per player stats of the match in the front tab, with the
+/// round history, shown over the output.
+///
+/// It is rebuilt from the backlog of the session when another tab comes to the front
+/// or the connection comes back.
+#[derive(Default)]
+pub struct Scoreboard {
+    stats: MatchStats,
+    visible: bool,
+    theme: Theme,
+}
+
+impl Scoreboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> &MatchStats {
+        &self.stats
+    }
+
+    fn side_style(&self, side: &str) -> Style {
+        match side {
+            "CT" => self.theme.get("team_ct"),
+            "T" => self.theme.get("team_t"),
+            _ => self.theme.get("hint"),
+        }
+    }
+
+    fn score_line(&self) -> Line<'_> {
+        let name = |name: &'static str, team: &str| match team {
+            "" => name.to_string(),
+            team => team.to_string(),
+        };
+        Line::from(vec![
+            Span::styled(name("CT", &self.stats.teams.0), self.side_style("CT")),
+            Span::styled(
+                format!(" {} ", self.stats.score.0),
+                self.theme.get("value").add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(":"),
+            Span::styled(
+                format!(" {} ", self.stats.score.1),
+                self.theme.get("value").add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(name("T", &self.stats.teams.1), self.side_style("T")),
+            Span::styled(
+                format!("   round {}, {}", self.stats.round(), self.stats.half()),
+                self.theme.get("hint"),
+            ),
+        ])
+    }
+
+    fn player_line(&self, player: &PlayerStats) -> Line<'_> {
+        let name = player.name.chars().take(NAME_WIDTH).collect::<String>();
+        Line::from(vec![
+            Span::styled(
+                format!("{:<width$}", name, width = NAME_WIDTH),
+                self.side_style(&player.side),
+            ),
+            Span::styled(
+                format!(
+                    "{:>4}{:>4}{:>4}{:>5}{:>5}",
+                    player.kills,
+                    player.deaths,
+                    player.assists,
+                    player.adr(self.stats.rounds.len()),
+                    player.headshot_percentage()
+                ),
+                self.theme.get("value"),
+            ),
+        ])
+    }
+
+    /// The rounds played as one letter each, coloured by the winner, with a gap where
+    /// the teams swapped sides.
+    fn history_line(&self) -> Line<'_> {
+        let mut spans = Vec::new();
+        for (i, round) in self.stats.rounds.iter().enumerate() {
+            let letter = match round.reason {
+                WinReason::Bomb => "B",
+                WinReason::Defuse => "D",
+                WinReason::Elimination => "E",
+                WinReason::Time => "T",
+                WinReason::HostagesRescued => "H",
+                WinReason::Surrender => "S",
+                WinReason::Other(_) => "?",
+            };
+            spans.push(Span::styled(letter, self.side_style(&round.winner)));
+            if self.stats.is_last_of_half(i + 1) {
+                spans.push(Span::raw(" "));
+            }
+        }
+        if spans.is_empty() {
+            spans.push(Span::styled("no rounds played", self.theme.get("hint")));
+        }
+        Line::from(spans)
+    }
+
+    pub fn lines(&self) -> Vec<Line<'_>> {
+        let mut lines = vec![self.score_line()];
+        for (side, team) in [("CT", &self.stats.teams.0), ("T", &self.stats.teams.1)] {
+            lines.push(Line::default());
+            let title = match team.as_str() {
+                "" => side.to_string(),
+                team => format!("{} {}", side, team),
+            };
+            let title = title.chars().take(NAME_WIDTH).collect::<String>();
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}", title, width = NAME_WIDTH),
+                    self.side_style(side).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{:>4}{:>4}{:>4}{:>5}{:>5}", "K", "D", "A", "ADR", "HS%"),
+                    self.theme.get("title"),
+                ),
+            ]));
+            for player in self.stats.players(side) {
+                lines.push(self.player_line(player));
+            }
+        }
+        lines.push(Line::default());
+        lines.push(self.history_line());
+        lines.push(Line::styled(
+            "B bomb, D defuse, E elimination, T time",
+            self.theme.get("hint"),
+        ));
+        lines
+    }
+}
+
+impl Component for Scoreboard {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.theme = config.theme(Mode::Normal);
+        Ok(())
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Log(line) => self.stats.update(&line.event),
+            Action::Replay(lines) => self.stats = MatchStats::from_lines(&lines),
+            Action::ToggleScoreboard => self.visible = !self.visible,
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, rect: Rect) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        let lines = self.lines();
+        let width = (NAME_WIDTH as u16 + 22 + 4).max(lines[0].width() as u16 + 4);
+        let width = width.min(rect.width);
+        let height = (lines.len() as u16 + 2).min(rect.height);
+        let area = Rect {
+            x: rect.x + (rect.width - width) / 2,
+            y: rect.y + (rect.height - height) / 2,
+            width,
+            height,
+        };
+        let mut title = vec![Span::styled(" scoreboard ", self.theme.get("title"))];
+        if !self.stats.map.is_empty() {
+            title.push(Span::styled(
+                format!("{} ", self.stats.map),
+                self.theme.get("hint"),
+            ));
+        }
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(self.theme.get("popup_border"))
+            .padding(Padding::horizontal(1));
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines)
+                .style(self.theme.get("popup"))
+                .block(block),
+            area,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::command::logs::parse_line;
+
+    const FIXTURE: &str = include_str!("../../fixtures/logs/cs2_match.log");
+
+    fn text(scoreboard: &Scoreboard) -> Vec<String> {
+        scoreboard
+            .lines()
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_lines() -> Result<()> {
+        let mut scoreboard = Scoreboard::new();
+        let lines = FIXTURE.lines().filter_map(parse_line).collect::<Vec<_>>();
+        for line in &lines[..lines.len() - 1] {
+            scoreboard.update(Action::Log(Box::new(line.clone())))?;
+        }
+        assert_eq!(
+            text(&scoreboard),
+            vec![
+                "Team Alpha 2 : 1 Team Bravo   round 4, overtime 1",
+                "",
+                "CT Team Alpha          K   D   A  ADR  HS%",
+                "Alice                  4   2   0  113   50",
+                "Carol                  2   2   2   33    0",
+                "",
+                "T Team Bravo           K   D   A  ADR  HS%",
+                "Dave                   3   3   0   66   33",
+                "Bob                    1   4   0   33  100",
+                "",
+                "E B D",
+                "B bomb, D defuse, E elimination, T time",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay() -> Result<()> {
+        let mut scoreboard = Scoreboard::new();
+        scoreboard.update(Action::ToggleScoreboard)?;
+        assert!(scoreboard.visible);
+        let lines = FIXTURE.lines().filter_map(parse_line).collect::<Vec<_>>();
+        scoreboard.update(Action::Replay(lines.clone()))?;
+        assert_eq!(scoreboard.stats(), &MatchStats::from_lines(&lines));
+        assert_eq!(scoreboard.stats().score, (2, 1));
+        // Another tab without a match clears it.
+        scoreboard.update(Action::Replay(Vec::new()))?;
+        assert_eq!(scoreboard.stats(), &MatchStats::new());
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{prelude::*, widgets::*};
+
+use super::Component;
+use crate::{
+    action::Action,
+    config::Config,
+    mode::Mode,
+    session::{Activity, Tab},
+    theme::Theme,
+    tui::Frame,
+};
+
+/// The row of session tabs at the top of the screen.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TabBar {
+    tabs: Vec<Tab>,
+    active: usize,
+    /// Column ranges of the tabs as last drawn, for mouse clicks.
+    spans: Vec<(u16, u16)>,
+    row: u16,
+    theme: Theme,
+}
+
+impl TabBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn labels(&self) -> Vec<Vec<Span<'static>>> {
+        self.tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| {
+                let style = match i == self.active {
+                    true => self.theme.get("selected"),
+                    false => self.theme.get("hint"),
+                };
+                let (indicator, indicator_style) = match tab.connected {
+                    true => ("● ", self.theme.get("connected")),
+                    false => ("○ ", self.theme.get("disconnected")),
+                };
+                let mut spans = vec![
+                    Span::styled(format!(" {} ", i + 1), style),
+                    Span::styled(indicator, style.patch(indicator_style)),
+                    Span::styled(tab.title.clone(), style),
+                ];
+                if let Some(status) = &tab.status {
+                    spans.push(Span::styled(
+                        format!(" {}", status),
+                        style.patch(self.theme.get("hint")),
+                    ));
+                }
+                match tab.activity {
+                    Activity::None => {}
+                    Activity::Output => {
+                        spans.push(Span::styled(" *", style.patch(self.theme.get("output"))))
+                    }
+                    Activity::Error => {
+                        spans.push(Span::styled(" !", style.patch(self.theme.get("error"))))
+                    }
+                }
+                spans.push(Span::styled(" ", style));
+                spans
+            })
+            .collect()
+    }
+}
+
+impl Component for TabBar {
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.theme = config.theme(Mode::Normal);
+        Ok(())
+    }
+
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) || mouse.row != self.row {
+            return Ok(None);
+        }
+        Ok(self
+            .spans
+            .iter()
+            .position(|(start, end)| (*start..*end).contains(&mouse.column))
+            .map(Action::SelectTab))
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if let Action::Tabs(tabs, active) = action {
+            self.tabs = tabs;
+            self.active = active;
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, rect: Rect) -> Result<()> {
+        let area = Rect { height: 1, ..rect };
+        self.row = area.y;
+        self.spans.clear();
+        let mut column = area.x;
+        let mut line = Vec::new();
+        for label in self.labels() {
+            let width = label.iter().map(|s| s.width() as u16).sum::<u16>();
+            self.spans.push((column, column + width));
+            column += width + 1;
+            line.extend(label);
+            line.push(Span::raw(" "));
+        }
+        f.render_widget(Paragraph::new(Line::from(line)), area);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn click(column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::empty(),
+        }
+    }
+
+    #[test]
+    fn test_click_selects_tab() -> Result<()> {
+        let mut tabs = TabBar::new();
+        tabs.update(Action::Tabs(
+            vec![
+                Tab {
+                    title: "new".to_string(),
+                    status: None,
+                    connected: false,
+                    activity: Activity::None,
+                },
+                Tab {
+                    title: "10.0.0.2:27015".to_string(),
+                    status: Some("live 3:1".to_string()),
+                    connected: true,
+                    activity: Activity::Error,
+                },
+            ],
+            0,
+        ))?;
+        let mut terminal = Terminal::new(backend::TestBackend::new(60, 5))?;
+        terminal.draw(|f| {
+            tabs.draw(f, f.size()).unwrap();
+        })?;
+        let row = (0..60)
+            .map(|x| terminal.backend().buffer().get(x, 0).symbol().to_string())
+            .collect::<String>();
+        assert_eq!(row.trim_end(), " 1 ○ new   2 ● 10.0.0.2:27015 live 3:1 !");
+        assert_eq!(
+            tabs.handle_mouse_events(click(2, 0))?,
+            Some(Action::SelectTab(0))
+        );
+        assert_eq!(
+            tabs.handle_mouse_events(click(14, 0))?,
+            Some(Action::SelectTab(1))
+        );
+        assert_eq!(tabs.handle_mouse_events(click(14, 1))?, None);
+        assert_eq!(tabs.handle_mouse_events(click(50, 0))?, None);
+        Ok(())
+    }
+}
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, fmt, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 use color_eyre::eyre::Result;
 use config::Value;
@@ -11,7 +15,25 @@ use serde::{
 };
 use serde_json::Value as JsonValue;
 
-use crate::{action::Action, components::home::Home, mode::Mode};
+use crate::{
+    action::Action,
+    chord::DEFAULT_TIMEOUT,
+    command::validator::ValidationPolicy,
+    components::{home::Home, killfeed::KillFeedConfig},
+    controller::MatchType,
+    layout::Placement,
+    mode::Mode,
+    ready::ReadyConfig,
+    theme::{Theme, DEFAULT_THEME},
+    utils::{get_config_dir, get_system_config_dir},
+};
+
+const CONFIG_FILES: [(&str, config::FileFormat); 4] = [
+    ("config.json5", config::FileFormat::Json5),
+    ("config.json", config::FileFormat::Json),
+    ("config.yaml", config::FileFormat::Yaml),
+    ("config.toml", config::FileFormat::Toml),
+];
 
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct Config {
@@ -19,29 +41,333 @@ pub struct Config {
     pub keybindings: KeyBindings,
     #[serde(default)]
     pub styles: Styles,
+    #[serde(default)]
+    pub validation: ValidationPolicy,
+    #[serde(default)]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+    /// How long to wait for the next key of a multi-key binding, in milliseconds.
+    #[serde(default = "default_chord_timeout")]
+    pub chord_timeout: u64,
+    #[serde(default)]
+    pub logs: LogConfig,
+    /// Match types for `match start <type>`, added to the built-in ones.
+    #[serde(default)]
+    pub matches: HashMap<String, MatchType>,
+    /// Chat commands players ready up and pause with.
+    #[serde(default)]
+    pub ready: ReadyConfig,
+    /// Where the panes go, by name, like `killfeed`.
+    #[serde(default)]
+    pub layout: HashMap<String, Placement>,
+    #[serde(default)]
+    pub killfeed: KillFeedConfig,
+}
+
+/// A saved server that can be connected to by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct Profile {
+    pub address: String,
+    #[serde(default)]
+    pub password: String,
+    /// Groups the server belongs to, for broadcasting commands.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Where the server should send its log to.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct LogConfig {
+    /// Receive logs over http, registered with `logaddress_add_http`.
+    pub http: Option<HttpLogConfig>,
+    /// Receive logs over udp, registered with `logaddress_add`.
+    pub udp: Option<UdpLogConfig>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct HttpLogConfig {
+    /// Local address to listen on, e.g. `0.0.0.0:27500`.
+    pub listen: String,
+    /// The url the server can reach the listener at, e.g. `http://203.0.113.5:27500/logs`.
+    pub url: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct UdpLogConfig {
+    /// Local address to listen on, e.g. `0.0.0.0:27500`.
+    pub listen: String,
+    /// The address the server can reach the listener at, e.g. `203.0.113.5:27500`.
+    pub address: String,
+    /// Set as `sv_logsecret` on the server, packets without it are dropped.
+    pub secret: Option<String>,
+}
+
+fn default_chord_timeout() -> u64 {
+    DEFAULT_TIMEOUT.as_millis() as u64
 }
 
 impl Config {
-    pub fn new() -> Result<Self, config::ConfigError> {
-        let keybindings_map: HashMap<Mode, HashMap<Vec<KeyEvent>, Action>> = HashMap::from([(
-            Mode::Home,
-            HashMap::from([
-                (parse_key_sequence("<?>").unwrap(), Action::ToggleShowHelp),
-                (parse_key_sequence("</>").unwrap(), Action::EnterInsert),
-                (parse_key_sequence("<Ctrl-d>").unwrap(), Action::Quit),
-                (parse_key_sequence("<Ctrl-c>").unwrap(), Action::Quit),
-                (parse_key_sequence("<Ctrl-z>").unwrap(), Action::Suspend),
-            ]),
-        )]);
-        let keybindings = KeyBindings(keybindings_map);
+    /// Load the configuration, layering the built-in defaults, the system config file,
+    /// the user config file and finally the file given on the command line.
+    pub fn new(config_file: Option<PathBuf>) -> Result<Self, config::ConfigError> {
+        if let Some(file) = &config_file {
+            if !file.exists() {
+                return Err(config::ConfigError::Message(format!(
+                    "config file {} does not exist",
+                    file.display()
+                )));
+            }
+        }
+
+        let mut builder = config::Config::builder();
+        for (path, format) in Self::files(config_file)
+            .into_iter()
+            .filter(|(path, _)| path.exists())
+        {
+            log::info!("Loading config from {}", path.display());
+            let source = config::File::from(path.as_path()).format(format);
+            // Parse each file on its own first so errors point at the file they come from.
+            config::Config::builder()
+                .add_source(source.clone())
+                .build()
+                .and_then(|c| c.try_deserialize::<Config>())
+                .map_err(|e| config::ConfigError::Message(format!("{}: {}", path.display(), e)))?;
+            builder = builder.add_source(source);
+        }
+        let mut cfg: Self = builder.build()?.try_deserialize()?;
+
+        let default_config = Self::default_config();
+        for (mode, default_bindings) in default_config.keybindings.iter() {
+            let user_bindings = cfg.keybindings.entry(*mode).or_default();
+            for (key, action) in default_bindings.iter() {
+                user_bindings
+                    .entry(key.clone())
+                    .or_insert_with(|| action.clone());
+            }
+        }
+        for (name, kind) in default_config.matches {
+            cfg.matches.entry(name).or_insert(kind);
+        }
+        for (mode, default_styles) in default_config.styles.iter() {
+            let user_styles = cfg.styles.entry(*mode).or_default();
+            for (name, style) in default_styles.iter() {
+                user_styles.entry(name.clone()).or_insert(*style);
+            }
+        }
+
+        Ok(cfg)
+    }
+
+    /// Every file the configuration may be loaded from, in the order they are layered.
+    pub fn files(config_file: Option<PathBuf>) -> Vec<(PathBuf, config::FileFormat)> {
+        let mut files = Vec::new();
+        for dir in [get_system_config_dir(), Some(get_config_dir())]
+            .into_iter()
+            .flatten()
+        {
+            for (file, format) in CONFIG_FILES {
+                files.push((dir.join(file), format));
+            }
+        }
+        if let Some(file) = config_file {
+            let format = CONFIG_FILES
+                .iter()
+                .find(|(name, _)| file.extension() == Path::new(name).extension())
+                .map(|(_, format)| *format)
+                .unwrap_or(config::FileFormat::Json5);
+            files.push((file, format));
+        }
+        files
+    }
+
+    /// The built-in configuration used underneath any config files.
+    pub fn default_config() -> Self {
+        let global = [
+            ("<Ctrl-c>", Action::Quit),
+            ("<Ctrl-d>", Action::Quit),
+            ("<Ctrl-z>", Action::Suspend),
+        ];
+        let editing = [
+            ("<Left>", Action::GoToPrevChar),
+            ("<Right>", Action::GoToNextChar),
+            ("<Ctrl-Left>", Action::GoToPrevWord),
+            ("<Ctrl-Right>", Action::GoToNextWord),
+            ("<Home>", Action::GoToStart),
+            ("<End>", Action::GoToEnd),
+            ("<Ctrl-a>", Action::GoToStart),
+            ("<Ctrl-e>", Action::GoToEnd),
+            ("<Backspace>", Action::DeletePrevChar),
+            ("<Delete>", Action::DeleteNextChar),
+            ("<Ctrl-w>", Action::DeletePrevWord),
+            ("<Ctrl-Delete>", Action::DeleteNextWord),
+            ("<Ctrl-u>", Action::DeleteLine),
+            ("<Ctrl-k>", Action::DeleteTillEnd),
+        ];
+        let modes = [
+            (
+                Mode::Normal,
+                vec![
+                    ("<?>", Action::ToggleShowHelp),
+                    ("</>", Action::EnterInsert),
+                    ("<i>", Action::EnterInsert),
+                    ("<Ctrl-f>", Action::Apropos(String::new())),
+                    ("<Ctrl-p>", Action::OpenPalette),
+                    ("<Ctrl-t>", Action::NewTab),
+                    ("<g><t>", Action::NextTab),
+                    ("<g><Shift-t>", Action::PrevTab),
+                    ("<g><x>", Action::CloseTab),
+                    ("<Ctrl-PageDown>", Action::NextTab),
+                    ("<Ctrl-PageUp>", Action::PrevTab),
+                    ("<c>", Action::FocusChat),
+                    ("<g><c>", Action::ToggleChat),
+                    ("<g><s>", Action::ToggleScoreboard),
+                    ("<g><k>", Action::ToggleKillFeed),
+                ],
+            ),
+            (
+                Mode::Insert,
+                [
+                    ("<Esc>", Action::EnterNormal),
+                    ("<Enter>", Action::Submit),
+                    ("<Alt-Enter>", Action::Expand),
+                    ("<Up>", Action::HistoryPrev),
+                    ("<Down>", Action::HistoryNext),
+                    ("<Tab>", Action::AcceptSuggestion),
+                    ("<Ctrl-f>", Action::Apropos(String::new())),
+                    ("<Ctrl-p>", Action::OpenPalette),
+                    ("<Ctrl-t>", Action::NewTab),
+                    ("<Ctrl-PageDown>", Action::NextTab),
+                    ("<Ctrl-PageUp>", Action::PrevTab),
+                ]
+                .into_iter()
+                .chain(editing.clone())
+                .collect(),
+            ),
+            (
+                Mode::Multiline,
+                [
+                    ("<Esc>", Action::EnterNormal),
+                    ("<Enter>", Action::InsertNewline),
+                    ("<Ctrl-s>", Action::Submit),
+                    ("<Alt-Enter>", Action::Submit),
+                    ("<Ctrl-x>", Action::Discard),
+                    ("<Up>", Action::GoToPrevLine),
+                    ("<Down>", Action::GoToNextLine),
+                ]
+                .into_iter()
+                .chain(editing.clone())
+                .collect(),
+            ),
+            (
+                Mode::Browse,
+                [
+                    ("<Esc>", Action::Cancel),
+                    ("<Enter>", Action::Submit),
+                    ("<Up>", Action::PrevItem),
+                    ("<Down>", Action::NextItem),
+                    ("<PageUp>", Action::PrevPage),
+                    ("<PageDown>", Action::NextPage),
+                ]
+                .into_iter()
+                .chain(editing.clone())
+                .collect(),
+            ),
+            (
+                Mode::Palette,
+                [
+                    ("<Esc>", Action::Cancel),
+                    ("<Ctrl-p>", Action::Cancel),
+                    ("<Enter>", Action::Submit),
+                    ("<Up>", Action::PrevItem),
+                    ("<Down>", Action::NextItem),
+                    ("<PageUp>", Action::PrevPage),
+                    ("<PageDown>", Action::NextPage),
+                ]
+                .into_iter()
+                .chain(editing.clone())
+                .collect(),
+            ),
+            (
+                Mode::Chat,
+                [
+                    ("<Esc>", Action::EnterNormal),
+                    ("<Enter>", Action::Submit),
+                    ("<PageUp>", Action::PrevPage),
+                    ("<PageDown>", Action::NextPage),
+                ]
+                .into_iter()
+                .chain(editing)
+                .collect(),
+            ),
+            (
+                Mode::Help,
+                vec![
+                    ("<?>", Action::ToggleShowHelp),
+                    ("<Esc>", Action::Cancel),
+                    ("<Up>", Action::PrevItem),
+                    ("<Down>", Action::NextItem),
+                    ("<PageUp>", Action::PrevPage),
+                    ("<PageDown>", Action::NextPage),
+                    ("<Backspace>", Action::DeletePrevChar),
+                ],
+            ),
+            (
+                Mode::Confirm,
+                vec![
+                    ("<y>", Action::Submit),
+                    ("<Enter>", Action::Submit),
+                    ("<n>", Action::Cancel),
+                    ("<Esc>", Action::Cancel),
+                ],
+            ),
+            (Mode::Processing, vec![]),
+        ];
+        let keybindings = KeyBindings(
+            modes
+                .into_iter()
+                .map(|(mode, bindings)| {
+                    let keymap = bindings
+                        .into_iter()
+                        .chain(global.clone())
+                        .map(|(keys, action)| (parse_key_sequence(keys).unwrap(), action))
+                        .collect();
+                    (mode, keymap)
+                })
+                .collect(),
+        );
 
         let styles: Styles = Styles::default();
-        let cfg: Self = Config {
+        Config {
             keybindings,
             styles,
-        };
+            validation: ValidationPolicy::default(),
+            theme: None,
+            profiles: HashMap::new(),
+            chord_timeout: default_chord_timeout(),
+            logs: LogConfig::default(),
+            matches: MatchType::defaults(),
+            ready: ReadyConfig::default(),
+            layout: HashMap::new(),
+            killfeed: KillFeedConfig::default(),
+        }
+    }
 
-        Ok(cfg)
+    /// Where the pane called `name` goes, against the right edge unless configured.
+    pub fn placement(&self, name: &str) -> Placement {
+        self.layout.get(name).copied().unwrap_or_default()
+    }
+
+    /// Resolve the selected theme with the `Normal` styles and then the styles of `mode` layered on top.
+    pub fn theme(&self, mode: Mode) -> Theme {
+        let name = self.theme.as_deref().unwrap_or(DEFAULT_THEME);
+        let theme = Theme::load(name).unwrap_or_else(|e| {
+            log::error!("Failed to load theme: {}", e);
+            Theme::load(DEFAULT_THEME).unwrap_or_default()
+        });
+        theme
+            .with_styles(self.styles.get(&Mode::Normal))
+            .with_styles(self.styles.get(&mode))
     }
 }
 
@@ -53,18 +379,28 @@ impl<'de> Deserialize<'de> for KeyBindings {
     where
         D: Deserializer<'de>,
     {
-        let parsed_map = HashMap::<Mode, HashMap<String, Action>>::deserialize(deserializer)?;
-
-        let keybindings = parsed_map
-            .into_iter()
-            .map(|(mode, inner_map)| {
-                let converted_inner_map = inner_map
-                    .into_iter()
-                    .map(|(key_str, cmd)| (parse_key_sequence(&key_str).unwrap(), cmd))
-                    .collect();
-                (mode, converted_inner_map)
-            })
-            .collect();
+        let parsed_map = HashMap::<Mode, HashMap<String, JsonValue>>::deserialize(deserializer)?;
+
+        let mut keybindings = HashMap::new();
+        for (mode, inner_map) in parsed_map {
+            let mut converted_inner_map = HashMap::new();
+            for (key_str, value) in inner_map {
+                let keys = parse_key_sequence(&key_str).map_err(|e| {
+                    de::Error::custom(format!(
+                        "invalid key `{}` in {:?} keybindings: {}",
+                        key_str, mode, e
+                    ))
+                })?;
+                let action = Action::deserialize(&value).map_err(|e| {
+                    de::Error::custom(format!(
+                        "unknown action {} bound to `{}` in {:?} keybindings: {}",
+                        value, key_str, mode, e
+                    ))
+                })?;
+                converted_inner_map.insert(keys, action);
+            }
+            keybindings.insert(mode, converted_inner_map);
+        }
 
         Ok(KeyBindings(keybindings))
     }
@@ -289,7 +625,9 @@ fn process_color_string(color_str: &str) -> (String, Modifier) {
         .replace("bright ", "")
         .replace("bold ", "")
         .replace("underline ", "")
-        .replace("inverse ", "");
+        .replace("inverse ", "")
+        .replace("italic ", "")
+        .replace("dim ", "");
 
     let mut modifiers = Modifier::empty();
     if color_str.contains("underline") {
@@ -301,6 +639,12 @@ fn process_color_string(color_str: &str) -> (String, Modifier) {
     if color_str.contains("inverse") {
         modifiers |= Modifier::REVERSED;
     }
+    if color_str.contains("italic") {
+        modifiers |= Modifier::ITALIC;
+    }
+    if color_str.contains("dim") {
+        modifiers |= Modifier::DIM;
+    }
 
     (color, modifiers)
 }
@@ -410,6 +754,14 @@ mod tests {
         assert!(modifiers.contains(Modifier::REVERSED));
     }
 
+    #[test]
+    fn test_process_color_string_dim_italic() {
+        let (color, modifiers) = process_color_string("dim italic red");
+        assert_eq!(color, "red");
+        assert!(modifiers.contains(Modifier::DIM));
+        assert!(modifiers.contains(Modifier::ITALIC));
+    }
+
     #[test]
     fn test_parse_color_rgb() {
         let color = parse_color("rgb123");
@@ -425,10 +777,10 @@ mod tests {
 
     #[test]
     fn test_config() -> Result<()> {
-        let c = Config::new()?;
+        let c = Config::new(None)?;
         assert_eq!(
             c.keybindings
-                .get(&Mode::Home)
+                .get(&Mode::Insert)
                 .unwrap()
                 .get(&parse_key_sequence("<Ctrl-c>").unwrap_or_default())
                 .unwrap(),
@@ -437,6 +789,152 @@ mod tests {
         Ok(())
     }
 
+    fn parse_config(s: &str) -> Result<Config, config::ConfigError> {
+        config::Config::builder()
+            .add_source(config::File::from_str(s, config::FileFormat::Json5))
+            .build()?
+            .try_deserialize()
+    }
+
+    #[test]
+    fn test_keybindings_from_json5() {
+        let c = parse_config(
+            r#"{ keybindings: { Home: { "<q>": "Quit", "<Ctrl-f>": { Apropos: "" } } } }"#,
+        )
+        .unwrap();
+        let keymap = c.keybindings.get(&Mode::Normal).unwrap();
+        assert_eq!(
+            keymap.get(&parse_key_sequence("<q>").unwrap()),
+            Some(&Action::Quit)
+        );
+        assert_eq!(
+            keymap.get(&parse_key_sequence("<Ctrl-f>").unwrap()),
+            Some(&Action::Apropos(String::new()))
+        );
+    }
+
+    #[test]
+    fn test_keybindings_per_mode() {
+        let c = parse_config(
+            r#"{ keybindings: { Normal: { "<q>": "Quit" }, Insert: { "<Ctrl-j>": "Submit" } } }"#,
+        )
+        .unwrap();
+        let q = parse_key_sequence("<q>").unwrap();
+        assert_eq!(c.keybindings[&Mode::Normal].get(&q), Some(&Action::Quit));
+        assert_eq!(c.keybindings[&Mode::Insert].get(&q), None);
+        assert_eq!(
+            c.keybindings[&Mode::Insert].get(&parse_key_sequence("<Ctrl-j>").unwrap()),
+            Some(&Action::Submit)
+        );
+    }
+
+    #[test]
+    fn test_default_keybindings() {
+        let c = Config::default_config();
+        let enter = parse_key_sequence("<Enter>").unwrap();
+        assert_eq!(
+            c.keybindings[&Mode::Insert].get(&enter),
+            Some(&Action::Submit)
+        );
+        assert_eq!(
+            c.keybindings[&Mode::Multiline].get(&enter),
+            Some(&Action::InsertNewline)
+        );
+        assert_eq!(c.keybindings[&Mode::Normal].get(&enter), None);
+        assert_eq!(
+            c.keybindings[&Mode::Normal].get(&parse_key_sequence("<g><Shift-t>").unwrap()),
+            Some(&Action::PrevTab)
+        );
+        assert!(c.keybindings.values().all(|keymap| keymap
+            .get(&parse_key_sequence("<Ctrl-c>").unwrap())
+            == Some(&Action::Quit)));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let c = parse_config(
+            r#"{ profiles: { scrim: { address: "10.0.0.2:27015", password: "hunter2", tags: ["event"] }, local: { address: "127.0.0.1:27015" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(c.profiles["scrim"].password, "hunter2");
+        assert_eq!(c.profiles["scrim"].tags, vec!["event"]);
+        assert_eq!(c.profiles["local"].password, "");
+        assert!(c.profiles["local"].tags.is_empty());
+    }
+
+    #[test]
+    fn test_matches() {
+        let c = parse_config(
+            r#"{ matches: { scrim: { auto: true, transitions: [{ from: "Warmup", on: "Ready", to: "Live", run: ["exec live"] }] } } }"#,
+        )
+        .unwrap();
+        assert!(c.matches["scrim"].auto);
+        assert_eq!(
+            c.matches["scrim"].transitions[0].to,
+            crate::controller::Phase::Live
+        );
+        assert!(Config::new(None)
+            .unwrap()
+            .matches
+            .contains_key("competitive"));
+    }
+
+    #[test]
+    fn test_ready() {
+        let c = parse_config(r#"{ ready: { required: 1, on_ready: ["exec live"] } }"#).unwrap();
+        assert_eq!(c.ready.required, 1);
+        assert_eq!(c.ready.on_ready, vec!["exec live"]);
+        // Anything left out keeps its default.
+        assert_eq!(c.ready.prefixes, vec![".", "!"]);
+        assert!(c.ready.announce);
+    }
+
+    #[test]
+    fn test_layout() {
+        let c = parse_config(
+            r#"{ layout: { killfeed: { region: "Left", size: 30 } }, killfeed: { history: 10 } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            c.layout["killfeed"],
+            Placement {
+                region: crate::layout::Region::Left,
+                size: 30,
+                visible: false,
+            }
+        );
+        assert_eq!(c.killfeed.history, 10);
+        assert_eq!(c.placement("chat"), Placement::default());
+    }
+
+    #[test]
+    fn test_validation() {
+        use crate::command::validator::Policy;
+        let c = parse_config(r#"{ validation: { cheat: "Block", read_only: "Ignore" } }"#).unwrap();
+        assert_eq!(
+            c.validation,
+            ValidationPolicy {
+                cheat: Policy::Block,
+                read_only: Policy::Ignore,
+                ..ValidationPolicy::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_keybindings_unknown_action() {
+        let e = parse_config(r#"{ keybindings: { Home: { "<j>": "ScheduleIncrement" } } }"#)
+            .unwrap_err();
+        assert!(e.to_string().contains("ScheduleIncrement"));
+        assert!(e.to_string().contains("<j>"));
+    }
+
+    #[test]
+    fn test_keybindings_invalid_key() {
+        let e = parse_config(r#"{ keybindings: { Home: { "<Ctrl-foo>": "Quit" } } }"#).unwrap_err();
+        assert!(e.to_string().contains("<Ctrl-foo>"));
+    }
+
     #[test]
     fn test_simple_keys() {
         assert_eq!(
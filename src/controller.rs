@@ -0,0 +1,503 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::{
+    action::Action,
+    command::logs::{side, LogEvent, LogLine},
+};
+
+/// Where a match is at.
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display, EnumString,
+)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum Phase {
+    /// No match is being run.
+    #[default]
+    Idle,
+    Warmup,
+    Knife,
+    /// The knife round is over and its winner picks a side.
+    SidePick,
+    Live,
+    Halftime,
+    Overtime,
+    Finished,
+}
+
+/// What moves a match on to its next phase.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum Trigger {
+    /// `match start`
+    Start,
+    /// Everyone readied up.
+    Ready,
+    /// A team won the knife round.
+    KnifeWon,
+    /// The knife winner keeps its side.
+    Stay,
+    /// The knife winner takes the other side.
+    Switch,
+    /// Half of `mp_maxrounds` is played.
+    Halftime,
+    RoundStart,
+    /// Regulation ended in a draw with `mp_overtime_enable` set.
+    Overtime,
+    GameOver,
+}
+
+/// Move from one phase to another on a trigger, running commands on the way.
+///
+/// `{winner}` in a command is replaced with the team that won the knife round.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    pub from: Phase,
+    pub on: Trigger,
+    pub to: Phase,
+    #[serde(default)]
+    pub run: Vec<String>,
+}
+
+impl Transition {
+    fn new(from: Phase, on: Trigger, to: Phase, run: &[&str]) -> Self {
+        Self {
+            from,
+            on,
+            to,
+            run: run.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+/// How a kind of match moves through its phases.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchType {
+    /// Run the commands of a transition right away instead of asking first.
+    #[serde(default)]
+    pub auto: bool,
+    pub transitions: Vec<Transition>,
+}
+
+impl MatchType {
+    /// The match types available without any config: `competitive` with a knife round
+    /// for sides, and `wingman` straight from warmup to live.
+    pub fn defaults() -> HashMap<String, MatchType> {
+        use Phase::*;
+        let regulation = [
+            Transition::new(Live, Trigger::Halftime, Halftime, &[]),
+            Transition::new(Halftime, Trigger::RoundStart, Live, &[]),
+            Transition::new(Live, Trigger::Overtime, Overtime, &[]),
+            Transition::new(Live, Trigger::GameOver, Finished, &[]),
+            Transition::new(Overtime, Trigger::GameOver, Finished, &[]),
+        ];
+        let competitive = [
+            Transition::new(Idle, Trigger::Start, Warmup, &["exec warmup"]),
+            Transition::new(Warmup, Trigger::Ready, Knife, &["exec knife"]),
+            Transition::new(
+                Knife,
+                Trigger::KnifeWon,
+                SidePick,
+                &[
+                    "mp_warmup_start",
+                    "say {winner} won the knife round, .stay or .switch",
+                ],
+            ),
+            Transition::new(SidePick, Trigger::Stay, Live, &["exec live"]),
+            Transition::new(
+                SidePick,
+                Trigger::Switch,
+                Live,
+                &["mp_swapteams", "exec live"],
+            ),
+        ];
+        let wingman = [
+            Transition::new(Idle, Trigger::Start, Warmup, &["exec warmup"]),
+            Transition::new(Warmup, Trigger::Ready, Live, &["exec wingman_live"]),
+        ];
+        HashMap::from([
+            (
+                "competitive".to_string(),
+                MatchType {
+                    auto: false,
+                    transitions: competitive.into_iter().chain(regulation.clone()).collect(),
+                },
+            ),
+            (
+                "wingman".to_string(),
+                MatchType {
+                    auto: false,
+                    transitions: wingman.into_iter().chain(regulation).collect(),
+                },
+            ),
+        ])
+    }
+}
+
+/// A transition that was taken, with the commands to run for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub from: Phase,
+    pub to: Phase,
+    pub run: Vec<String>,
+    /// Whether to run the commands without asking.
+    pub auto: bool,
+}
+
+/// Follows a match through its phases from the server log and convar values,
+/// and says what to run at each transition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchController {
+    name: Option<String>,
+    kind: Option<MatchType>,
+    phase: Phase,
+    max_rounds: u32,
+    overtime: bool,
+    /// Rounds won by CT and T.
+    score: (u32, u32),
+    knife_winner: Option<String>,
+}
+
+impl Default for MatchController {
+    fn default() -> Self {
+        Self {
+            name: None,
+            kind: None,
+            phase: Phase::Idle,
+            max_rounds: 24,
+            overtime: true,
+            score: (0, 0),
+            knife_winner: None,
+        }
+    }
+}
+
+impl MatchController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn score(&self) -> (u32, u32) {
+        self.score
+    }
+
+    /// The side that won the knife round, which gets to pick.
+    pub fn knife_winner(&self) -> Option<&str> {
+        self.knife_winner.as_deref()
+    }
+
+    /// Convars the controller follows, queried when a match starts.
+    pub fn convars() -> [&'static str; 2] {
+        ["mp_maxrounds", "mp_overtime_enable"]
+    }
+
+    /// A short description for the tab bar, `None` while no match is run.
+    pub fn label(&self) -> Option<String> {
+        match self.phase {
+            Phase::Idle => None,
+            Phase::Live | Phase::Halftime | Phase::Overtime | Phase::Finished => {
+                Some(format!("{} {}:{}", self.phase, self.score.0, self.score.1))
+            }
+            phase => Some(phase.to_string()),
+        }
+    }
+
+    /// Lines describing the match, for `match status`.
+    pub fn describe(&self) -> Vec<String> {
+        let Some(name) = &self.name else {
+            return vec!["No match running, start one with `match start <type>`".to_string()];
+        };
+        let mut lines = vec![
+            format!("Match: {} in {}", name, self.phase),
+            format!(
+                "Score: CT {} - {} T, {} rounds{}",
+                self.score.0,
+                self.score.1,
+                self.max_rounds,
+                match self.overtime {
+                    true => " with overtime",
+                    false => "",
+                }
+            ),
+        ];
+        if let Some(kind) = &self.kind {
+            for transition in kind.transitions.iter().filter(|t| t.from == self.phase) {
+                lines.push(format!("  on {} -> {}", transition.on, transition.to));
+            }
+        }
+        lines
+    }
+
+    /// Start a match of `kind` from scratch.
+    pub fn start(&mut self, name: &str, kind: MatchType) -> Result<Step, String> {
+        *self = Self {
+            name: Some(name.to_string()),
+            kind: Some(kind),
+            max_rounds: self.max_rounds,
+            overtime: self.overtime,
+            ..Self::default()
+        };
+        self.trigger(Trigger::Start)
+    }
+
+    pub fn stop(&mut self) {
+        *self = Self {
+            max_rounds: self.max_rounds,
+            overtime: self.overtime,
+            ..Self::default()
+        };
+    }
+
+    /// Put the match in `phase` without running anything, to correct it by hand.
+    pub fn force(&mut self, phase: Phase) {
+        self.phase = phase;
+    }
+
+    /// Take the transition for `trigger` out of the current phase.
+    pub fn trigger(&mut self, trigger: Trigger) -> Result<Step, String> {
+        let kind = self.kind.as_ref().ok_or("No match running")?;
+        let transition = kind
+            .transitions
+            .iter()
+            .find(|t| t.from == self.phase && t.on == trigger)
+            .ok_or_else(|| format!("Nothing happens on {} in {}", trigger, self.phase))?;
+        let winner = self.knife_winner.as_deref().unwrap_or("The winner");
+        let step = Step {
+            from: self.phase,
+            to: transition.to,
+            run: transition
+                .run
+                .iter()
+                .map(|c| c.replace("{winner}", winner))
+                .collect(),
+            auto: kind.auto,
+        };
+        // The live cfg restarts the game, the second half goes on with the score.
+        if step.to == Phase::Live && step.from != Phase::Halftime {
+            self.score = (0, 0);
+        }
+        self.phase = step.to;
+        log::info!(
+            "Match moved from {} to {} on {}",
+            step.from,
+            step.to,
+            trigger
+        );
+        Ok(step)
+    }
+
+    /// The trigger a log event stands for in the current phase, if any.
+    fn log_trigger(&mut self, event: &LogEvent) -> Option<Trigger> {
+        match event {
+            LogEvent::RoundWon { team, .. } if self.phase == Phase::Knife => {
+                self.knife_winner = side(team).map(str::to_string);
+                Some(Trigger::KnifeWon)
+            }
+            LogEvent::RoundWon {
+                ct_score, t_score, ..
+            } if matches!(self.phase, Phase::Live | Phase::Overtime) => {
+                self.score = (*ct_score, *t_score);
+                let played = ct_score + t_score;
+                match self.phase {
+                    Phase::Live if self.max_rounds > 0 && played == self.max_rounds / 2 => {
+                        Some(Trigger::Halftime)
+                    }
+                    Phase::Live
+                        if self.overtime && played == self.max_rounds && ct_score == t_score =>
+                    {
+                        Some(Trigger::Overtime)
+                    }
+                    _ => None,
+                }
+            }
+            LogEvent::MatchStatus {
+                ct_score, t_score, ..
+            } if matches!(self.phase, Phase::Live | Phase::Halftime | Phase::Overtime) => {
+                self.score = (*ct_score, *t_score);
+                None
+            }
+            LogEvent::RoundStart => Some(Trigger::RoundStart),
+            LogEvent::GameOver { score, .. } => {
+                self.score = *score;
+                Some(Trigger::GameOver)
+            }
+            LogEvent::Cvar { name, value } => {
+                self.set_convar(name, value);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn set_convar(&mut self, name: &str, value: &str) {
+        match name {
+            "mp_maxrounds" => self.max_rounds = value.parse().unwrap_or(self.max_rounds),
+            "mp_overtime_enable" => self.overtime = value != "0",
+            _ => {}
+        }
+    }
+
+    fn observe(&mut self, line: &LogLine) -> Option<Step> {
+        let trigger = self.log_trigger(&line.event)?;
+        // Most events mean nothing in most phases, only an actual transition counts.
+        self.kind.as_ref()?;
+        self.trigger(trigger).ok()
+    }
+
+    /// Follow the log and convar values of the session, returns the transition taken if any.
+    pub fn update(&mut self, action: &Action) -> Option<Step> {
+        match action {
+            Action::Log(line) => self.observe(line),
+            Action::ConvarValue(name, value, _) => {
+                self.set_convar(name, value);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::command::logs::parse_line;
+
+    fn log(controller: &mut MatchController, line: &str) -> Option<Step> {
+        let line = parse_line(&format!("10/18/2026 - 21:30:00.000 - {}", line)).unwrap();
+        controller.update(&Action::Log(Box::new(line)))
+    }
+
+    fn round_won(controller: &mut MatchController, team: &str, ct: u32, t: u32) -> Option<Step> {
+        log(
+            controller,
+            &format!(
+                r#"Team "{}" triggered "SFUI_Notice_Target_Bombed" (CT "{}") (T "{}")"#,
+                team, ct, t
+            ),
+        )
+    }
+
+    fn competitive() -> MatchType {
+        MatchType::defaults()["competitive"].clone()
+    }
+
+    #[test]
+    fn test_competitive_match() {
+        let mut controller = MatchController::new();
+        assert_eq!(controller.label(), None);
+        assert_eq!(
+            controller.trigger(Trigger::Ready).unwrap_err(),
+            "No match running"
+        );
+        // The log alone does not start a match.
+        assert_eq!(round_won(&mut controller, "CT", 1, 0), None);
+
+        let step = controller.start("competitive", competitive()).unwrap();
+        assert_eq!(step.run, vec!["exec warmup"]);
+        assert!(!step.auto);
+        assert_eq!(controller.label(), Some("warmup".to_string()));
+        assert!(controller.trigger(Trigger::Stay).is_err());
+
+        let step = controller.trigger(Trigger::Ready).unwrap();
+        assert_eq!((step.from, step.to), (Phase::Warmup, Phase::Knife));
+        assert_eq!(step.run, vec!["exec knife"]);
+
+        let step = round_won(&mut controller, "TERRORIST", 0, 1).unwrap();
+        assert_eq!(step.to, Phase::SidePick);
+        assert_eq!(step.run[1], "say T won the knife round, .stay or .switch");
+
+        let step = controller.trigger(Trigger::Switch).unwrap();
+        assert_eq!(step.run, vec!["mp_swapteams", "exec live"]);
+        assert_eq!(controller.phase(), Phase::Live);
+        assert_eq!(controller.score(), (0, 0));
+    }
+
+    #[test]
+    fn test_halftime_and_overtime() {
+        let mut controller = MatchController::new();
+        controller.update(&Action::ConvarValue(
+            "mp_maxrounds".to_string(),
+            "4".to_string(),
+            None,
+        ));
+        controller
+            .start("wingman", MatchType::defaults()["wingman"].clone())
+            .unwrap();
+        assert_eq!(
+            controller.trigger(Trigger::Ready).unwrap().run,
+            vec!["exec wingman_live"]
+        );
+        assert_eq!(round_won(&mut controller, "CT", 1, 0), None);
+        assert_eq!(
+            round_won(&mut controller, "CT", 2, 0).map(|s| s.to),
+            Some(Phase::Halftime)
+        );
+        assert_eq!(controller.label(), Some("halftime 2:0".to_string()));
+        assert_eq!(
+            log(&mut controller, r#"World triggered "Round_Start""#).map(|s| s.to),
+            Some(Phase::Live)
+        );
+        // Rounds starting mid-half change nothing.
+        assert_eq!(
+            log(&mut controller, r#"World triggered "Round_Start""#),
+            None
+        );
+        round_won(&mut controller, "TERRORIST", 2, 1);
+        assert_eq!(
+            round_won(&mut controller, "TERRORIST", 2, 2).map(|s| s.to),
+            Some(Phase::Overtime)
+        );
+        assert_eq!(
+            log(
+                &mut controller,
+                "Game Over: competitive mg_active de_nuke score 3:5 after 40 min"
+            )
+            .map(|s| s.to),
+            Some(Phase::Finished)
+        );
+        assert_eq!(controller.label(), Some("finished 3:5".to_string()));
+    }
+
+    #[test]
+    fn test_convars_from_log() {
+        let mut controller = MatchController::new();
+        log(&mut controller, r#"server cvar "mp_overtime_enable" = "0""#);
+        log(&mut controller, r#"server cvar "mp_maxrounds" = "2""#);
+        controller.start("competitive", competitive()).unwrap();
+        controller.force(Phase::Live);
+        assert_eq!(
+            round_won(&mut controller, "CT", 1, 0).map(|s| s.to),
+            Some(Phase::Halftime)
+        );
+        controller.force(Phase::Live);
+        // Without overtime a draw is left to the server to end.
+        assert_eq!(round_won(&mut controller, "TERRORIST", 1, 1), None);
+        assert!(controller.describe()[1].ends_with("2 rounds"));
+    }
+
+    #[test]
+    fn test_configured_transitions() {
+        let kind: MatchType = json5::from_str(
+            r#"{ auto: true, transitions: [
+                { from: 'Idle', on: 'Start', to: 'Live', run: ['exec live'] },
+                { from: 'Live', on: 'GameOver', to: 'Idle' },
+            ] }"#,
+        )
+        .unwrap();
+        let mut controller = MatchController::new();
+        let step = controller.start("scrim", kind).unwrap();
+        assert!(step.auto);
+        assert_eq!(step.to, Phase::Live);
+        controller.stop();
+        assert_eq!(controller.phase(), Phase::Idle);
+        assert!(controller.describe()[0].starts_with("No match"));
+    }
+}
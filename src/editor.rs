@@ -0,0 +1,212 @@
+use tui_input::InputRequest;
+
+/// A minimal multi-line text buffer used when the input is expanded, e.g. for pasted cfg blocks.
+#[derive(Debug, Clone)]
+pub struct Editor {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        // There is always a line for the cursor to be on.
+        Self {
+            lines: vec![String::new()],
+            row: 0,
+            col: 0,
+        }
+    }
+}
+
+impl Editor {
+    pub fn new(text: &str) -> Self {
+        let mut editor = Self::default();
+        editor.insert_str(text);
+        editor
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Lines that would actually be sent, i.e. without blank lines.
+    pub fn line_count(&self) -> usize {
+        self.lines.iter().filter(|l| !l.trim().is_empty()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.line_count() == 0
+    }
+
+    /// Cursor position as (column, row) in characters.
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.col, self.row)
+    }
+
+    fn byte_index(&self) -> usize {
+        self.lines[self.row]
+            .char_indices()
+            .nth(self.col)
+            .map(|(i, _)| i)
+            .unwrap_or(self.lines[self.row].len())
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let index = self.byte_index();
+        self.lines[self.row].insert(index, c);
+        self.col += 1;
+    }
+
+    pub fn insert_newline(&mut self) {
+        let index = self.byte_index();
+        let rest = self.lines[self.row].split_off(index);
+        self.row += 1;
+        self.col = 0;
+        self.lines.insert(self.row, rest);
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.replace("\r\n", "\n").replace('\r', "\n").chars() {
+            match c {
+                '\n' => self.insert_newline(),
+                c => self.insert_char(c),
+            }
+        }
+    }
+
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row + 1 < self.lines.len() {
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn prev_word(&self) -> usize {
+        let chars = self.lines[self.row].chars().collect::<Vec<_>>();
+        let mut col = self.col;
+        while col > 0 && chars[col - 1].is_whitespace() {
+            col -= 1;
+        }
+        while col > 0 && !chars[col - 1].is_whitespace() {
+            col -= 1;
+        }
+        col
+    }
+
+    fn next_word(&self) -> usize {
+        let chars = self.lines[self.row].chars().collect::<Vec<_>>();
+        let mut col = self.col;
+        while col < chars.len() && !chars[col].is_whitespace() {
+            col += 1;
+        }
+        while col < chars.len() && chars[col].is_whitespace() {
+            col += 1;
+        }
+        col
+    }
+
+    /// Remove the characters between two columns of the current line.
+    fn remove_range(&mut self, from: usize, to: usize) {
+        self.lines[self.row] = self.lines[self.row]
+            .chars()
+            .enumerate()
+            .filter(|(i, _)| *i < from || *i >= to)
+            .map(|(_, c)| c)
+            .collect();
+        self.col = from;
+    }
+
+    pub fn up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    pub fn down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    /// Apply an edit to the current line, returns false if nothing changed.
+    pub fn handle(&mut self, request: InputRequest) -> bool {
+        let before = (self.row, self.col, self.lines.clone());
+        match request {
+            InputRequest::SetCursor(col) => self.col = col.min(self.line_len(self.row)),
+            InputRequest::InsertChar(c) => self.insert_str(&c.to_string()),
+            InputRequest::GoToPrevChar if self.col > 0 => self.col -= 1,
+            InputRequest::GoToPrevChar if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+            }
+            InputRequest::GoToNextChar if self.col < self.line_len(self.row) => self.col += 1,
+            InputRequest::GoToNextChar if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = 0;
+            }
+            InputRequest::GoToPrevChar | InputRequest::GoToNextChar => {}
+            InputRequest::GoToPrevWord => self.col = self.prev_word(),
+            InputRequest::GoToNextWord => self.col = self.next_word(),
+            InputRequest::GoToStart => self.col = 0,
+            InputRequest::GoToEnd => self.col = self.line_len(self.row),
+            InputRequest::DeletePrevChar => self.backspace(),
+            InputRequest::DeleteNextChar => self.delete(),
+            InputRequest::DeletePrevWord => self.remove_range(self.prev_word(), self.col),
+            InputRequest::DeleteNextWord => self.remove_range(self.col, self.next_word()),
+            InputRequest::DeleteLine => self.remove_range(0, self.line_len(self.row)),
+            InputRequest::DeleteTillEnd => self.remove_range(self.col, self.line_len(self.row)),
+        }
+        before != (self.row, self.col, self.lines.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_editing() {
+        let mut editor = Editor::new("mp_warmup_end\nbot_quota 10");
+        assert_eq!(editor.cursor(), (12, 1));
+        editor.handle(InputRequest::DeletePrevWord);
+        assert_eq!(editor.lines(), ["mp_warmup_end", "bot_quota "]);
+        editor.handle(InputRequest::GoToStart);
+        editor.handle(InputRequest::DeletePrevChar);
+        assert_eq!(editor.lines(), ["mp_warmup_endbot_quota "]);
+        assert_eq!(editor.cursor(), (13, 0));
+        editor.insert_newline();
+        editor.up();
+        editor.handle(InputRequest::SetCursor(9));
+        editor.handle(InputRequest::DeleteTillEnd);
+        assert_eq!(editor.text(), "mp_warmup\nbot_quota ");
+    }
+}
@@ -0,0 +1,456 @@
+use std::{
+    io::{self, ErrorKind, Write},
+    process::ExitCode,
+};
+
+use rcon::Error;
+use serde::Serialize;
+use tokio::time::{timeout, Duration};
+
+use crate::{
+    broadcast::{self, ServerResult},
+    cli::{Commands, ServerArgs},
+    client::Client,
+    command::{
+        cfg,
+        status::Status,
+        validator::{Validation, ValidationPolicy, Validator},
+    },
+    config::{Config, Profile},
+    repl,
+};
+
+/// Exit codes of the non-interactive commands.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Exit {
+    Ok = 0,
+    /// A command failed to send or the server did not know it.
+    CommandFailed = 1,
+    /// Bad arguments, e.g. an unknown profile or missing cfg file.
+    Usage = 2,
+    /// Could not connect or authenticate.
+    ConnectFailed = 3,
+    /// The server did not answer in time, connecting or to a command.
+    TimedOut = 4,
+    /// Validation blocked the command, or asked to confirm it without `--force`.
+    Refused = 5,
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub command: String,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    #[serde(skip)]
+    pub timed_out: bool,
+}
+
+impl Response {
+    pub fn ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Print the response to stdout, or the error to stderr.
+    pub fn print(&self) -> io::Result<()> {
+        match (&self.error, &self.response) {
+            (Some(error), _) => writeln!(io::stderr(), "{}: {}", self.command, error),
+            (None, Some(text)) if !text.is_empty() => {
+                let mut stdout = io::stdout().lock();
+                write!(stdout, "{}", text)?;
+                if !text.ends_with('\n') {
+                    writeln!(stdout)?;
+                }
+                stdout.flush()
+            }
+            (None, _) => Ok(()),
+        }
+    }
+}
+
+/// Find the server to connect to from `--profile` or `--address`.
+pub fn resolve(server: &ServerArgs, config: &Config) -> Result<Profile, String> {
+    match (&server.profile, &server.address) {
+        (Some(name), _) => config
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("unknown profile `{}`", name)),
+        (None, Some(address)) => Ok(Profile {
+            address: address.clone(),
+            password: server.password.clone().unwrap_or_default(),
+            ..Default::default()
+        }),
+        (None, None) => Err("either --profile or --address is required".to_string()),
+    }
+}
+
+/// Why connecting failed, a server that does not answer is told apart from one that
+/// refuses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectError {
+    Failed(String),
+    TimedOut(String),
+}
+
+impl ConnectError {
+    pub fn exit(&self) -> Exit {
+        match self {
+            Self::Failed(_) => Exit::ConnectFailed,
+            Self::TimedOut(_) => Exit::TimedOut,
+        }
+    }
+}
+
+impl std::fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Failed(e) | Self::TimedOut(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+pub async fn connect(profile: &Profile, limit: Duration) -> Result<Client, ConnectError> {
+    let mut client = Client::default();
+    client.set_address(&profile.address);
+    client.set_password(&profile.password);
+    match timeout(limit, client.connect()).await {
+        Ok(Ok(_)) => Ok(client),
+        Ok(Err(e)) => Err(ConnectError::Failed(format!(
+            "failed to connect to {}: {:?}",
+            profile.address, e
+        ))),
+        Err(_) => Err(ConnectError::TimedOut(format!(
+            "timed out connecting to {} after {}s",
+            profile.address,
+            limit.as_secs()
+        ))),
+    }
+}
+
+/// Send a command, treating a response saying the command is unknown as a failure.
+pub async fn send(client: &mut Client, command: &str, limit: Duration) -> Response {
+    let failed = |error: String, timed_out: bool| Response {
+        command: command.to_string(),
+        response: None,
+        error: Some(error),
+        timed_out,
+    };
+    client.set_timeout(limit);
+    match client.send_command(command).await {
+        Ok(response) if response.trim_start().starts_with("Unknown command") => Response {
+            command: command.to_string(),
+            error: Some(response.trim().to_string()),
+            response: Some(response),
+            timed_out: false,
+        },
+        Ok(response) => Response {
+            command: command.to_string(),
+            response: Some(response),
+            error: None,
+            timed_out: false,
+        },
+        Err(Error::Io(e)) if e.kind() == ErrorKind::TimedOut => {
+            failed(format!("no response after {}s", limit.as_secs()), true)
+        }
+        Err(e) => failed(format!("{:?}", e), false),
+    }
+}
+
+/// The exit code for `responses`, a timeout wins over a failed command.
+fn exit(responses: &[Response]) -> Exit {
+    if responses.iter().any(|r| r.timed_out) {
+        Exit::TimedOut
+    } else if responses.iter().all(Response::ok) {
+        Exit::Ok
+    } else {
+        Exit::CommandFailed
+    }
+}
+
+fn print_responses(responses: &[Response], json: bool) -> Exit {
+    if json {
+        match serde_json::to_string_pretty(responses) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("{}", e),
+        }
+    } else {
+        for response in responses {
+            if response.print().is_err() {
+                break;
+            }
+        }
+    }
+    exit(responses)
+}
+
+/// Check every command a broadcast sends like the terminal UI does, warnings are printed
+/// and confirming is left to `force`.
+fn check_broadcast(validator: &Validator, command: &str, force: bool) -> Result<(), String> {
+    match validator.validate_line(command) {
+        Validation::Ok => {}
+        Validation::Warn(warning) => eprintln!("{}: {}", command, warning),
+        Validation::Confirm(reason) if force => eprintln!("{}: {}", command, reason),
+        Validation::Confirm(reason) => {
+            return Err(format!("{}: {}, use --force to send it", command, reason))
+        }
+        Validation::Block(reason) => return Err(format!("{}: blocked: {}", command, reason)),
+    }
+    Ok(())
+}
+
+async fn run_broadcast(
+    config: &Config,
+    group: &str,
+    command: &str,
+    json: bool,
+    force: bool,
+    limit: Duration,
+) -> Exit {
+    let targets = broadcast::group(&config.profiles, group);
+    if targets.is_empty() {
+        eprintln!("no profiles tagged `{}`", group);
+        return Exit::Usage;
+    }
+    if let Err(e) = check_broadcast(&Validator::new(config.validation), command, force) {
+        eprintln!("{}", e);
+        return Exit::Refused;
+    }
+    let results = broadcast::broadcast(targets, command, limit).await;
+    if json {
+        match serde_json::to_string_pretty(&results) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("{}", e),
+        }
+    } else {
+        let width = crossterm::terminal::size()
+            .map(|(width, _)| width as usize)
+            .unwrap_or(120);
+        let mut stdout = io::stdout().lock();
+        for line in broadcast::columns(&results, width) {
+            if writeln!(stdout, "{}", line).is_err() {
+                break;
+            }
+        }
+    }
+    match results.iter().all(ServerResult::ok) {
+        true => Exit::Ok,
+        false => Exit::CommandFailed,
+    }
+}
+
+/// Run one of the non-interactive subcommands.
+pub async fn run(command: Commands, config: &Config) -> Exit {
+    let (server, commands) = match &command {
+        Commands::Run { server, command } => (server, vec![command.join(" ")]),
+        Commands::Exec { server, file } => match std::fs::read_to_string(cfg::path(file)) {
+            Ok(contents) => (server, cfg::commands(&contents)),
+            Err(e) => {
+                eprintln!("{}: {}", cfg::path(file).display(), e);
+                return Exit::Usage;
+            }
+        },
+        Commands::Status { server } => (server, vec!["status".to_string()]),
+        Commands::Repl { server } => return repl::run(server, config).await,
+        Commands::Broadcast {
+            group,
+            command,
+            json,
+            force,
+            timeout,
+        } => {
+            let limit = Duration::from_secs(*timeout);
+            return run_broadcast(config, group, &command.join(" "), *json, *force, limit).await;
+        }
+    };
+    let profile = match resolve(server, config) {
+        Ok(profile) => profile,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Exit::Usage;
+        }
+    };
+    let mut client = match connect(&profile, server.timeout()).await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("{}", e);
+            return e.exit();
+        }
+    };
+
+    let mut responses = Vec::new();
+    for command in commands {
+        let response = send(&mut client, &command, server.timeout()).await;
+        // The connection is in an unknown state after a timeout.
+        let timed_out = response.timed_out;
+        responses.push(response);
+        if timed_out {
+            break;
+        }
+    }
+
+    match command {
+        Commands::Status { .. } if server.json => {
+            let Some(Response {
+                response: Some(response),
+                ..
+            }) = responses.first()
+            else {
+                return print_responses(&responses, false);
+            };
+            if responses[0].timed_out {
+                return print_responses(&responses, false);
+            }
+            let mut status = Status::default();
+            status.update(response.clone());
+            match serde_json::to_string_pretty(&status) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("{}", e),
+            }
+            Exit::Ok
+        }
+        _ => print_responses(&responses, server.json),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn server(profile: Option<&str>, address: Option<&str>) -> ServerArgs {
+        ServerArgs {
+            profile: profile.map(str::to_string),
+            address: address.map(str::to_string),
+            password: Some("secret".to_string()),
+            json: false,
+            timeout: 1,
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "scrim".to_string(),
+            Profile {
+                address: "10.0.0.2:27015".to_string(),
+                password: "hunter2".to_string(),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            resolve(&server(Some("scrim"), None), &config)
+                .unwrap()
+                .password,
+            "hunter2"
+        );
+        assert_eq!(
+            resolve(&server(None, Some("127.0.0.1:27015")), &config).unwrap(),
+            Profile {
+                address: "127.0.0.1:27015".to_string(),
+                password: "secret".to_string(),
+                ..Default::default()
+            }
+        );
+        assert!(resolve(&server(Some("missing"), None), &config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_failure_exit_code() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "closed".to_string(),
+            Profile {
+                address: "127.0.0.1:1".to_string(),
+                ..Default::default()
+            },
+        );
+        let status = Commands::Status {
+            server: server(Some("closed"), None),
+        };
+        assert_eq!(run(status, &config).await, Exit::ConnectFailed);
+        let unknown = Commands::Status {
+            server: server(Some("missing"), None),
+        };
+        assert_eq!(run(unknown, &config).await, Exit::Usage);
+        let empty = Commands::Broadcast {
+            group: "event".to_string(),
+            command: vec!["status".to_string()],
+            json: false,
+            force: false,
+            timeout: 1,
+        };
+        assert_eq!(run(empty, &config).await, Exit::Usage);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_is_validated() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "closed".to_string(),
+            Profile {
+                address: "127.0.0.1:1".to_string(),
+                tags: vec!["event".to_string()],
+                ..Default::default()
+            },
+        );
+        let broadcast = |command: &str, force: bool| Commands::Broadcast {
+            group: "event".to_string(),
+            command: vec![command.to_string()],
+            json: true,
+            force,
+            timeout: 1,
+        };
+        // sv_cheats is unknown, so a cheat has to be confirmed.
+        assert_eq!(
+            run(broadcast("sv_infinite_ammo 1", false), &config).await,
+            Exit::Refused
+        );
+        // Forced, it gets as far as the closed server.
+        assert_eq!(
+            run(broadcast("sv_infinite_ammo 1", true), &config).await,
+            Exit::CommandFailed
+        );
+        config.validation.cheat = crate::command::validator::Policy::Block;
+        assert_eq!(
+            run(broadcast("say hi; sv_infinite_ammo 1", true), &config).await,
+            Exit::Refused
+        );
+    }
+
+    #[tokio::test]
+    async fn test_silent_server_times_out() {
+        // Takes the connection but never answers the login.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+        let status = Commands::Status {
+            server: server(None, Some(&address)),
+        };
+        let started = std::time::Instant::now();
+        assert_eq!(run(status, &Config::default()).await, Exit::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_check_broadcast() {
+        let validator = Validator::new(ValidationPolicy::default());
+        assert!(check_broadcast(&validator, "say hi; mp_restartgame 1", false).is_ok());
+        // The command after the `;` needs confirming just like on its own.
+        assert!(check_broadcast(&validator, "say hi; sv_infinite_ammo 1", false).is_err());
+        assert!(check_broadcast(&validator, "say hi; sv_infinite_ammo 1", true).is_ok());
+        assert!(check_broadcast(&validator, "exec missing", true).is_err());
+    }
+}
@@ -1,13 +1,39 @@
-use crossterm::event::{Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
-
-use tui_input::{backend::crossterm::EventHandler, Input, StateChanged};
-
-use crate::command::{autocompleter::AutoCompleter, history::History, Command};
+use tui_input::{Input, InputRequest};
+
+use crate::{
+    action::Action,
+    command::{
+        autocompleter::AutoCompleter,
+        convar::{ConvarCache, ConvarValue},
+        history::History,
+        Command,
+    },
+};
+
+/// The text field edit an action stands for, if any.
+pub fn input_request(action: &Action) -> Option<InputRequest> {
+    Some(match action {
+        Action::GoToPrevChar => InputRequest::GoToPrevChar,
+        Action::GoToNextChar => InputRequest::GoToNextChar,
+        Action::GoToPrevWord => InputRequest::GoToPrevWord,
+        Action::GoToNextWord => InputRequest::GoToNextWord,
+        Action::GoToStart => InputRequest::GoToStart,
+        Action::GoToEnd => InputRequest::GoToEnd,
+        Action::DeletePrevChar => InputRequest::DeletePrevChar,
+        Action::DeleteNextChar => InputRequest::DeleteNextChar,
+        Action::DeletePrevWord => InputRequest::DeletePrevWord,
+        Action::DeleteNextWord => InputRequest::DeleteNextWord,
+        Action::DeleteLine => InputRequest::DeleteLine,
+        Action::DeleteTillEnd => InputRequest::DeleteTillEnd,
+        _ => return None,
+    })
+}
 
 pub struct Inputwrapper {
     input: Input,
     history: History,
     auto_completer: AutoCompleter,
+    convars: ConvarCache,
     pub suggestion: Option<String>,
 }
 
@@ -17,21 +43,40 @@ impl Inputwrapper {
             input: Input::default(),
             history: History::new(),
             auto_completer: AutoCompleter::default(),
+            convars: ConvarCache::default(),
             suggestion: None,
         }
     }
 
     pub fn init(&mut self) {
-        self.auto_completer.load_commands(".config/commands.txt");
-        self.auto_completer.load_commands(".config/convars.txt");
+        self.auto_completer.load_catalog();
     }
 
     pub fn value(&self) -> &str {
         self.input.value()
     }
 
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.input.handle(InputRequest::InsertChar(c));
+        }
+        self.update_suggestion();
+        self.watch_convar();
+    }
+
+    pub fn set_value(&mut self, value: String) {
+        self.input = Input::new(value);
+        self.update_suggestion();
+        self.watch_convar();
+    }
+
+    pub fn search(&self, query: &str) -> Vec<Command> {
+        self.auto_completer.search(query)
+    }
+
     pub fn reset(&mut self) {
         self.input.reset();
+        self.watch_convar();
     }
 
     pub fn cursor(&self) -> usize {
@@ -42,42 +87,12 @@ impl Inputwrapper {
         self.input.visual_scroll(width)
     }
 
-    pub fn handle_event(&mut self, event: &Event) -> Option<StateChanged> {
-        let state_changed = match event {
-            Event::Key(key) => match key.code {
-                KeyCode::Up => {
-                    self.forwards();
-                    Some(StateChanged {
-                        value: false,
-                        cursor: false,
-                    })
-                }
-                KeyCode::Down => {
-                    self.backwards();
-                    Some(StateChanged {
-                        value: false,
-                        cursor: false,
-                    })
-                }
-                KeyCode::Tab => {
-                    self.accept_suggestion();
-                    Some(StateChanged {
-                        value: true,
-                        cursor: true,
-                    })
-                }
-                _ => self.input.handle_event(event),
-            },
-            _ => self.input.handle_event(event),
-        };
-        match state_changed {
-            Some(state_changed) => {
-                if state_changed.value {
-                    self.update_suggestion();
-                }
-                Some(state_changed)
+    pub fn handle(&mut self, request: InputRequest) {
+        if let Some(state_changed) = self.input.handle(request) {
+            if state_changed.value {
+                self.update_suggestion();
             }
-            _ => None,
+            self.watch_convar();
         }
     }
 
@@ -85,6 +100,7 @@ impl Inputwrapper {
         if let Some(command) = self.history.forwards() {
             self.update_suggestion();
             self.input = Input::new(command);
+            self.watch_convar();
         }
     }
 
@@ -99,6 +115,7 @@ impl Inputwrapper {
                 self.update_suggestion();
             }
         }
+        self.watch_convar();
     }
 
     pub fn push_history(&mut self, command: String) {
@@ -126,6 +143,7 @@ impl Inputwrapper {
         if let Some(suggestion) = &self.suggestion {
             self.input = Input::new(suggestion.clone());
         }
+        self.watch_convar();
     }
 
     pub fn get_current_command(&self) -> Option<Command> {
@@ -133,6 +151,36 @@ impl Inputwrapper {
         let value = command_parts[0];
         self.auto_completer.get_command(value)
     }
+
+    fn watch_convar(&mut self) {
+        let command = self.get_current_command().filter(|c| c.is_convar());
+        self.convars
+            .watch(command.as_ref().map(|c| c.name.as_str()));
+    }
+
+    /// Returns the convar under the cursor once the input has settled and its cached value is missing or stale.
+    pub fn convar_query_due(&mut self) -> Option<String> {
+        self.convars.due()
+    }
+
+    pub fn convar_value(&self, name: &str) -> Option<&ConvarValue> {
+        self.convars.get(name)
+    }
+
+    pub fn set_convar_value(&mut self, name: String, value: String, default: Option<String>) {
+        self.convars.insert(name, value, default);
+    }
+
+    /// Mark the cached value of the convar set by `command` as stale.
+    pub fn invalidate_convar(&mut self, command: &str) {
+        if let Some(name) = command.split(' ').next() {
+            self.convars.invalidate(name);
+        }
+    }
+
+    pub fn invalidate_convars(&mut self) {
+        self.convars.invalidate_all();
+    }
 }
 
 impl Default for Inputwrapper {
@@ -0,0 +1,109 @@
+use ratatui::layout::Rect;
+use serde::Deserialize;
+
+/// An edge of the output a pane can be put against.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize)]
+pub enum Region {
+    Top,
+    Bottom,
+    Left,
+    #[default]
+    Right,
+}
+
+/// Where a pane goes and whether it is shown from the start.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Placement {
+    pub region: Region,
+    /// Columns for `Left` and `Right`, rows for `Top` and `Bottom`.
+    pub size: u16,
+    pub visible: bool,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Self {
+            region: Region::Right,
+            size: 40,
+            visible: false,
+        }
+    }
+}
+
+/// Take the panes off the edges of `rect` one after the other, returns their rects and
+/// what is left for the output. A pane never takes more than half of what is left.
+pub fn split(rect: Rect, placements: &[Placement]) -> (Vec<Rect>, Rect) {
+    let mut rest = rect;
+    let mut rects = Vec::new();
+    for placement in placements {
+        let pane = match placement.region {
+            Region::Top | Region::Bottom => {
+                let height = placement.size.min(rest.height / 2);
+                rest.height -= height;
+                let y = match placement.region {
+                    Region::Top => {
+                        rest.y += height;
+                        rest.y - height
+                    }
+                    _ => rest.y + rest.height,
+                };
+                Rect { y, height, ..rest }
+            }
+            Region::Left | Region::Right => {
+                let width = placement.size.min(rest.width / 2);
+                rest.width -= width;
+                let x = match placement.region {
+                    Region::Left => {
+                        rest.x += width;
+                        rest.x - width
+                    }
+                    _ => rest.x + rest.width,
+                };
+                Rect { x, width, ..rest }
+            }
+        };
+        rects.push(pane);
+    }
+    (rects, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn placement(region: Region, size: u16) -> Placement {
+        Placement {
+            region,
+            size,
+            visible: true,
+        }
+    }
+
+    #[test]
+    fn test_split() {
+        let rect = Rect::new(0, 1, 100, 30);
+        assert_eq!(split(rect, &[]), (vec![], rect));
+        assert_eq!(
+            split(rect, &[placement(Region::Right, 40)]),
+            (vec![Rect::new(60, 1, 40, 30)], Rect::new(0, 1, 60, 30))
+        );
+        assert_eq!(
+            split(
+                rect,
+                &[placement(Region::Top, 5), placement(Region::Left, 20)]
+            ),
+            (
+                vec![Rect::new(0, 1, 100, 5), Rect::new(0, 6, 20, 25)],
+                Rect::new(20, 6, 80, 25)
+            )
+        );
+        // The output keeps at least half.
+        assert_eq!(
+            split(rect, &[placement(Region::Bottom, 100)]),
+            (vec![Rect::new(0, 16, 100, 15)], Rect::new(0, 1, 100, 15))
+        );
+    }
+}
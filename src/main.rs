@@ -5,16 +5,35 @@
 extern crate lazy_static;
 pub mod action;
 pub mod app;
+pub mod broadcast;
+pub mod browser;
+pub mod chord;
 pub mod cli;
 pub mod client;
 pub mod command;
 pub mod components;
 pub mod config;
+pub mod controller;
+pub mod editor;
+pub mod headless;
 pub mod inputwrapper;
+pub mod layout;
+pub mod matchfile;
 pub mod mode;
+pub mod palette;
 pub mod popup;
+pub mod ready;
+pub mod receiver;
+pub mod repl;
+pub mod report;
+pub mod session;
+pub mod stats;
+pub mod theme;
 pub mod tui;
 pub mod utils;
+pub mod watcher;
+
+use std::process::ExitCode;
 
 use clap::Parser;
 use cli::Cli;
@@ -22,27 +41,33 @@ use color_eyre::eyre::Result;
 
 use crate::{
     app::App,
+    config::Config,
     utils::{initialize_logging, initialize_panic_handler},
 };
 
-async fn tokio_main() -> Result<()> {
+async fn tokio_main() -> Result<ExitCode> {
     initialize_logging()?;
 
     initialize_panic_handler()?;
 
     let args = Cli::parse();
-    let mut app = App::new(args.tick_rate, args.frame_rate)?;
+    if let Some(command) = args.command {
+        let config = Config::new(args.config)?;
+        return Ok(headless::run(command, &config).await.into());
+    }
+    let mut app = App::new(args.tick_rate, args.frame_rate, args.config)?;
     app.run().await?;
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    if let Err(e) = tokio_main().await {
-        eprintln!("{} error: Something went wrong", env!("CARGO_PKG_NAME"));
-        Err(e)
-    } else {
-        Ok(())
+async fn main() -> Result<ExitCode> {
+    match tokio_main().await {
+        Ok(code) => Ok(code),
+        Err(e) => {
+            eprintln!("{} error: Something went wrong", env!("CARGO_PKG_NAME"));
+            Err(e)
+        }
     }
 }
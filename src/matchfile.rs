@@ -0,0 +1,751 @@
+use std::{collections::BTreeSet, path::Path};
+
+use serde::Deserialize;
+
+use crate::{
+    command::{cfg, logs::steam3},
+    controller::{MatchType, Phase, Transition, Trigger},
+};
+
+/// A team of a match file, `team1` starts on CT.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TeamConfig {
+    pub name: String,
+    /// Two letter country code shown next to the name.
+    #[serde(default)]
+    pub flag: String,
+    /// SteamIDs of the players, in any of the usual forms.
+    #[serde(default)]
+    pub players: Vec<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TeamSlot {
+    Team1,
+    Team2,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VetoAction {
+    Ban,
+    Pick,
+    /// The map left over, played last.
+    Decider,
+}
+
+/// One step of the map veto.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Veto {
+    /// Who bans or picks, none for the decider.
+    #[serde(default)]
+    pub team: Option<TeamSlot>,
+    pub action: VetoAction,
+    pub map: String,
+}
+
+/// The cfg run when each phase begins, empty for none.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PhaseCfgs {
+    pub warmup: String,
+    pub knife: String,
+    pub live: String,
+    pub halftime: String,
+    pub overtime: String,
+    pub finished: String,
+}
+
+impl Default for PhaseCfgs {
+    fn default() -> Self {
+        Self {
+            warmup: "warmup".to_string(),
+            knife: "knife".to_string(),
+            live: "live".to_string(),
+            halftime: String::new(),
+            overtime: String::new(),
+            finished: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Overtime {
+    pub enabled: bool,
+    pub max_rounds: u32,
+    pub start_money: u32,
+}
+
+impl Default for Overtime {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_rounds: 6,
+            start_money: 10000,
+        }
+    }
+}
+
+/// A whole match in one file, loaded with `match load <file>`: the teams, the maps to
+/// play, the cfgs to run per phase and the rules.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MatchFile {
+    #[serde(default)]
+    pub name: String,
+    pub team1: TeamConfig,
+    pub team2: TeamConfig,
+    /// Maps played in order, or the outcome of `veto`.
+    #[serde(default)]
+    pub maps: Vec<String>,
+    #[serde(default)]
+    pub veto: Vec<Veto>,
+    #[serde(default = "default_max_rounds")]
+    pub max_rounds: u32,
+    #[serde(default)]
+    pub overtime: Overtime,
+    /// Record a demo of every map with GOTV.
+    #[serde(default)]
+    pub gotv: bool,
+    /// Sides are picked by the winner of a knife round, `team1` starts on CT otherwise.
+    #[serde(default = "default_knife")]
+    pub knife: bool,
+    #[serde(default)]
+    pub cfgs: PhaseCfgs,
+    /// Run the commands of a transition right away instead of asking first.
+    #[serde(default)]
+    pub auto: bool,
+}
+
+fn default_max_rounds() -> u32 {
+    24
+}
+
+fn default_knife() -> bool {
+    true
+}
+
+/// Whether `value` can go into a server command, a quote or `;` would end it and start
+/// another.
+fn is_safe(value: &str) -> bool {
+    !value.contains(['"', ';']) && !value.contains(char::is_control)
+}
+
+impl MatchFile {
+    /// Read and validate the match file at `path`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let file = Self::parse(&contents)
+            .map_err(|e| format!("Invalid match file {}: {}", path.display(), e))?;
+        Ok(file)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let file: Self = json5::from_str(contents).map_err(|e| e.to_string())?;
+        file.validate()?;
+        Ok(file)
+    }
+
+    /// Everything wrong with the file, so it can be fixed in one go.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+        let mut players = BTreeSet::new();
+        for team in [&self.team1, &self.team2] {
+            if team.name.trim().is_empty() {
+                problems.push("a team has no name".to_string());
+            } else if !is_safe(&team.name) {
+                problems.push(format!("team name `{}` has a quote or `;`", team.name));
+            }
+            let flag = team.flag.len() == 2 && team.flag.chars().all(|c| c.is_ascii_uppercase());
+            if !team.flag.is_empty() && !flag {
+                problems.push(format!(
+                    "flag `{}` of {} is not a two letter country code",
+                    team.flag, team.name
+                ));
+            }
+            for player in &team.players {
+                match steam3(player) {
+                    Some(id) if !players.insert(id.clone()) => {
+                        problems.push(format!("{} is listed twice", player))
+                    }
+                    Some(_) => {}
+                    None => problems.push(format!("`{}` is not a SteamID", player)),
+                }
+            }
+        }
+        if self.team1.name == self.team2.name {
+            problems.push("both teams have the same name".to_string());
+        }
+        match (self.maps.is_empty(), self.veto.is_empty()) {
+            (true, true) => problems.push("no maps or veto".to_string()),
+            (false, false) => problems.push("both maps and veto, only one can be used".to_string()),
+            _ => {}
+        }
+        let mut seen = BTreeSet::new();
+        for map in self.maps.iter().chain(self.veto.iter().map(|v| &v.map)) {
+            if map.trim().is_empty() || map.contains(char::is_whitespace) || !is_safe(map) {
+                problems.push(format!("`{}` is not a map name", map));
+            } else if !seen.insert(map) {
+                problems.push(format!("{} is in there twice", map));
+            }
+        }
+        for (i, veto) in self.veto.iter().enumerate() {
+            match (veto.action, veto.team) {
+                (VetoAction::Decider, Some(_)) => {
+                    problems.push(format!("the decider {} has a team", veto.map))
+                }
+                (VetoAction::Decider, None) if i + 1 < self.veto.len() => {
+                    problems.push(format!("the decider {} is not the last step", veto.map))
+                }
+                (VetoAction::Ban | VetoAction::Pick, None) => {
+                    problems.push(format!("no team bans or picks {}", veto.map))
+                }
+                _ => {}
+            }
+        }
+        if !self.veto.is_empty() && self.maps().is_empty() {
+            problems.push("the veto leaves no map to play".to_string());
+        }
+        if self.max_rounds == 0 || !self.max_rounds.is_multiple_of(2) {
+            problems.push(format!(
+                "max_rounds {} is not an even number",
+                self.max_rounds
+            ));
+        }
+        if self.overtime.enabled
+            && (self.overtime.max_rounds == 0 || !self.overtime.max_rounds.is_multiple_of(2))
+        {
+            problems.push(format!(
+                "overtime max_rounds {} is not an even number",
+                self.overtime.max_rounds
+            ));
+        }
+        let cfgs = &self.cfgs;
+        for name in [
+            &cfgs.warmup,
+            &cfgs.knife,
+            &cfgs.live,
+            &cfgs.halftime,
+            &cfgs.overtime,
+            &cfgs.finished,
+        ] {
+            if !is_safe(name) {
+                problems.push(format!("cfg `{}` has a quote or `;`", name));
+            } else if !name.is_empty() && !cfg::path(name).exists() {
+                problems.push(format!("there is no {}", cfg::path(name).display()));
+            }
+        }
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(problems.join(", ")),
+        }
+    }
+
+    /// Name of the match, the team names if it has none.
+    pub fn title(&self) -> String {
+        match self.name.as_str() {
+            "" => format!("{} vs {}", self.team1.name, self.team2.name),
+            name => name.to_string(),
+        }
+    }
+
+    /// The maps to play, in order.
+    pub fn maps(&self) -> Vec<String> {
+        match self.veto.is_empty() {
+            true => self.maps.clone(),
+            false => self
+                .veto
+                .iter()
+                .filter(|v| v.action != VetoAction::Ban)
+                .map(|v| v.map.clone())
+                .collect(),
+        }
+    }
+
+    /// SteamIDs of the players of both teams, as the log has them.
+    pub fn roster(&self) -> BTreeSet<String> {
+        self.team1
+            .players
+            .iter()
+            .chain(&self.team2.players)
+            .filter_map(|player| steam3(player))
+            .collect()
+    }
+
+    /// The cfg run when `phase` begins, if any.
+    pub fn cfg(&self, phase: Phase) -> Option<&str> {
+        let name = match phase {
+            Phase::Warmup => &self.cfgs.warmup,
+            Phase::Knife => &self.cfgs.knife,
+            Phase::Live => &self.cfgs.live,
+            Phase::Halftime => &self.cfgs.halftime,
+            Phase::Overtime => &self.cfgs.overtime,
+            Phase::Finished => &self.cfgs.finished,
+            Phase::Idle | Phase::SidePick => return None,
+        };
+        (!name.is_empty()).then_some(name.as_str())
+    }
+
+    /// The team names and flags, rounds, overtime and GOTV as server commands.
+    pub fn settings(&self) -> Vec<String> {
+        let mut commands = Vec::new();
+        for (i, team) in [(1, &self.team1), (2, &self.team2)] {
+            commands.push(format!("mp_teamname_{} \"{}\"", i, team.name));
+            if !team.flag.is_empty() {
+                commands.push(format!("mp_teamflag_{} \"{}\"", i, team.flag));
+            }
+        }
+        commands.push(format!("mp_maxrounds {}", self.max_rounds));
+        commands.push(format!(
+            "mp_overtime_enable {}",
+            u8::from(self.overtime.enabled)
+        ));
+        if self.overtime.enabled {
+            commands.push(format!(
+                "mp_overtime_maxrounds {}",
+                self.overtime.max_rounds
+            ));
+            commands.push(format!(
+                "mp_overtime_startmoney {}",
+                self.overtime.start_money
+            ));
+        }
+        if self.gotv {
+            commands.push("tv_enable 1".to_string());
+        }
+        commands
+    }
+
+    /// Name of the demo of the map at `index`, like `cup_final_1_de_nuke`.
+    pub fn demo(&self, index: usize) -> String {
+        let title = self
+            .title()
+            .to_lowercase()
+            .chars()
+            .map(|c| match c.is_ascii_alphanumeric() {
+                true => c,
+                false => '_',
+            })
+            .collect::<String>();
+        format!(
+            "{}_{}_{}",
+            title,
+            index + 1,
+            self.maps().get(index).map_or("", |m| m.as_str())
+        )
+    }
+
+    /// How the map at `index` moves through its phases. The settings follow each cfg so the
+    /// cfgs can't undo them, the next map is left to the [`Series`] as it may be decided.
+    pub fn match_type(&self, index: usize) -> MatchType {
+        use Phase::*;
+        let run = |phase: Phase, before: &[String], after: &[String]| {
+            let mut commands = before.to_vec();
+            if let Some(cfg) = self.cfg(phase) {
+                commands.push(format!("exec {}", cfg));
+            }
+            commands.extend_from_slice(after);
+            commands
+        };
+        let settings = self.settings();
+        let mut live = settings.clone();
+        if self.gotv {
+            live.push(format!("tv_record {}", self.demo(index)));
+        }
+        let mut transitions = vec![Transition {
+            from: Idle,
+            on: Trigger::Start,
+            to: Warmup,
+            run: run(Warmup, &[], &settings),
+        }];
+        match self.knife {
+            true => transitions.extend([
+                Transition {
+                    from: Warmup,
+                    on: Trigger::Ready,
+                    to: Knife,
+                    run: run(Knife, &[], &[]),
+                },
+                Transition {
+                    from: Knife,
+                    on: Trigger::KnifeWon,
+                    to: SidePick,
+                    run: vec![
+                        "mp_warmup_start".to_string(),
+                        "say {winner} won the knife round, .stay or .switch".to_string(),
+                    ],
+                },
+                Transition {
+                    from: SidePick,
+                    on: Trigger::Stay,
+                    to: Live,
+                    run: run(Live, &[], &live),
+                },
+                Transition {
+                    from: SidePick,
+                    on: Trigger::Switch,
+                    to: Live,
+                    run: run(Live, &["mp_swapteams".to_string()], &live),
+                },
+            ]),
+            false => transitions.push(Transition {
+                from: Warmup,
+                on: Trigger::Ready,
+                to: Live,
+                run: run(Live, &[], &live),
+            }),
+        }
+        let mut finished = Vec::new();
+        if self.gotv {
+            finished.push("tv_stoprecord".to_string());
+        }
+        transitions.extend([
+            Transition {
+                from: Live,
+                on: Trigger::Halftime,
+                to: Halftime,
+                run: run(Halftime, &[], &[]),
+            },
+            Transition {
+                from: Halftime,
+                on: Trigger::RoundStart,
+                to: Live,
+                run: vec![],
+            },
+            Transition {
+                from: Live,
+                on: Trigger::Overtime,
+                to: Overtime,
+                run: run(Overtime, &[], &[]),
+            },
+            Transition {
+                from: Live,
+                on: Trigger::GameOver,
+                to: Finished,
+                run: run(Finished, &[], &finished),
+            },
+            Transition {
+                from: Overtime,
+                on: Trigger::GameOver,
+                to: Finished,
+                run: run(Finished, &[], &finished),
+            },
+        ]);
+        MatchType {
+            auto: self.auto,
+            transitions,
+        }
+    }
+
+    /// Lines describing the match, for `match load` and `match status`.
+    pub fn describe(&self) -> Vec<String> {
+        let team = |team: &TeamConfig| match team.flag.as_str() {
+            "" => format!("{}, {} players", team.name, team.players.len()),
+            flag => format!("{} [{}], {} players", team.name, flag, team.players.len()),
+        };
+        vec![
+            format!("Match file: {}", self.title()),
+            format!("  CT first: {}", team(&self.team1)),
+            format!("  T first: {}", team(&self.team2)),
+            format!(
+                "  Maps: {}, {} rounds{}{}",
+                self.maps().join(", "),
+                self.max_rounds,
+                match self.overtime.enabled {
+                    true => format!(", overtime of {}", self.overtime.max_rounds),
+                    false => String::new(),
+                },
+                match self.gotv {
+                    true => ", recorded",
+                    false => "",
+                }
+            ),
+        ]
+    }
+}
+
+/// A loaded match file being played, map after map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Series {
+    pub file: MatchFile,
+    /// Index of the map being played, or loaded.
+    pub map: usize,
+    /// The server is changing to the map, the match starts once it has.
+    pub loading: bool,
+    /// Maps won by `team1` and `team2`.
+    pub wins: (u32, u32),
+    /// Name of the team on CT as the log last had it, the sides swap at halftime.
+    pub ct_team: String,
+}
+
+impl Series {
+    pub fn new(file: MatchFile) -> Self {
+        Self {
+            file,
+            map: 0,
+            loading: false,
+            wins: (0, 0),
+            ct_team: String::new(),
+        }
+    }
+
+    pub fn current_map(&self) -> String {
+        self.file.maps().get(self.map).cloned().unwrap_or_default()
+    }
+
+    /// Count the map for the team that won it, from the CT and T score at game over.
+    pub fn record(&mut self, score: (u32, u32)) {
+        let ct = match self.ct_team.as_str() {
+            name if name == self.file.team1.name => TeamSlot::Team1,
+            name if name == self.file.team2.name => TeamSlot::Team2,
+            name => {
+                log::warn!("Team on CT `{}` is not in the match file", name);
+                return;
+            }
+        };
+        let winner = match score.0.cmp(&score.1) {
+            std::cmp::Ordering::Greater => ct,
+            std::cmp::Ordering::Less => match ct {
+                TeamSlot::Team1 => TeamSlot::Team2,
+                TeamSlot::Team2 => TeamSlot::Team1,
+            },
+            std::cmp::Ordering::Equal => return,
+        };
+        match winner {
+            TeamSlot::Team1 => self.wins.0 += 1,
+            TeamSlot::Team2 => self.wins.1 += 1,
+        }
+    }
+
+    /// The team that won most of the maps, once the maps left can't change it.
+    pub fn winner(&self) -> Option<&TeamConfig> {
+        let majority = self.file.maps().len() as u32 / 2 + 1;
+        match self.wins {
+            (team1, _) if team1 >= majority => Some(&self.file.team1),
+            (_, team2) if team2 >= majority => Some(&self.file.team2),
+            _ => None,
+        }
+    }
+
+    /// Move on to the next map once the game is over, `false` after the last one or once
+    /// the series is won.
+    pub fn next_map(&mut self) -> bool {
+        if self.map + 1 >= self.file.maps().len() || self.winner().is_some() {
+            return false;
+        }
+        self.map += 1;
+        self.loading = true;
+        true
+    }
+
+    /// The maps won so far, like `Team Alpha 1-0 Team Bravo`.
+    pub fn score(&self) -> String {
+        format!(
+            "{} {}-{} {}",
+            self.file.team1.name, self.wins.0, self.wins.1, self.file.team2.name
+        )
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "Map {} of {}: {}{}, {}",
+            self.map + 1,
+            self.file.maps().len(),
+            self.current_map(),
+            match self.loading {
+                true => ", loading",
+                false => "",
+            },
+            self.score()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CUP_FINAL: &str = include_str!("../fixtures/matches/cup_final.json5");
+
+    fn minimal() -> &'static str {
+        r#"{ team1: { name: "A" }, team2: { name: "B" }, maps: ["de_nuke"], gotv: false }"#
+    }
+
+    #[test]
+    fn test_parse() {
+        let file = MatchFile::parse(CUP_FINAL).unwrap();
+        assert_eq!(file.maps(), vec!["de_nuke", "de_inferno", "de_mirage"]);
+        assert_eq!(
+            file.roster().into_iter().collect::<Vec<_>>(),
+            vec![
+                "[U:1:1001]",
+                "[U:1:1002]",
+                "[U:1:1003]",
+                "[U:1:1004]",
+                "[U:1:1005]"
+            ]
+        );
+        assert_eq!(file.demo(1), "cup_final_2_de_inferno");
+        assert_eq!(
+            file.describe()[3],
+            "  Maps: de_nuke, de_inferno, de_mirage, 24 rounds, overtime of 6, recorded"
+        );
+
+        let file = MatchFile::parse(minimal()).unwrap();
+        assert_eq!(file.title(), "A vs B");
+        assert!(file.knife);
+        assert_eq!(file.overtime, Overtime::default());
+        assert_eq!(file.cfg(Phase::Live), Some("live"));
+        assert_eq!(file.cfg(Phase::Halftime), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        let problems = MatchFile::parse(
+            r#"{
+                team1: { name: "A", flag: "swe", players: ["[U:1:1]", "nobody"] },
+                team2: { name: "A", players: ["STEAM_1:1:0"] },
+                veto: [
+                    { action: "decider", map: "de_nuke" },
+                    { action: "ban", map: "de_nuke" },
+                ],
+                max_rounds: 13,
+                cfgs: { live: "nope" },
+            }"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            problems,
+            [
+                "flag `swe` of A is not a two letter country code",
+                "`nobody` is not a SteamID",
+                "STEAM_1:1:0 is listed twice",
+                "both teams have the same name",
+                "de_nuke is in there twice",
+                "the decider de_nuke is not the last step",
+                "no team bans or picks de_nuke",
+                "max_rounds 13 is not an even number",
+                "there is no cfg/nope.cfg",
+            ]
+            .join(", ")
+        );
+        assert!(
+            MatchFile::parse(r#"{ team1: { name: "A" }, team2: { name: "B" } }"#)
+                .unwrap_err()
+                .contains("no maps or veto")
+        );
+        assert_eq!(
+            MatchFile::parse(
+                r#"{
+                    team1: { name: "A\";quit" },
+                    team2: { name: "B", flag: "S;" },
+                    maps: ["de_nuke;quit", "de_dust2\""],
+                    cfgs: { live: "live;quit" },
+                }"#,
+            )
+            .unwrap_err(),
+            [
+                "team name `A\";quit` has a quote or `;`",
+                "flag `S;` of B is not a two letter country code",
+                "`de_nuke;quit` is not a map name",
+                "`de_dust2\"` is not a map name",
+                "cfg `live;quit` has a quote or `;`",
+            ]
+            .join(", ")
+        );
+        assert!(MatchFile::load(Path::new("nowhere.json5"))
+            .unwrap_err()
+            .starts_with("Failed to read nowhere.json5"));
+    }
+
+    #[test]
+    fn test_match_type() {
+        let file = MatchFile::parse(CUP_FINAL).unwrap();
+        let kind = file.match_type(0);
+        assert!(!kind.auto);
+        let run = |from: Phase, on: Trigger| {
+            kind.transitions
+                .iter()
+                .find(|t| t.from == from && t.on == on)
+                .unwrap()
+                .run
+                .clone()
+        };
+        let settings = [
+            "mp_teamname_1 \"Team Alpha\"",
+            "mp_teamflag_1 \"SE\"",
+            "mp_teamname_2 \"Team Bravo\"",
+            "mp_teamflag_2 \"NO\"",
+            "mp_maxrounds 24",
+            "mp_overtime_enable 1",
+            "mp_overtime_maxrounds 6",
+            "mp_overtime_startmoney 12500",
+            "tv_enable 1",
+        ];
+        assert_eq!(
+            run(Phase::Idle, Trigger::Start),
+            [&["exec warmup"], &settings[..]].concat()
+        );
+        assert_eq!(run(Phase::Warmup, Trigger::Ready), vec!["exec knife"]);
+        assert_eq!(
+            run(Phase::SidePick, Trigger::Switch),
+            [
+                &["mp_swapteams", "exec live"],
+                &settings[..],
+                &["tv_record cup_final_1_de_nuke"]
+            ]
+            .concat()
+        );
+        assert_eq!(run(Phase::Live, Trigger::GameOver), vec!["tv_stoprecord"]);
+
+        let file = MatchFile::parse(
+            r#"{ team1: { name: "A" }, team2: { name: "B" }, maps: ["de_nuke"], knife: false, auto: true }"#,
+        )
+        .unwrap();
+        let kind = file.match_type(0);
+        assert!(kind.auto);
+        let ready = kind
+            .transitions
+            .iter()
+            .find(|t| t.from == Phase::Warmup)
+            .unwrap();
+        assert_eq!(
+            (ready.to, ready.run[0].as_str()),
+            (Phase::Live, "exec live")
+        );
+    }
+
+    #[test]
+    fn test_series() {
+        let mut series = Series::new(MatchFile::parse(CUP_FINAL).unwrap());
+        series.ct_team = "Team Alpha".to_string();
+        series.record((13, 4));
+        assert!(series.next_map());
+        series.loading = false;
+        // Team Alpha is on T after halftime.
+        series.ct_team = "Team Bravo".to_string();
+        series.record((9, 13));
+        assert_eq!(series.wins, (2, 0));
+        assert_eq!(series.winner().unwrap().name, "Team Alpha");
+        // No decider after a 2-0.
+        assert!(!series.next_map());
+        assert_eq!(
+            series.describe(),
+            "Map 2 of 3: de_inferno, Team Alpha 2-0 Team Bravo"
+        );
+
+        let mut series = Series::new(MatchFile::parse(CUP_FINAL).unwrap());
+        series.ct_team = "Team Bravo".to_string();
+        series.record((13, 11));
+        series.ct_team = "Team Alpha".to_string();
+        series.record((16, 14));
+        assert_eq!(series.winner(), None);
+        assert!(series.next_map());
+        assert!(series.next_map());
+        assert_eq!(series.map, 2);
+    }
+}
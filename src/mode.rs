@@ -1,7 +1,45 @@
 use serde::{Deserialize, Serialize};
 
+/// The input state of the app, each with its own keymap and styles.
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
 pub enum Mode {
+    /// Keys are shortcuts, nothing is being typed.
+    Normal,
+    /// Typing a command into the input line.
     #[default]
-    Home,
+    Insert,
+    /// Editing a multi-line block in the expanded input.
+    Multiline,
+    /// Searching the command catalog.
+    Browse,
+    /// Picking something to run from the command palette.
+    Palette,
+    /// Reading the help screen.
+    Help,
+    /// Answering a confirmation prompt.
+    Confirm,
+    /// Typing a message into the chat pane.
+    Chat,
+    Processing,
+}
+
+impl TryFrom<String> for Mode {
+    type Error = String;
+
+    /// Parse a mode name, accepting `Home` from older configs for `Normal`.
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Ok(match name.as_str() {
+            "Normal" | "Home" => Mode::Normal,
+            "Insert" => Mode::Insert,
+            "Multiline" => Mode::Multiline,
+            "Browse" => Mode::Browse,
+            "Palette" => Mode::Palette,
+            "Help" => Mode::Help,
+            "Confirm" => Mode::Confirm,
+            "Chat" => Mode::Chat,
+            "Processing" => Mode::Processing,
+            _ => return Err(format!("unknown mode `{}`", name)),
+        })
+    }
 }
@@ -0,0 +1,312 @@
+use ratatui::{prelude::*, widgets::*};
+use tui_input::{Input, InputRequest};
+
+use crate::{action::Action, popup::Popup, theme::Theme};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EntryKind {
+    Action,
+    Command,
+    Profile,
+    Cfg,
+}
+
+impl EntryKind {
+    fn label(&self) -> &'static str {
+        match self {
+            EntryKind::Action => "action",
+            EntryKind::Command => "command",
+            EntryKind::Profile => "profile",
+            EntryKind::Cfg => "cfg",
+        }
+    }
+}
+
+/// What picking an entry does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Choice {
+    /// Dispatch the action right away.
+    Run(Action),
+    /// Put the text in the input line so the arguments can be filled in.
+    Edit(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub kind: EntryKind,
+    pub name: String,
+    pub description: String,
+    /// The key bound to the entry, if any.
+    pub key: Option<String>,
+    pub choice: Choice,
+}
+
+impl Entry {
+    pub fn new(kind: EntryKind, name: &str, description: &str, choice: Choice) -> Self {
+        Self {
+            kind,
+            name: name.to_string(),
+            description: description.to_string(),
+            key: None,
+            choice,
+        }
+    }
+
+    pub fn key(mut self, key: Option<String>) -> Self {
+        self.key = key;
+        self
+    }
+}
+
+/// Actions worth triggering by hand, with a description for the palette.
+pub fn actions() -> Vec<(Action, &'static str)> {
+    vec![
+        (Action::ToggleShowHelp, "Show the keybindings and commands"),
+        (Action::Apropos(String::new()), "Search the command catalog"),
+        (Action::EnterInsert, "Start typing a command"),
+        (Action::EnterNormal, "Stop typing a command"),
+        (Action::Expand, "Continue in the multi-line editor"),
+        (Action::FocusChat, "Reply in the in-game chat"),
+        (Action::ToggleChat, "Show or hide the in-game chat"),
+        (Action::ToggleScoreboard, "Show or hide the scoreboard"),
+        (Action::ToggleKillFeed, "Show or hide the kill feed"),
+        (
+            Action::NewTab,
+            "Open a session to another server in a new tab",
+        ),
+        (Action::NextTab, "Switch to the next tab"),
+        (Action::PrevTab, "Switch to the previous tab"),
+        (Action::CloseTab, "Close the current tab and its connection"),
+        (Action::Suspend, "Suspend the application"),
+        (Action::Quit, "Quit the application"),
+    ]
+}
+
+/// Score `text` against a fuzzy `query`, `None` if the query is not a subsequence of it.
+///
+/// Consecutive matches and matches at the start of a word score higher, gaps lower.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = position + text[position..].iter().position(|c| *c == q)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || matches!(text[index - 1], ' ' | '_' | '-' | '.' | ':') {
+            score += 3;
+        }
+        score -= (index - previous.map(|p| p + 1).unwrap_or(0)).min(3) as i64;
+        previous = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+/// Popup listing everything that can be run by hand, fuzzy filtered by a query.
+#[derive(Default)]
+pub struct Palette {
+    query: Input,
+    entries: Vec<Entry>,
+    results: Vec<usize>,
+    selected: usize,
+    page_size: usize,
+}
+
+impl Palette {
+    pub fn new(entries: Vec<Entry>) -> Self {
+        let mut palette = Self {
+            entries,
+            page_size: 10,
+            ..Default::default()
+        };
+        palette.search();
+        palette
+    }
+
+    fn search(&mut self) {
+        let query = self.query.value();
+        let mut results = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                let name = fuzzy_score(query, &entry.name);
+                let description = fuzzy_score(query, &entry.description).map(|s| s / 2);
+                Some((i, name.max(description)?))
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .cmp(a_score)
+                .then(self.entries[*a].kind.cmp(&self.entries[*b].kind))
+                .then(a.cmp(b))
+        });
+        self.results = results.into_iter().map(|(i, _)| i).collect();
+        self.selected = 0;
+    }
+
+    pub fn results(&self) -> Vec<&Entry> {
+        self.results.iter().map(|i| &self.entries[*i]).collect()
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.results.len().saturating_sub(1));
+    }
+
+    pub fn prev_item(&mut self) {
+        self.select(self.selected.saturating_sub(1));
+    }
+
+    pub fn next_item(&mut self) {
+        self.select(self.selected + 1);
+    }
+
+    pub fn prev_page(&mut self) {
+        self.select(self.selected.saturating_sub(self.page_size));
+    }
+
+    pub fn next_page(&mut self) {
+        self.select(self.selected + self.page_size);
+    }
+
+    pub fn selection(&self) -> Option<&Entry> {
+        self.results.get(self.selected).map(|i| &self.entries[*i])
+    }
+
+    pub fn edit(&mut self, request: InputRequest) {
+        if self.query.handle(request).is_some_and(|state| state.value) {
+            self.search();
+        }
+    }
+
+    pub fn widget(&mut self, area: Rect, theme: &Theme) -> Popup<'_> {
+        // 2 for borders, 2 for the query and 2 for the count
+        self.page_size = (area.height.saturating_sub(6) as usize).max(1);
+        let page = self.selected / self.page_size;
+        let width = area.width.saturating_sub(2) as usize;
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("> ", theme.get("hint")),
+                Span::raw(self.query.value()),
+            ]),
+            Line::from(""),
+        ];
+        for (i, entry) in self
+            .results()
+            .into_iter()
+            .enumerate()
+            .skip(page * self.page_size)
+            .take(self.page_size)
+        {
+            let style = match i == self.selected {
+                true => theme.get("selected"),
+                false => theme.get("popup"),
+            };
+            let kind = format!("{:<8}", entry.kind.label());
+            let key = entry.key.clone().unwrap_or_default();
+            let used = kind.len() + entry.name.chars().count() + 3 + key.len();
+            let description = entry
+                .description
+                .chars()
+                .take(width.saturating_sub(used + 1))
+                .collect::<String>();
+            let padding = width.saturating_sub(used + description.chars().count());
+            lines.push(Line::from(vec![
+                Span::styled(kind, style.patch(theme.get("hint"))),
+                Span::styled(entry.name.clone(), style.patch(theme.get("title"))),
+                Span::styled(" - ", style.patch(theme.get("hint"))),
+                Span::styled(description, style),
+                Span::styled(" ".repeat(padding), style),
+                Span::styled(key, style.patch(theme.get("key"))),
+            ]));
+        }
+        lines.resize(self.page_size + 2, Line::from(""));
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(Span::styled(
+                format!("{} of {}", self.results.len(), self.entries.len()),
+                theme.get("hint"),
+            ))
+            .alignment(Alignment::Right),
+        );
+
+        Popup::default()
+            .title("Command palette")
+            .content(lines)
+            .style(theme.get("popup"))
+            .title_style(theme.get("title"))
+            .border_style(theme.get("popup_border"))
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.query.visual_cursor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("xyz", "exec warmup"), None);
+        assert_eq!(fuzzy_score("", "exec"), Some(0));
+        assert!(fuzzy_score("wu", "warmup").is_some());
+        // Consecutive letters beat scattered ones, word starts beat the middle of words.
+        assert!(fuzzy_score("live", "live") > fuzzy_score("live", "wingman_live"));
+        assert!(fuzzy_score("wl", "wingman_live") > fuzzy_score("wl", "knife_wall"));
+        assert!(fuzzy_score("quit", "Quit") > fuzzy_score("quit", "quick exit"));
+    }
+
+    #[test]
+    fn test_search_ranking() {
+        let entries = vec![
+            Entry::new(
+                EntryKind::Action,
+                "Quit",
+                "Quit the application",
+                Choice::Run(Action::Quit),
+            ),
+            Entry::new(
+                EntryKind::Cfg,
+                "live",
+                "exec live",
+                Choice::Run(Action::Command("exec live".into())),
+            ),
+            Entry::new(
+                EntryKind::Cfg,
+                "wingman_live",
+                "exec wingman_live",
+                Choice::Run(Action::Command("exec wingman_live".into())),
+            ),
+            Entry::new(
+                EntryKind::Profile,
+                "local",
+                "127.0.0.1:27015",
+                Choice::Run(Action::Connect("127.0.0.1:27015".into(), String::new())),
+            ),
+        ];
+        let mut palette = Palette::new(entries);
+        assert_eq!(palette.results().len(), 4);
+        for c in "live".chars() {
+            palette.edit(InputRequest::InsertChar(c));
+        }
+        let names = palette
+            .results()
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["live", "wingman_live"]);
+        palette.next_item();
+        assert_eq!(palette.selection().unwrap().name, "wingman_live");
+        palette.next_item();
+        assert_eq!(palette.selection().unwrap().name, "wingman_live");
+    }
+}
@@ -0,0 +1,481 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Deserialize;
+use strum::{Display, EnumString};
+
+use crate::{
+    command::logs::{side, steam3, LogEvent, LogPlayer},
+    controller::{MatchController, Phase, Trigger},
+};
+
+/// How players ready up and pause from the game chat.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ReadyConfig {
+    /// What chat commands start with, `.ready` or `!ready` by default.
+    pub prefixes: Vec<String>,
+    /// Players of each team that have to be ready.
+    pub required: usize,
+    /// Run once everyone is ready while no match is run, a match moves on from warmup instead.
+    pub on_ready: Vec<String>,
+    pub on_pause: Vec<String>,
+    /// Run once both teams asked to unpause.
+    pub on_unpause: Vec<String>,
+    /// Say in the game chat who is ready and what is waited for.
+    pub announce: bool,
+}
+
+impl Default for ReadyConfig {
+    fn default() -> Self {
+        Self {
+            prefixes: vec![".".to_string(), "!".to_string()],
+            required: 5,
+            on_ready: vec!["mp_warmup_end".to_string()],
+            on_pause: vec!["mp_pause_match".to_string()],
+            on_unpause: vec!["mp_unpause_match".to_string()],
+            announce: true,
+        }
+    }
+}
+
+/// A command typed in the game chat.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum ChatCommand {
+    Ready,
+    Unready,
+    Pause,
+    Unpause,
+    Stay,
+    Switch,
+}
+
+impl ChatCommand {
+    /// The command a chat message stands for, if it starts with one of `prefixes`.
+    pub fn parse(message: &str, prefixes: &[String]) -> Option<Self> {
+        let message = message.trim();
+        let rest = prefixes
+            .iter()
+            .filter(|prefix| !prefix.is_empty())
+            .find_map(|prefix| message.strip_prefix(prefix.as_str()))?;
+        rest.split_whitespace().next()?.parse().ok()
+    }
+}
+
+/// What a chat command calls for.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// Announcements and configured commands to send to the server.
+    pub commands: Vec<String>,
+    /// Moves the match on, if one is run.
+    pub trigger: Option<Trigger>,
+}
+
+/// Keeps the ready state of both teams from chat commands in the server log.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReadyUp {
+    config: ReadyConfig,
+    /// Ready players by [`LogPlayer::key`], with their name and side.
+    ready: BTreeMap<String, (String, &'static str)>,
+    /// Sides that asked to unpause.
+    unpause: BTreeSet<&'static str>,
+    paused: bool,
+    /// SteamIDs of the players of a loaded match file, anyone may ready up when empty.
+    roster: BTreeSet<String>,
+}
+
+impl ReadyUp {
+    pub fn new(config: ReadyConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_config(&mut self, config: ReadyConfig) {
+        self.config = config;
+    }
+
+    pub fn set_roster(&mut self, roster: BTreeSet<String>) {
+        self.roster = roster;
+        self.ready.clear();
+    }
+
+    /// Ready players of CT and T.
+    pub fn counts(&self) -> (usize, usize) {
+        let ct = self.ready.values().filter(|(_, s)| *s == "CT").count();
+        (ct, self.ready.len() - ct)
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Handle a log event, given where the match is at.
+    pub fn update(&mut self, event: &LogEvent, controller: &MatchController) -> Outcome {
+        match event {
+            LogEvent::Say {
+                player, message, ..
+            } => match ChatCommand::parse(message, &self.config.prefixes) {
+                Some(command) => self.command(command, player, controller),
+                None => Outcome::default(),
+            },
+            LogEvent::SwitchedTeam { player, to, .. } => {
+                let key = player.key();
+                match side(to) {
+                    Some(side) => {
+                        if let Some(entry) = self.ready.get_mut(&key) {
+                            entry.1 = side;
+                        }
+                    }
+                    None => {
+                        self.ready.remove(&key);
+                    }
+                }
+                Outcome::default()
+            }
+            LogEvent::Disconnected { player, .. } => {
+                self.ready.remove(&player.key());
+                Outcome::default()
+            }
+            _ => Outcome::default(),
+        }
+    }
+
+    fn command(
+        &mut self,
+        command: ChatCommand,
+        player: &LogPlayer,
+        controller: &MatchController,
+    ) -> Outcome {
+        // Spectators and coaches have no say in it.
+        let Some(side) = player.side() else {
+            return Outcome::default();
+        };
+        let mut outcome = Outcome::default();
+        match command {
+            ChatCommand::Ready | ChatCommand::Unready
+                if matches!(controller.phase(), Phase::Idle | Phase::Warmup) =>
+            {
+                let rostered = steam3(&player.steamid).is_some_and(|id| self.roster.contains(&id));
+                if !self.roster.is_empty() && !rostered {
+                    self.say(
+                        &mut outcome,
+                        format!("{} is not on a team of this match", player.name),
+                    );
+                    return outcome;
+                }
+                let changed = match command {
+                    ChatCommand::Ready => self
+                        .ready
+                        .insert(player.key(), (player.name.clone(), side))
+                        .is_none(),
+                    _ => self.ready.remove(&player.key()).is_some(),
+                };
+                if !changed {
+                    return outcome;
+                }
+                let (ct, t) = self.counts();
+                let required = self.config.required;
+                self.say(
+                    &mut outcome,
+                    format!(
+                        "{} is {}, CT {}/{}, T {}/{}",
+                        player.name,
+                        match command {
+                            ChatCommand::Ready => "ready",
+                            _ => "not ready",
+                        },
+                        ct,
+                        required,
+                        t,
+                        required
+                    ),
+                );
+                if ct >= required && t >= required {
+                    self.ready.clear();
+                    self.say(&mut outcome, "Everyone is ready".to_string());
+                    match controller.phase() {
+                        Phase::Warmup => outcome.trigger = Some(Trigger::Ready),
+                        _ => outcome.commands.extend(self.config.on_ready.clone()),
+                    }
+                }
+            }
+            ChatCommand::Pause if !self.paused => {
+                self.paused = true;
+                self.unpause.clear();
+                self.say(&mut outcome, format!("{} paused the match", player.name));
+                outcome.commands.extend(self.config.on_pause.clone());
+            }
+            ChatCommand::Unpause if self.paused && self.unpause.insert(side) => {
+                if self.unpause.len() < 2 {
+                    let prefix = self.config.prefixes.first().map_or("", |p| p.as_str());
+                    self.say(
+                        &mut outcome,
+                        format!(
+                            "{} wants to unpause, waiting for {} to {}unpause",
+                            side,
+                            match side {
+                                "CT" => "T",
+                                _ => "CT",
+                            },
+                            prefix
+                        ),
+                    );
+                    return outcome;
+                }
+                self.paused = false;
+                self.unpause.clear();
+                self.say(&mut outcome, "Unpausing".to_string());
+                outcome.commands.extend(self.config.on_unpause.clone());
+            }
+            ChatCommand::Stay | ChatCommand::Switch
+                if controller.phase() == Phase::SidePick
+                    && controller.knife_winner() == Some(side) =>
+            {
+                outcome.trigger = Some(match command {
+                    ChatCommand::Stay => Trigger::Stay,
+                    _ => Trigger::Switch,
+                });
+            }
+            _ => {}
+        }
+        outcome
+    }
+
+    fn say(&self, outcome: &mut Outcome, message: String) {
+        if self.config.announce {
+            outcome
+                .commands
+                .push(format!("say {}", chat_safe(&message)));
+        }
+    }
+}
+
+/// `message` without what would end the `say` and start another command, player names
+/// are picked by the players.
+fn chat_safe(message: &str) -> String {
+    message
+        .chars()
+        .map(|c| match c {
+            ';' | '"' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        action::Action,
+        command::logs::parse_line,
+        controller::{MatchType, Step},
+    };
+
+    fn event(line: &str) -> LogEvent {
+        parse_line(&format!("10/18/2026 - 21:30:00.000 - {}", line))
+            .unwrap()
+            .event
+    }
+
+    fn say(ready: &mut ReadyUp, controller: &MatchController, who: &str, message: &str) -> Outcome {
+        let (name, team) = who.split_once(' ').unwrap();
+        ready.update(
+            &event(&format!(
+                r#""{}<2><[U:1:{}]><{}>" say "{}""#,
+                name, name, team, message
+            )),
+            controller,
+        )
+    }
+
+    fn two_a_side() -> ReadyUp {
+        ReadyUp::new(ReadyConfig {
+            required: 2,
+            ..ReadyConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_parse() {
+        let prefixes = ReadyConfig::default().prefixes;
+        assert_eq!(
+            ChatCommand::parse(".ready", &prefixes),
+            Some(ChatCommand::Ready)
+        );
+        assert_eq!(
+            ChatCommand::parse(" !UNPAUSE please", &prefixes),
+            Some(ChatCommand::Unpause)
+        );
+        assert_eq!(ChatCommand::parse("ready", &prefixes), None);
+        assert_eq!(ChatCommand::parse(".gg", &prefixes), None);
+        assert_eq!(
+            ChatCommand::parse("-stay", &["-".to_string()]),
+            Some(ChatCommand::Stay)
+        );
+    }
+
+    #[test]
+    fn test_ready_without_match() {
+        let mut ready = two_a_side();
+        let controller = MatchController::new();
+        assert_eq!(
+            say(&mut ready, &controller, "Alice CT", ".ready").commands,
+            vec!["say Alice is ready, CT 1/2, T 0/2"]
+        );
+        // Readying twice or from the stands changes nothing.
+        assert_eq!(
+            say(&mut ready, &controller, "Alice CT", ".ready"),
+            Outcome::default()
+        );
+        assert_eq!(
+            say(&mut ready, &controller, "Sam Spectator", ".ready"),
+            Outcome::default()
+        );
+        say(&mut ready, &controller, "Carol CT", ".ready");
+        say(&mut ready, &controller, "Bob TERRORIST", ".ready");
+        assert_eq!(
+            say(&mut ready, &controller, "Bob TERRORIST", ".unready").commands,
+            vec!["say Bob is not ready, CT 2/2, T 0/2"]
+        );
+        say(&mut ready, &controller, "Bob TERRORIST", ".ready");
+        ready.update(
+            &event(r#""Dave<2><[U:1:Dave]>" switched from team <Unassigned> to <TERRORIST>"#),
+            &controller,
+        );
+        assert_eq!(
+            say(&mut ready, &controller, "Dave TERRORIST", "!ready").commands,
+            vec![
+                "say Dave is ready, CT 2/2, T 2/2",
+                "say Everyone is ready",
+                "mp_warmup_end"
+            ]
+        );
+        assert_eq!(ready.counts(), (0, 0));
+    }
+
+    #[test]
+    fn test_ready_moves_match_on() {
+        let mut ready = ReadyUp::new(ReadyConfig {
+            required: 1,
+            announce: false,
+            ..ReadyConfig::default()
+        });
+        let mut controller = MatchController::new();
+        controller
+            .start("competitive", MatchType::defaults()["competitive"].clone())
+            .unwrap();
+        say(&mut ready, &controller, "Alice CT", ".ready");
+        // A player who leaves is no longer ready.
+        ready.update(
+            &event(r#""Alice<2><[U:1:Alice]><CT>" disconnected (reason "Disconnect")"#),
+            &controller,
+        );
+        say(&mut ready, &controller, "Bob TERRORIST", ".ready");
+        assert_eq!(ready.counts(), (0, 1));
+        let outcome = say(&mut ready, &controller, "Carol CT", ".ready");
+        assert_eq!(
+            outcome,
+            Outcome {
+                commands: vec![],
+                trigger: Some(Trigger::Ready),
+            }
+        );
+        let step = controller.trigger(Trigger::Ready).unwrap();
+        assert_eq!(step.to, Phase::Knife);
+
+        // Only the knife winner picks a side.
+        let line = parse_line(
+            r#"10/18/2026 - 21:31:00.000 - Team "TERRORIST" triggered "SFUI_Notice_Terrorists_Win" (CT "0") (T "1")"#,
+        )
+        .unwrap();
+        let step: Option<Step> = controller.update(&Action::Log(Box::new(line)));
+        assert_eq!(step.unwrap().to, Phase::SidePick);
+        assert_eq!(
+            say(&mut ready, &controller, "Carol CT", ".stay").trigger,
+            None
+        );
+        assert_eq!(
+            say(&mut ready, &controller, "Bob TERRORIST", ".switch").trigger,
+            Some(Trigger::Switch)
+        );
+    }
+
+    #[test]
+    fn test_roster() {
+        let mut ready = two_a_side();
+        let controller = MatchController::new();
+        ready.set_roster(BTreeSet::from(["[U:1:1001]".to_string()]));
+        let mut ready_up = |player: &str| {
+            ready
+                .update(
+                    &event(&format!(r#""{}<CT>" say ".ready""#, player)),
+                    &controller,
+                )
+                .commands
+        };
+        assert_eq!(
+            ready_up("Bob<3><[U:1:1002]>"),
+            vec!["say Bob is not on a team of this match"]
+        );
+        // The roster may use another form of the SteamID than the log.
+        assert_eq!(
+            ready_up("Alice<2><STEAM_1:1:500>"),
+            vec!["say Alice is ready, CT 1/2, T 0/2"]
+        );
+    }
+
+    #[test]
+    fn test_hostile_name() {
+        let mut ready = two_a_side();
+        let controller = MatchController::new();
+        let outcome = ready.update(
+            &event(r#""x;rcon_password pwned"<2><[U:1:66]><CT>" say ".ready""#),
+            &controller,
+        );
+        assert_eq!(
+            outcome.commands,
+            vec!["say x rcon_password pwned  is ready, CT 1/2, T 0/2"]
+        );
+        let outcome = ready.update(
+            &event("\"x\nquit<3><[U:1:67]><TERRORIST>\" say \".pause\""),
+            &controller,
+        );
+        assert_eq!(
+            outcome.commands,
+            vec!["say x quit paused the match", "mp_pause_match"]
+        );
+    }
+
+    #[test]
+    fn test_unpause_needs_both_teams() {
+        let mut ready = two_a_side();
+        let controller = MatchController::new();
+        assert_eq!(
+            say(&mut ready, &controller, "Alice CT", ".unpause"),
+            Outcome::default()
+        );
+        assert_eq!(
+            say(&mut ready, &controller, "Bob TERRORIST", ".pause").commands,
+            vec!["say Bob paused the match", "mp_pause_match"]
+        );
+        assert!(ready.paused());
+        assert_eq!(
+            say(&mut ready, &controller, "Bob TERRORIST", ".unpause").commands,
+            vec!["say T wants to unpause, waiting for CT to .unpause"]
+        );
+        assert_eq!(
+            say(&mut ready, &controller, "Dave TERRORIST", ".unpause"),
+            Outcome::default()
+        );
+        assert_eq!(
+            say(&mut ready, &controller, "Alice CT", ".unpause").commands,
+            vec!["say Unpausing", "mp_unpause_match"]
+        );
+        assert!(!ready.paused());
+    }
+}
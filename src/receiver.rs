@@ -0,0 +1,207 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::Action,
+    command::logs::{self, LogEvent, LogLine},
+};
+
+use self::{http::HttpReceiver, udp::UdpReceiver};
+
+pub mod http;
+pub mod udp;
+
+/// The log listeners, started when the first session needs them and shared by all.
+#[derive(Default)]
+pub struct Receivers {
+    pub http: Option<HttpReceiver>,
+    pub udp: Option<UdpReceiver>,
+}
+
+impl Receivers {
+    pub async fn http(&mut self, listen: &str) -> Result<&HttpReceiver, String> {
+        match &mut self.http {
+            Some(receiver) => Ok(receiver),
+            http => Ok(http.insert(
+                HttpReceiver::bind(listen)
+                    .await
+                    .map_err(|e| format!("Failed to listen for logs on {}: {}", listen, e))?,
+            )),
+        }
+    }
+
+    pub async fn udp(&mut self, listen: &str) -> Result<&UdpReceiver, String> {
+        match &mut self.udp {
+            Some(receiver) => Ok(receiver),
+            udp => Ok(udp.insert(
+                UdpReceiver::bind(listen)
+                    .await
+                    .map_err(|e| format!("Failed to listen for logs on {}: {}", listen, e))?,
+            )),
+        }
+    }
+}
+
+/// Lines kept of one match. Only the events the match is rebuilt from are kept, which
+/// leaves a long overtime far below, it bounds a server that never starts a match.
+const MAX_BACKLOG: usize = 100_000;
+
+/// The lines of the current match, from its `Match_Start` on.
+#[derive(Debug, Default)]
+struct Backlog {
+    /// Lines with their number among the ones received.
+    lines: VecDeque<(usize, LogLine)>,
+    /// Lines received in all, including the ones not kept.
+    received: usize,
+}
+
+impl Backlog {
+    fn push(&mut self, line: LogLine) {
+        if matches!(line.event, LogEvent::MatchStart { .. }) {
+            self.restart();
+        }
+        self.received += 1;
+        if !replayed(&line.event) {
+            return;
+        }
+        if self.lines.len() == MAX_BACKLOG {
+            self.lines.pop_front();
+        }
+        self.lines.push_back((self.received, line));
+    }
+
+    /// Drop the lines of the last match, except the latest team names and convar values
+    /// as the server does not repeat them.
+    fn restart(&mut self) {
+        let mut seen = HashSet::new();
+        let mut kept = VecDeque::new();
+        for (number, line) in self.lines.drain(..).rev() {
+            let key = match &line.event {
+                LogEvent::TeamPlaying { team, .. } => format!("team {}", team),
+                LogEvent::Cvar { name, .. } => format!("cvar {}", name),
+                _ => continue,
+            };
+            if seen.insert(key) {
+                kept.push_front((number, line));
+            }
+        }
+        self.lines = kept;
+    }
+}
+
+/// The events the scoreboard and kill feed are rebuilt from, purchases, chat and the
+/// like are left out of the backlog.
+fn replayed(event: &LogEvent) -> bool {
+    matches!(
+        event,
+        LogEvent::SwitchedTeam { .. }
+            | LogEvent::Killed { .. }
+            | LogEvent::Attacked { .. }
+            | LogEvent::Suicide { .. }
+            | LogEvent::Assist { .. }
+            | LogEvent::MatchStart { .. }
+            | LogEvent::RoundStart
+            | LogEvent::RoundWon { .. }
+            | LogEvent::MatchStatus { .. }
+            | LogEvent::TeamPlaying { .. }
+            | LogEvent::GameOver { .. }
+            | LogEvent::Cvar { .. }
+    )
+}
+
+/// Where the log lines of one server go: the channel of its session, and the backlog
+/// anything built from them is rebuilt from, like after switching tabs or reconnecting.
+#[derive(Debug, Clone)]
+pub struct Feed {
+    tx: UnboundedSender<Action>,
+    backlog: Arc<Mutex<Backlog>>,
+}
+
+impl Feed {
+    pub fn new(tx: UnboundedSender<Action>) -> Self {
+        Self {
+            tx,
+            backlog: Arc::default(),
+        }
+    }
+
+    /// Parse the log lines in `text`, keep them and send each as an action, returns how
+    /// many were sent.
+    pub fn publish(&self, text: &str) -> usize {
+        // Held while sending, so the backlog is in the order the lines are sent in.
+        let Ok(mut backlog) = self.backlog.lock() else {
+            return 0;
+        };
+        let mut sent = 0;
+        for line in text.lines().filter_map(logs::parse_line) {
+            log::debug!("Log event: {:?}", line.event);
+            backlog.push(line.clone());
+            if self.tx.send(Action::Log(Box::new(line))).is_ok() {
+                sent += 1;
+            }
+        }
+        sent
+    }
+
+    /// The kept lines among the first `count` received, the ones after are still on
+    /// their way to the session.
+    pub fn backlog(&self, count: usize) -> Vec<LogLine> {
+        let Ok(backlog) = self.backlog.lock() else {
+            return Vec::new();
+        };
+        backlog
+            .lines
+            .iter()
+            .take_while(|(number, _)| *number <= count)
+            .map(|(_, line)| line.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn test_backlog() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let feed = Feed::new(tx);
+        let text = [
+            r#"10/18/2026 - 21:29:00.000 - server cvar "mp_maxrounds" = "30""#,
+            r#"10/18/2026 - 21:29:01.000 - server cvar "mp_maxrounds" = "24""#,
+            r#"10/18/2026 - 21:29:02.000 - MatchStatus: Team playing "CT": Team Alpha"#,
+            r#"10/18/2026 - 21:29:03.000 - World triggered "Round_Start""#,
+            r#"10/18/2026 - 21:30:00.000 - World triggered "Match_Start" on "de_nuke""#,
+            r#"10/18/2026 - 21:30:01.000 - World triggered "Round_Start""#,
+            r#"10/18/2026 - 21:30:02.000 - "Bob<3><[U:1:1002]><TERRORIST>" purchased "ak47""#,
+            r#"10/18/2026 - 21:30:03.000 - "Bob<3><[U:1:1002]><TERRORIST>" say "gl hf""#,
+            r#"10/18/2026 - 21:30:04.000 - World triggered "Round_Start""#,
+        ]
+        .join("\n");
+        assert_eq!(feed.publish(&text), 9);
+        let times = |lines: Vec<LogLine>| lines.into_iter().map(|l| l.time).collect::<Vec<_>>();
+        // Warmup is dropped, the setup before the match is kept.
+        assert_eq!(
+            times(feed.backlog(6)),
+            vec!["21:29:01", "21:29:02", "21:30:00", "21:30:01"]
+        );
+        // Lines the session has not seen yet are left out.
+        assert_eq!(
+            times(feed.backlog(5)),
+            vec!["21:29:01", "21:29:02", "21:30:00"]
+        );
+        // Purchases and chat are not kept.
+        assert_eq!(
+            times(feed.backlog(9)),
+            vec!["21:29:01", "21:29:02", "21:30:00", "21:30:01", "21:30:04"]
+        );
+        assert!(matches!(rx.try_recv(), Ok(Action::Log(_))));
+    }
+}
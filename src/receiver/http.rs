@@ -0,0 +1,319 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::UnboundedSender,
+    task::JoinHandle,
+};
+
+use super::Feed;
+
+/// Bodies bigger than this are refused, the server sends a few kilobytes at a time.
+const MAX_BODY: usize = 1 << 20;
+
+/// Request lines and headers together bigger than this are refused, the server sends a
+/// handful of short ones.
+const MAX_HEAD: usize = 8 << 10;
+
+type Routes = Arc<Mutex<HashMap<String, Feed>>>;
+
+/// Listens for the log lines CS2 posts to the urls added with `logaddress_add_http`.
+///
+/// Every session gets a path of its own, so one listener serves all tabs. Paths hold a
+/// [`token`], as anyone who can reach the port could post log lines otherwise.
+pub struct HttpReceiver {
+    local_addr: SocketAddr,
+    routes: Routes,
+    task: JoinHandle<()>,
+}
+
+impl HttpReceiver {
+    pub async fn bind(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let routes = Routes::default();
+        let task = tokio::spawn(accept(listener, routes.clone()));
+        log::info!("Receiving logs over http on {}", local_addr);
+        Ok(Self {
+            local_addr,
+            routes,
+            task,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Publish the log lines posted to `path` on `feed`.
+    pub fn route(&self, path: &str, feed: Feed) {
+        if let Ok(mut routes) = self.routes.lock() {
+            routes.insert(path.to_string(), feed);
+        }
+    }
+
+    pub fn unroute(&self, path: &str) {
+        if let Ok(mut routes) = self.routes.lock() {
+            routes.remove(path);
+        }
+    }
+}
+
+impl Drop for HttpReceiver {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn accept(listener: TcpListener, routes: Routes) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let routes = routes.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve(stream, routes).await {
+                        log::warn!("Log connection from {} failed: {}", peer, e);
+                    }
+                });
+            }
+            Err(e) => log::error!("Failed to accept log connection: {}", e),
+        }
+    }
+}
+
+/// A random path segment nobody can guess.
+pub fn token() -> String {
+    let mut bytes = [0u8; 16];
+    if let Err(e) = getrandom::getrandom(&mut bytes) {
+        log::error!("Failed to generate a log token: {}", e);
+    }
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// What a request starts with, before the body.
+enum Head {
+    Request {
+        method: String,
+        path: String,
+        content_length: usize,
+        close: bool,
+    },
+    Closed,
+    TooLarge,
+}
+
+/// Read the request line and headers, refusing to read more than `MAX_HEAD` bytes of them.
+async fn read_head(stream: &mut BufReader<TcpStream>) -> io::Result<Head> {
+    let mut left = MAX_HEAD;
+    let mut lines = Vec::new();
+    loop {
+        let mut line = String::new();
+        let read = (&mut *stream)
+            .take(left as u64)
+            .read_line(&mut line)
+            .await?;
+        if read == 0 && left > 0 {
+            return Ok(Head::Closed);
+        }
+        if read == left && !line.ends_with('\n') {
+            return Ok(Head::TooLarge);
+        }
+        left -= read;
+        let line = line.trim_end().to_string();
+        match (line.is_empty(), lines.is_empty()) {
+            // Blank lines before a request are to be ignored.
+            (true, true) => continue,
+            (true, false) => break,
+            (false, _) => lines.push(line),
+        }
+    }
+    let mut parts = lines[0].split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let mut content_length = 0;
+    let mut close = false;
+    for header in &lines[1..] {
+        if let Some((name, value)) = header.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                "connection" => close = value.trim().eq_ignore_ascii_case("close"),
+                _ => {}
+            }
+        }
+    }
+    Ok(Head::Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        content_length,
+        close,
+    })
+}
+
+/// Answer requests on one connection until the server closes it.
+async fn serve(stream: TcpStream, routes: Routes) -> io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    loop {
+        let (method, path, content_length, close) = match read_head(&mut stream).await? {
+            Head::Request {
+                method,
+                path,
+                content_length,
+                close,
+            } => (method, path, content_length, close),
+            Head::Closed => return Ok(()),
+            Head::TooLarge => {
+                respond(stream.get_mut(), "431 Request Header Fields Too Large").await?;
+                return Ok(());
+            }
+        };
+        if content_length > MAX_BODY {
+            respond(stream.get_mut(), "413 Payload Too Large").await?;
+            return Ok(());
+        }
+        let mut body = vec![0; content_length];
+        stream.read_exact(&mut body).await?;
+
+        let feed = routes
+            .lock()
+            .ok()
+            .and_then(|routes| routes.get(&path).cloned());
+        let status = match (method.as_str(), feed) {
+            ("POST", Some(feed)) => {
+                feed.publish(&String::from_utf8_lossy(&body));
+                "200 OK"
+            }
+            (_, Some(_)) => "405 Method Not Allowed",
+            (_, None) => "404 Not Found",
+        };
+        respond(stream.get_mut(), status).await?;
+        if close {
+            return Ok(());
+        }
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str) -> io::Result<()> {
+    stream
+        .write_all(format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).as_bytes())
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{action::Action, command::logs::LogEvent};
+
+    const FIXTURE: &str = include_str!("../../fixtures/logs/cs2_http.log");
+
+    async fn post(addr: SocketAddr, path: &str, body: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            path,
+            addr,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_posted_lines_are_published() {
+        let receiver = HttpReceiver::bind("127.0.0.1:0").await.unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        receiver.route("/1", Feed::new(tx));
+
+        let response = post(receiver.local_addr(), "/1", FIXTURE).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        let mut events = Vec::new();
+        while let Ok(Action::Log(line)) = rx.try_recv() {
+            events.push(line);
+        }
+        assert_eq!(events.len(), FIXTURE.lines().count());
+        assert_eq!(events[0].time, "20:15:01");
+        assert!(events.iter().any(|line| matches!(
+            &line.event,
+            LogEvent::Say { message, .. } if message == "gl hf"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_path() {
+        let receiver = HttpReceiver::bind("127.0.0.1:0").await.unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        receiver.route("/1", Feed::new(tx));
+        let response = post(receiver.local_addr(), "/2", FIXTURE).await;
+        assert!(response.starts_with("HTTP/1.1 404"));
+        receiver.unroute("/1");
+        let response = post(receiver.local_addr(), "/1", FIXTURE).await;
+        assert!(response.starts_with("HTTP/1.1 404"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive() {
+        let receiver = HttpReceiver::bind("127.0.0.1:0").await.unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        receiver.route("/logs", Feed::new(tx));
+        let mut stream = TcpStream::connect(receiver.local_addr()).await.unwrap();
+        let line = "10/18/2026 - 20:15:01.000 - World triggered \"Round_Start\"\n";
+        let request = format!(
+            "POST /logs HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            line.len(),
+            line
+        );
+        stream
+            .write_all(format!("{}{}", request, request).as_bytes())
+            .await
+            .unwrap();
+        let mut stream = BufReader::new(stream);
+        for _ in 0..2 {
+            let mut status = String::new();
+            stream.read_line(&mut status).await.unwrap();
+            assert_eq!(status, "HTTP/1.1 200 OK\r\n");
+            let mut rest = String::new();
+            while rest != "\r\n" {
+                rest.clear();
+                stream.read_line(&mut rest).await.unwrap();
+            }
+        }
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_head_too_large() {
+        let receiver = HttpReceiver::bind("127.0.0.1:0").await.unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        receiver.route("/logs", Feed::new(tx));
+        let mut stream = TcpStream::connect(receiver.local_addr()).await.unwrap();
+        // Exactly as much as is read, a server closing on unread data resets the connection.
+        let mut request = "POST /logs HTTP/1.1\r\nX-Padding: ".to_string();
+        request.push_str(&"a".repeat(MAX_HEAD - request.len()));
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 431"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_token() {
+        let token = token();
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, super::token());
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{net::UdpSocket, task::JoinHandle};
+
+use super::Feed;
+
+/// Every log packet starts with this, followed by `R` or by `S` and the `sv_logsecret`.
+const HEADER: &[u8] = &[0xff, 0xff, 0xff, 0xff];
+
+struct Route {
+    feed: Feed,
+    secret: Option<String>,
+}
+
+type Routes = Arc<Mutex<HashMap<SocketAddr, Route>>>;
+
+/// Listens for the log packets Source servers send to addresses added with `logaddress_add`.
+///
+/// Packets are told apart by the address of the server that sent them.
+pub struct UdpReceiver {
+    local_addr: SocketAddr,
+    routes: Routes,
+    task: JoinHandle<()>,
+}
+
+impl UdpReceiver {
+    pub async fn bind(addr: &str) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr).await?;
+        let local_addr = socket.local_addr()?;
+        let routes = Routes::default();
+        let task = tokio::spawn(receive(socket, routes.clone()));
+        log::info!("Receiving logs over udp on {}", local_addr);
+        Ok(Self {
+            local_addr,
+            routes,
+            task,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Publish the log lines `server` sends on `feed`, dropping packets without `secret` if set.
+    pub fn route(&self, server: SocketAddr, feed: Feed, secret: Option<String>) {
+        if let Ok(mut routes) = self.routes.lock() {
+            routes.insert(server, Route { feed, secret });
+        }
+    }
+
+    pub fn unroute(&self, server: SocketAddr) {
+        if let Ok(mut routes) = self.routes.lock() {
+            routes.remove(&server);
+        }
+    }
+}
+
+impl Drop for UdpReceiver {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The log text of a packet, `None` if it is not a log packet or the secret does not match.
+fn payload<'a>(packet: &'a [u8], secret: Option<&str>) -> Option<&'a [u8]> {
+    let packet = packet.strip_prefix(HEADER)?;
+    let text = match (packet.first()?, secret) {
+        (b'R', None) => &packet[1..],
+        (b'S', Some(secret)) => packet[1..].strip_prefix(secret.as_bytes())?,
+        _ => return None,
+    };
+    // A longer secret starting with ours must not pass either.
+    if !text.starts_with(b"L ") {
+        return None;
+    }
+    // Lines end with a newline and a nul byte.
+    let end = text.iter().position(|b| *b == 0).unwrap_or(text.len());
+    Some(&text[..end])
+}
+
+async fn receive(socket: UdpSocket, routes: Routes) {
+    let mut buffer = vec![0; 65536];
+    loop {
+        let (len, from) = match socket.recv_from(&mut buffer).await {
+            Ok(received) => received,
+            Err(e) => {
+                log::error!("Failed to receive log packet: {}", e);
+                continue;
+            }
+        };
+        let Ok(routes) = routes.lock() else {
+            return;
+        };
+        // Servers normally send from their game port, fall back to the address alone.
+        let route = routes.get(&from).or_else(|| {
+            routes
+                .iter()
+                .find(|(server, _)| server.ip() == from.ip())
+                .map(|(_, route)| route)
+        });
+        match route {
+            Some(route) => match payload(&buffer[..len], route.secret.as_deref()) {
+                Some(text) => {
+                    route.feed.publish(&String::from_utf8_lossy(text));
+                }
+                None => log::warn!(
+                    "Dropped log packet from {} with a bad header or secret",
+                    from
+                ),
+            },
+            None => log::debug!("Dropped log packet from unknown server {}", from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::{
+        sync::mpsc,
+        time::{sleep, Duration},
+    };
+
+    use super::*;
+    use crate::{action::Action, command::logs::LogEvent};
+
+    const FIXTURE: &str = include_str!("../../fixtures/logs/classic_udp.log");
+
+    fn packet(kind: &str, line: &str) -> Vec<u8> {
+        [HEADER, kind.as_bytes(), line.as_bytes(), b"\n\0"].concat()
+    }
+
+    #[test]
+    fn test_payload() {
+        let line = "L 10/18/2026 - 20:15:01: Log file started";
+        assert_eq!(
+            payload(&packet("R", line), None),
+            Some(format!("{}\n", line).as_bytes())
+        );
+        assert_eq!(
+            payload(&packet("S12345", line), Some("12345")),
+            Some(format!("{}\n", line).as_bytes())
+        );
+        assert_eq!(payload(&packet("S999", line), Some("12345")), None);
+        assert_eq!(payload(&packet("S123456", line), Some("12345")), None);
+        assert_eq!(payload(&packet("R", line), Some("12345")), None);
+        assert_eq!(payload(&packet("S12345", line), None), None);
+        assert_eq!(payload(line.as_bytes(), None), None);
+    }
+
+    #[tokio::test]
+    async fn test_packets_are_published() {
+        let receiver = UdpReceiver::bind("127.0.0.1:0").await.unwrap();
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let stranger = UdpSocket::bind("127.0.0.2:0").await.unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        receiver.route(
+            server.local_addr().unwrap(),
+            Feed::new(tx),
+            Some("4242".to_string()),
+        );
+
+        for line in FIXTURE.lines() {
+            server
+                .send_to(&packet("S4242", line), receiver.local_addr())
+                .await
+                .unwrap();
+        }
+        let line = FIXTURE.lines().next().unwrap();
+        server
+            .send_to(&packet("S1", line), receiver.local_addr())
+            .await
+            .unwrap();
+        stranger
+            .send_to(&packet("S4242", line), receiver.local_addr())
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(100)).await;
+
+        let mut events = Vec::new();
+        while let Ok(Action::Log(line)) = rx.try_recv() {
+            events.push(line);
+        }
+        assert_eq!(events.len(), FIXTURE.lines().count());
+        assert!(events.iter().any(|line| matches!(
+            &line.event,
+            LogEvent::Say { message, team_only: false, .. } if message == "gg"
+        )));
+    }
+}
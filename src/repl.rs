@@ -0,0 +1,363 @@
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    sync::Arc,
+    time::Duration,
+};
+
+use rustyline::{
+    completion::Completer, error::ReadlineError, highlight::Highlighter, hint::Hinter,
+    history::DefaultHistory, validate::Validator, Context, Editor, Helper,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    cli::ServerArgs,
+    client::Client,
+    command::{autocompleter::AutoCompleter, builtin::BUILTINS, cfg},
+    config::Config,
+    headless::{self, Exit, Response},
+};
+
+/// Completes command names from the catalog.
+struct CatalogHelper {
+    catalog: Arc<AutoCompleter>,
+}
+
+impl Completer for CatalogHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        // Only the command name is completed, not its arguments.
+        let start = line[..pos].rfind(';').map(|i| i + 1).unwrap_or(0);
+        let word = line[start..pos].trim_start();
+        if word.contains(' ') {
+            return Ok((pos, Vec::new()));
+        }
+        Ok((pos - word.len(), self.catalog.get_suggestions(word, 100)))
+    }
+}
+
+impl Hinter for CatalogHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CatalogHelper {}
+
+impl Validator for CatalogHelper {}
+
+impl Helper for CatalogHelper {}
+
+fn rows(rows: impl IntoIterator<Item = (String, String)>) -> String {
+    rows.into_iter()
+        .map(|(name, description)| format!("{:<32} {}\n", name, description))
+        .collect()
+}
+
+/// A line-oriented session on a single server, without the terminal UI.
+pub struct Repl {
+    client: Client,
+    catalog: Arc<AutoCompleter>,
+    /// How long to wait for a response.
+    timeout: Duration,
+    /// Print every response as a line of json.
+    json: bool,
+    failed: bool,
+    timed_out: bool,
+}
+
+impl Repl {
+    pub fn new(client: Client, timeout: Duration, json: bool) -> Self {
+        let mut catalog = AutoCompleter::new();
+        catalog.load_catalog();
+        Self {
+            client,
+            catalog: Arc::new(catalog),
+            timeout,
+            json,
+            failed: false,
+            timed_out: false,
+        }
+    }
+
+    fn exit(&self) -> Exit {
+        if self.timed_out {
+            Exit::TimedOut
+        } else if self.failed {
+            Exit::CommandFailed
+        } else {
+            Exit::Ok
+        }
+    }
+
+    /// Run every command on a line, returns false once the session should end.
+    pub async fn line(&mut self, line: &str) -> bool {
+        for command in cfg::commands(line) {
+            let mut words = command.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("quit" | "exit"), _) => return false,
+                (Some("help"), _) => {
+                    let mut help = BUILTINS
+                        .iter()
+                        .map(|b| (b.usage.to_string(), b.description.to_string()))
+                        .collect::<Vec<_>>();
+                    help.push(("quit".to_string(), "End the session".to_string()));
+                    if !self.output(&command, Ok(rows(help))) {
+                        return false;
+                    }
+                }
+                (Some("apropos"), _) => {
+                    let query = command.trim_start_matches("apropos").trim();
+                    let found = self
+                        .catalog
+                        .search(query)
+                        .into_iter()
+                        .map(|c| (c.name, c.description));
+                    if !self.output(&command, Ok(rows(found))) {
+                        return false;
+                    }
+                }
+                (Some("exec"), Some(file)) => match std::fs::read_to_string(cfg::path(file)) {
+                    Ok(contents) => {
+                        for command in cfg::commands(&contents) {
+                            if !self.send(&command).await {
+                                return false;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        self.failed = true;
+                        let error = format!("{}: {}", cfg::path(file).display(), e);
+                        if !self.output(&command, Err(error)) {
+                            return false;
+                        }
+                    }
+                },
+                _ => {
+                    if !self.send(&command).await {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Print what the client itself answered to `command`.
+    fn output(&self, command: &str, result: Result<String, String>) -> bool {
+        let (response, error) = match result {
+            Ok(text) => (Some(text), None),
+            Err(error) => (None, Some(error)),
+        };
+        self.print(&Response {
+            command: command.to_string(),
+            response,
+            error,
+            timed_out: false,
+        })
+    }
+
+    /// Send `command`, ending the session after a timeout as a late response would be taken
+    /// for the response to the next command.
+    async fn send(&mut self, command: &str) -> bool {
+        let response = headless::send(&mut self.client, command, self.timeout).await;
+        self.failed |= !response.ok();
+        self.timed_out |= response.timed_out;
+        if !self.print(&response) {
+            return false;
+        }
+        if response.timed_out {
+            eprintln!("ending the session, the server stopped answering");
+            return false;
+        }
+        true
+    }
+
+    /// Print a response, returns false once nobody is reading the output any more, e.g.
+    /// `| head`.
+    fn print(&self, response: &Response) -> bool {
+        if !self.json {
+            return response.print().is_ok();
+        }
+        let Ok(json) = serde_json::to_string(response) else {
+            return false;
+        };
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", json)
+            .and_then(|_| stdout.flush())
+            .is_ok()
+    }
+
+    /// Read commands from a pipe until it closes. Lines are read on a thread of their own,
+    /// blocking reads would hold up the runtime.
+    async fn piped(&mut self) -> Exit {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::task::spawn_blocking(move || {
+            for line in io::stdin().lock().lines() {
+                let failed = line.is_err();
+                if tx.send(line).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        while let Some(line) = rx.recv().await {
+            match line {
+                Ok(line) => {
+                    if !self.line(&line).await {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return Exit::Usage;
+                }
+            }
+        }
+        self.exit()
+    }
+
+    /// Read commands from the terminal with line editing, history and completion.
+    async fn interactive(&mut self, prompt: &str) -> Exit {
+        let mut editor = match Editor::<CatalogHelper, DefaultHistory>::new() {
+            Ok(editor) => editor,
+            Err(e) => {
+                eprintln!("{}", e);
+                return Exit::Usage;
+            }
+        };
+        editor.set_helper(Some(CatalogHelper {
+            catalog: self.catalog.clone(),
+        }));
+        loop {
+            // The editor goes to a blocking thread for each line and comes back with it.
+            let prompt = prompt.to_string();
+            let read = tokio::task::spawn_blocking(move || {
+                let line = editor.readline(&prompt);
+                (editor, line)
+            })
+            .await;
+            let line;
+            (editor, line) = match read {
+                Ok(read) => read,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return Exit::Usage;
+                }
+            };
+            if let Ok(line) = &line {
+                if !line.trim().is_empty() {
+                    let _ = editor.add_history_entry(line.as_str());
+                }
+            }
+            if let Some(exit) = self.edited(line).await {
+                return exit;
+            }
+        }
+    }
+
+    /// Handle what the editor read, returns how to exit once the session should end.
+    async fn edited(&mut self, line: rustyline::Result<String>) -> Option<Exit> {
+        match line {
+            Ok(line) => match self.line(&line).await {
+                true => None,
+                false => Some(self.exit()),
+            },
+            Err(ReadlineError::Interrupted) => None,
+            Err(ReadlineError::Eof) => Some(self.exit()),
+            Err(e) => {
+                eprintln!("{}", e);
+                Some(Exit::Usage)
+            }
+        }
+    }
+}
+
+pub async fn run(server: &ServerArgs, config: &Config) -> Exit {
+    let profile = match headless::resolve(server, config) {
+        Ok(profile) => profile,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Exit::Usage;
+        }
+    };
+    let client = match headless::connect(&profile, server.timeout()).await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("{}", e);
+            return e.exit();
+        }
+    };
+    let mut repl = Repl::new(client, server.timeout(), server.json);
+    match std::io::stdin().is_terminal() {
+        true => repl.interactive(&format!("{}> ", profile.address)).await,
+        false => repl.piped().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{client::silent_server, config::Profile};
+
+    #[tokio::test]
+    async fn test_quit_ends_session() {
+        let mut repl = Repl::new(Client::default(), Duration::from_secs(1), false);
+        assert!(repl.line("// just a comment").await);
+        assert!(!repl.line("help; quit; status").await);
+        assert!(!repl.failed);
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let response = Response {
+            command: "help".to_string(),
+            response: Some(rows([("quit".to_string(), "End the session".to_string())])),
+            error: None,
+            timed_out: false,
+        };
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            format!(
+                r#"{{"command":"help","response":"quit{}End the session\n","error":null}}"#,
+                " ".repeat(29)
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_remembered() {
+        let mut repl = Repl::new(Client::default(), Duration::from_secs(1), false);
+        assert!(repl.line("exec does_not_exist").await);
+        assert!(repl.failed);
+        // An interactive session exits like a piped one.
+        assert_eq!(repl.edited(Ok("help".to_string())).await, None);
+        assert_eq!(
+            repl.edited(Err(ReadlineError::Eof)).await,
+            Some(Exit::CommandFailed)
+        );
+        assert_eq!(
+            repl.edited(Ok("quit".to_string())).await,
+            Some(Exit::CommandFailed)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout_ends_session() {
+        let profile = Profile {
+            address: silent_server().await,
+            password: "secret".to_string(),
+            ..Default::default()
+        };
+        let timeout = Duration::from_millis(200);
+        let client = headless::connect(&profile, timeout).await.unwrap();
+        let mut repl = Repl::new(client, timeout, false);
+        assert!(!repl.line("status; say hi").await);
+        assert_eq!(repl.exit(), Exit::TimedOut);
+    }
+}
@@ -0,0 +1,306 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::Result;
+use serde::Serialize;
+
+use crate::{
+    command::status::Status,
+    stats::{MatchStats, PlayerStats},
+};
+
+/// How a round ended, as written to the report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoundReport {
+    pub round: usize,
+    /// `CT` or `T`.
+    pub winner: String,
+    /// Name of the team on the winning side.
+    pub team: String,
+    pub reason: String,
+    pub ct_score: u32,
+    pub t_score: u32,
+}
+
+/// What a player did in the match, as written to the report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlayerReport {
+    pub name: String,
+    pub steamid: String,
+    /// `CT` or `T` at the end of the match.
+    pub side: String,
+    pub team: String,
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+    pub damage: u32,
+    pub adr: u32,
+    pub headshots: u32,
+    pub headshot_percentage: u32,
+    /// From the last `status`, empty if the player was not in it.
+    pub ping: Option<u16>,
+    pub connected: Option<String>,
+}
+
+/// The teams, map, score, rounds and player stats of a match, written after the game or
+/// with `report`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    /// When the report was made, in RFC 3339.
+    pub created: String,
+    pub server: String,
+    pub map: String,
+    pub ct_team: String,
+    pub t_team: String,
+    pub ct_score: u32,
+    pub t_score: u32,
+    pub finished: bool,
+    pub rounds: Vec<RoundReport>,
+    pub players: Vec<PlayerReport>,
+}
+
+impl Report {
+    /// The report of the match in `stats`, with what the server last said in `status`.
+    pub fn new(
+        stats: &MatchStats,
+        status: &Status,
+        created: chrono::DateTime<chrono::Local>,
+    ) -> Self {
+        let team = |side: &str| match side {
+            "CT" => stats.teams.0.clone(),
+            "T" => stats.teams.1.clone(),
+            _ => String::new(),
+        };
+        let rounds = stats
+            .rounds
+            .iter()
+            .enumerate()
+            .map(|(i, round)| RoundReport {
+                round: i + 1,
+                winner: round.winner.clone(),
+                team: round.team.clone(),
+                reason: round.reason.name().to_string(),
+                ct_score: round.score.0,
+                t_score: round.score.1,
+            })
+            .collect();
+        let players = ["CT", "T"]
+            .into_iter()
+            .flat_map(|side| stats.players(side))
+            .map(|player| Self::player(player, team(&player.side), stats.rounds.len(), status))
+            .collect();
+        Self {
+            created: created.to_rfc3339(),
+            server: status.servername.clone(),
+            map: match stats.map.as_str() {
+                "" => status.map.clone(),
+                map => map.to_string(),
+            },
+            ct_team: stats.teams.0.clone(),
+            t_team: stats.teams.1.clone(),
+            ct_score: stats.score.0,
+            t_score: stats.score.1,
+            finished: stats.game_over,
+            rounds,
+            players,
+        }
+    }
+
+    fn player(player: &PlayerStats, team: String, rounds: usize, status: &Status) -> PlayerReport {
+        // `status` quotes the names.
+        let seen = status
+            .players
+            .iter()
+            .find(|p| p.name.trim_matches('\'') == player.name);
+        PlayerReport {
+            name: player.name.clone(),
+            steamid: player.steamid.clone(),
+            side: player.side.clone(),
+            team,
+            kills: player.kills,
+            deaths: player.deaths,
+            assists: player.assists,
+            damage: player.damage,
+            adr: player.adr(rounds),
+            headshots: player.headshots,
+            headshot_percentage: player.headshot_percentage(),
+            ping: seen.map(|p| p.ping),
+            connected: seen.map(|p| p.time.clone()),
+        }
+    }
+
+    /// File names start with when the report was made and the map, like
+    /// `20261018-213350-de_nuke`.
+    pub fn stem(&self) -> String {
+        let created = chrono::DateTime::parse_from_rfc3339(&self.created)
+            .map(|t| t.format("%Y%m%d-%H%M%S").to_string())
+            .unwrap_or_default();
+        match self.map.as_str() {
+            "" => created,
+            map => format!("{}-{}", created, map),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// One row per player.
+    pub fn players_csv(&self) -> Result<String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for player in &self.players {
+            writer.serialize(player)?;
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
+    /// One row per round.
+    pub fn rounds_csv(&self) -> Result<String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for round in &self.rounds {
+            writer.serialize(round)?;
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
+    /// Write the report to `dir` as `<stem>.json`, `<stem>-players.csv` and
+    /// `<stem>-rounds.csv`, returns the files written.
+    pub fn write(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir)?;
+        let stem = self.stem();
+        let files = [
+            (format!("{}.json", stem), self.to_json()?),
+            (format!("{}-players.csv", stem), self.players_csv()?),
+            (format!("{}-rounds.csv", stem), self.rounds_csv()?),
+        ];
+        let mut written = Vec::new();
+        for (name, contents) in files {
+            let path = dir.join(name);
+            fs::write(&path, contents)?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::command::{logs::parse_line, status::Player};
+
+    const FIXTURE: &str = include_str!("../fixtures/logs/cs2_match.log");
+
+    fn report() -> Report {
+        let lines = FIXTURE.lines().filter_map(parse_line).collect::<Vec<_>>();
+        let status = Status {
+            servername: "Cup #1".to_string(),
+            map: "de_nuke".to_string(),
+            players: vec![Player {
+                name: "'Alice'".to_string(),
+                ping: 12,
+                time: "04:21".to_string(),
+                ..Player::default()
+            }],
+        };
+        let created = chrono::Local
+            .with_ymd_and_hms(2026, 10, 18, 21, 33, 50)
+            .unwrap();
+        Report::new(&MatchStats::from_lines(&lines), &status, created)
+    }
+
+    #[test]
+    fn test_report() {
+        let report = report();
+        assert_eq!(report.stem(), "20261018-213350-de_nuke");
+        assert_eq!(report.server, "Cup #1");
+        assert_eq!((report.ct_score, report.t_score), (2, 1));
+        assert!(report.finished);
+        assert_eq!(
+            report.rounds[1],
+            RoundReport {
+                round: 2,
+                winner: "T".to_string(),
+                team: "Team Bravo".to_string(),
+                reason: "bomb".to_string(),
+                ct_score: 1,
+                t_score: 1,
+            }
+        );
+        assert_eq!(
+            report
+                .players
+                .iter()
+                .map(|p| (p.name.as_str(), p.team.as_str(), p.kills, p.adr, p.ping))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Alice", "Team Alpha", 4, 113, Some(12)),
+                ("Carol", "Team Alpha", 2, 33, None),
+                ("Dave", "Team Bravo", 3, 66, None),
+                ("Bob", "Team Bravo", 1, 33, None),
+            ]
+        );
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["ct_team"], "Team Alpha");
+        assert_eq!(json["players"][0]["connected"], "04:21");
+    }
+
+    #[test]
+    fn test_halftime() {
+        let lines = include_str!("../fixtures/logs/cs2_halftime.log")
+            .lines()
+            .filter_map(parse_line)
+            .collect::<Vec<_>>();
+        let report = Report::new(
+            &MatchStats::from_lines(&lines),
+            &Status::default(),
+            chrono::Local::now(),
+        );
+        assert_eq!(report.ct_team, "Team Bravo");
+        // Team Alpha won both rounds, one on each side.
+        assert_eq!(
+            report
+                .rounds
+                .iter()
+                .map(|r| (r.winner.as_str(), r.team.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("CT", "Team Alpha"), ("T", "Team Alpha")]
+        );
+        assert_eq!(report.players[0].name, "Bob");
+        assert_eq!(report.players[0].team, "Team Bravo");
+    }
+
+    #[test]
+    fn test_csv() -> Result<()> {
+        let report = report();
+        let players = report.players_csv()?;
+        let mut lines = players.lines();
+        assert_eq!(
+            lines.next(),
+            Some("name,steamid,side,team,kills,deaths,assists,damage,adr,headshots,headshot_percentage,ping,connected")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("Alice,[U:1:1001],CT,Team Alpha,4,2,0,340,113,2,50,12,04:21")
+        );
+        assert_eq!(
+            report.rounds_csv()?.lines().collect::<Vec<_>>(),
+            vec![
+                "round,winner,team,reason,ct_score,t_score",
+                "1,CT,Team Alpha,elimination,1,0",
+                "2,T,Team Bravo,bomb,1,1",
+                "3,CT,Team Alpha,defuse,2,1",
+            ]
+        );
+        let dir = std::env::temp_dir().join(format!("rcon-report-{}", std::process::id()));
+        let files = report.write(&dir)?;
+        assert_eq!(files.len(), 3);
+        assert_eq!(fs::read_to_string(&files[1])?, players);
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,699 @@
+use std::{collections::HashMap, net::SocketAddr, path::Path};
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    net::lookup_host,
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+};
+
+use crate::{
+    action::Action,
+    client::{Client, ClientTask},
+    command::logs::{side, LogEvent, LogLine},
+    command::validator::Validator,
+    components::{chat::Chat, home::Home, Component},
+    config::{Config, LogConfig},
+    controller::{MatchController, MatchType, Phase, Step, Trigger},
+    matchfile::{MatchFile, Series},
+    ready::ReadyUp,
+    receiver::{http, Feed, Receivers},
+    report::Report,
+    stats::MatchStats,
+};
+
+/// What happened in a background tab since it was last looked at.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Activity {
+    #[default]
+    None,
+    Output,
+    Error,
+}
+
+/// What the tab bar shows of a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tab {
+    pub title: String,
+    /// Phase and score of the match being run.
+    pub status: Option<String>,
+    pub connected: bool,
+    pub activity: Activity,
+}
+
+/// One server connection with its own output, history and status, shown in a tab.
+///
+/// The client reports back on a channel of its own, so responses end up in the
+/// session that sent the command even when another tab is in front.
+pub struct Session {
+    pub id: usize,
+    pub client: ClientTask,
+    pub home: Home,
+    pub chat: Chat,
+    pub validator: Validator,
+    pub controller: MatchController,
+    /// A transition of a background tab waiting to be offered until the tab is shown.
+    pub pending: Option<Step>,
+    pub ready: ReadyUp,
+    /// The match file loaded with `match load`.
+    pub series: Option<Series>,
+    pub activity: Activity,
+    connected: bool,
+    /// Url of the http log receiver the server posts to.
+    log_url: Option<String>,
+    /// Address udp log packets come from.
+    log_server: Option<SocketAddr>,
+    feed: Feed,
+    /// Log lines taken off the channel so far.
+    log_lines: usize,
+    tx: UnboundedSender<Action>,
+    rx: UnboundedReceiver<Action>,
+}
+
+impl Session {
+    pub fn new(id: usize, config: &Config, action_tx: UnboundedSender<Action>) -> Result<Self> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut client = Client::default();
+        client.register_action_handler(tx.clone())?;
+        let mut home = Home::new();
+        home.register_action_handler(action_tx.clone())?;
+        home.register_config_handler(config.clone())?;
+        let mut chat = Chat::new();
+        chat.register_action_handler(action_tx)?;
+        chat.register_config_handler(config.clone())?;
+        Ok(Self {
+            id,
+            client: ClientTask::new(client),
+            home,
+            chat,
+            validator: Validator::new(config.validation),
+            controller: MatchController::new(),
+            pending: None,
+            ready: ReadyUp::new(config.ready.clone()),
+            series: None,
+            activity: Activity::None,
+            connected: false,
+            log_url: None,
+            log_server: None,
+            feed: Feed::new(tx.clone()),
+            log_lines: 0,
+            tx,
+            rx,
+        })
+    }
+
+    pub fn register_config_handler(&mut self, config: &Config) -> Result<()> {
+        self.validator.set_policy(config.validation);
+        self.ready.set_config(config.ready.clone());
+        self.home.register_config_handler(config.clone())?;
+        self.chat.register_config_handler(config.clone())
+    }
+
+    pub fn tab(&self) -> Tab {
+        Tab {
+            title: match self.client.address().as_str() {
+                "" => "new".to_string(),
+                address => address.to_string(),
+            },
+            status: self.controller.label(),
+            connected: self.connected,
+            activity: self.activity,
+        }
+    }
+
+    /// A sender for anything else that reports to this session, like its log receiver.
+    pub fn sender(&self) -> UnboundedSender<Action> {
+        self.tx.clone()
+    }
+
+    /// Actions sent to the session since the last call.
+    pub fn received(&mut self) -> Vec<Action> {
+        let mut actions = Vec::new();
+        while let Ok(action) = self.rx.try_recv() {
+            actions.push(action);
+        }
+        actions
+    }
+
+    /// The log lines of the current match this session has handled, to rebuild what the
+    /// components show from.
+    pub fn backlog(&self) -> Vec<LogLine> {
+        self.feed.backlog(self.log_lines)
+    }
+
+    /// Have the server send its log to this session, starting the listeners all
+    /// sessions share on first use.
+    pub async fn start_logs(
+        &mut self,
+        receivers: &mut Receivers,
+        config: &LogConfig,
+    ) -> Result<(), String> {
+        let mut commands = Vec::new();
+        if let Some(http) = &config.http {
+            let receiver = receivers.http(&http.listen).await?;
+            // The receiver takes anything posted to the path, so it must not be guessable.
+            let url = format!(
+                "{}/{}/{}",
+                http.url.trim_end_matches('/'),
+                self.id,
+                http::token()
+            );
+            receiver.route(url_path(&url), self.feed.clone());
+            commands.push(format!("logaddress_add_http \"{}\"", url));
+            self.log_url = Some(url);
+        }
+        if let Some(udp) = &config.udp {
+            let receiver = receivers.udp(&udp.listen).await?;
+            let address = self.client.address();
+            let server = lookup_host(&address)
+                .await
+                .ok()
+                .and_then(|mut addrs| addrs.next())
+                .ok_or_else(|| format!("Failed to resolve {}", address))?;
+            receiver.route(server, self.feed.clone(), udp.secret.clone());
+            self.log_server = Some(server);
+            if let Some(secret) = &udp.secret {
+                commands.push(format!("sv_logsecret {}", secret));
+            }
+            commands.push(format!("logaddress_add {}", udp.address));
+        }
+        if !commands.is_empty() {
+            commands.insert(0, "log on".to_string());
+        }
+        for command in commands {
+            self.client.send_quiet(&command, "register log address");
+        }
+        Ok(())
+    }
+
+    /// Take the log addresses off the server again, while the connection is still up.
+    pub async fn stop_logs(&mut self, receivers: &Receivers, config: &LogConfig) {
+        let mut commands = Vec::new();
+        if let Some(url) = self.log_url.take() {
+            if let Some(receiver) = &receivers.http {
+                receiver.unroute(url_path(&url));
+            }
+            // Only ours, other tools may log to the server as well.
+            commands.push(format!("logaddress_del_http \"{}\"", url));
+        }
+        if let Some(server) = self.log_server.take() {
+            if let Some(receiver) = &receivers.udp {
+                receiver.unroute(server);
+            }
+            if let Some(udp) = &config.udp {
+                commands.push(format!("logaddress_del {}", udp.address));
+            }
+        }
+        for command in commands {
+            self.client.send_quiet(&command, "remove log address");
+        }
+    }
+
+    /// Send commands from the config to the server, like the commands of a match transition.
+    pub async fn run_commands(&mut self, commands: &[String]) {
+        for command in commands {
+            self.client.update(Action::SendCommand(command.clone()));
+        }
+    }
+
+    /// Run the commands of a match transition, or ask first unless the match type runs them
+    /// on its own. Background tabs ask once they are shown.
+    pub async fn advance(&mut self, step: Step, active: bool) -> Result<()> {
+        self.home.update(Action::Insert(format!(
+            "Match: {} -> {}",
+            step.from, step.to
+        )))?;
+        if step.run.is_empty() {
+            return Ok(());
+        }
+        match (step.auto, active) {
+            (true, _) => self.run_commands(&step.run).await,
+            (false, true) => {
+                self.home
+                    .update(Action::OfferCommands(step.to.to_string(), step.run))?;
+            }
+            (false, false) => self.pending = Some(step),
+        }
+        Ok(())
+    }
+
+    /// Answer the chat commands of players, sending what they call for and moving the
+    /// match on.
+    pub async fn ready_up(&mut self, action: &Action, active: bool) -> Result<()> {
+        let Action::Log(line) = action else {
+            return Ok(());
+        };
+        let outcome = self.ready.update(&line.event, &self.controller);
+        self.run_commands(&outcome.commands).await;
+        if let Some(trigger) = outcome.trigger {
+            match self.controller.trigger(trigger) {
+                Ok(step) => self.advance(step, active).await?,
+                Err(e) => log::warn!("Ignoring chat command: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle `match <args>`.
+    pub async fn match_command(
+        &mut self,
+        args: &str,
+        types: &HashMap<String, MatchType>,
+    ) -> Result<(), String> {
+        let mut words = args.split_whitespace();
+        let step =
+            match (words.next(), words.next()) {
+                (None | Some("status"), _) => {
+                    let mut lines = Vec::new();
+                    if let Some(series) = &self.series {
+                        lines.extend(series.file.describe());
+                        lines.push(format!("  {}", series.describe()));
+                    }
+                    lines.extend(self.controller.describe());
+                    // The output shows the newest entry first.
+                    for line in lines.into_iter().rev() {
+                        self.home.insert(line);
+                    }
+                    return Ok(());
+                }
+                (Some("load"), Some(_)) => {
+                    let path = args.trim_start().trim_start_matches("load").trim();
+                    return self.load_match(Path::new(path)).await;
+                }
+                (Some("start"), name) => {
+                    self.unload_match();
+                    let name = name.unwrap_or("competitive");
+                    let kind = types.get(name).cloned().ok_or_else(|| {
+                        let mut names = types.keys().cloned().collect::<Vec<_>>();
+                        names.sort();
+                        format!("Unknown match type `{}`, try {}", name, names.join(", "))
+                    })?;
+                    for convar in MatchController::convars() {
+                        self.client.update(Action::QueryConvar(convar.to_string()));
+                    }
+                    self.controller.start(name, kind)?
+                }
+                (Some("ready"), None) => self.controller.trigger(Trigger::Ready)?,
+                (Some("stay"), None) => self.controller.trigger(Trigger::Stay)?,
+                (Some("switch"), None) => self.controller.trigger(Trigger::Switch)?,
+                (Some("phase"), Some(phase)) => {
+                    let phase = phase
+                        .parse::<Phase>()
+                        .map_err(|_| format!("Unknown phase `{}`", phase))?;
+                    self.controller.force(phase);
+                    return Ok(());
+                }
+                (Some("stop"), None) => {
+                    self.unload_match();
+                    self.controller.stop();
+                    return Ok(());
+                }
+                _ => return Err(
+                    "Usage: match [start <type>|load <file>|ready|stay|switch|phase <phase>|stop]"
+                        .to_string(),
+                ),
+            };
+        self.advance(step, true).await.map_err(|e| e.to_string())
+    }
+
+    /// Load a match file, set the server up for it and start it on its first map, changing
+    /// to that map first if the server is on another one.
+    pub async fn load_match(&mut self, path: &Path) -> Result<(), String> {
+        let file = MatchFile::load(path)?;
+        let mut lines = file.describe();
+        let status = self.client.status();
+        if !status.players.is_empty() {
+            let roster = file.team1.players.len() + file.team2.players.len();
+            lines.push(format!(
+                "  {} players on the server, {} on the teams",
+                status.players.len(),
+                roster
+            ));
+        }
+        self.ready.set_roster(file.roster());
+        let mut series = Series::new(file);
+        let map = series.current_map();
+        series.loading = status.map != map;
+        for line in lines.into_iter().rev() {
+            self.home.insert(line);
+        }
+        let loading = series.loading;
+        self.series = Some(series);
+        self.controller.stop();
+        match loading {
+            true => {
+                self.home.insert(format!(
+                    "Changing level to {}, the match starts once it is loaded",
+                    map
+                ));
+                self.run_commands(&[format!("changelevel {}", map)]).await;
+                Ok(())
+            }
+            false => self.start_map(true).await.map_err(|e| e.to_string()),
+        }
+    }
+
+    fn unload_match(&mut self) {
+        if self.series.take().is_some() {
+            self.ready.set_roster(Default::default());
+        }
+    }
+
+    /// Start the match on the current map of the loaded file.
+    async fn start_map(&mut self, active: bool) -> Result<()> {
+        let Some(series) = &self.series else {
+            return Ok(());
+        };
+        let file = &series.file;
+        let kind = file.match_type(series.map);
+        let name = format!("{}, {}", file.title(), series.current_map());
+        // The rules come from the file rather than what the server has now.
+        self.controller
+            .set_convar("mp_maxrounds", &file.max_rounds.to_string());
+        self.controller.set_convar(
+            "mp_overtime_enable",
+            &u8::from(file.overtime.enabled).to_string(),
+        );
+        match self.controller.start(&name, kind) {
+            Ok(step) => self.advance(step, active).await,
+            Err(e) => {
+                self.home.update(Action::Error(e))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Follow the maps of a loaded match file: start each once the server has loaded it,
+    /// and wait for the next one when a game is over.
+    pub async fn follow_series(&mut self, action: &Action, active: bool) -> Result<()> {
+        let (Action::Log(line), Some(series)) = (action, self.series.as_mut()) else {
+            return Ok(());
+        };
+        match &line.event {
+            LogEvent::MapStarted { map } if series.loading && *map == series.current_map() => {
+                series.loading = false;
+                self.start_map(active).await?;
+            }
+            LogEvent::TeamPlaying { team, name } if side(team) == Some("CT") => {
+                series.ct_team.clone_from(name);
+            }
+            LogEvent::GameOver { score, .. }
+                if !series.loading && self.controller.phase() == Phase::Finished =>
+            {
+                series.record(*score);
+                if series.next_map() {
+                    let next = series.current_map();
+                    self.home.insert(format!(
+                        "{}, next map is {}, waiting for it to load",
+                        series.score(),
+                        next
+                    ));
+                    self.run_commands(&[format!("changelevel {}", next)]).await;
+                } else {
+                    self.home.insert(format!(
+                        "{} is over, {}",
+                        series.file.title(),
+                        series.score()
+                    ));
+                    self.unload_match();
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Write the report of the current match to `dir`, built from the backlog and the last
+    /// `status`, and say where it went.
+    pub fn report(&mut self, dir: &Path) -> Result<()> {
+        let stats = MatchStats::from_lines(&self.backlog());
+        let report = Report::new(&stats, &self.client.status(), chrono::Local::now());
+        match report.write(dir) {
+            Ok(files) => {
+                for file in files.iter().rev() {
+                    self.home.insert(format!("  {}", file.display()));
+                }
+                self.home.insert("Match report written to".to_string());
+            }
+            Err(e) => {
+                self.home
+                    .update(Action::Error(format!("Failed to write report: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the report on its own once the server says the game is over.
+    pub fn report_game_over(&mut self, action: &Action, dir: &Path) -> Result<()> {
+        match action {
+            Action::Log(line) if matches!(line.event, LogEvent::GameOver { .. }) => {
+                self.report(dir)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Keep track of the connection, and of anything new while the tab is in the background.
+    pub fn observe(&mut self, action: &Action, active: bool) {
+        self.validator.update(action);
+        let activity = match action {
+            Action::Connected(connected) => {
+                self.connected = *connected;
+                match connected {
+                    true => Activity::None,
+                    false => Activity::Error,
+                }
+            }
+            Action::Insert(_) | Action::InsertAll(_) => Activity::Output,
+            Action::Log(_) => {
+                self.log_lines += 1;
+                Activity::None
+            }
+            Action::Error(_) => Activity::Error,
+            _ => Activity::None,
+        };
+        if !active {
+            self.activity = self.activity.max(activity);
+        }
+    }
+}
+
+/// The path part of `url`, which is what the server asks the listener for.
+fn url_path(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    rest.find('/').map(|i| &rest[i..]).unwrap_or("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{command::logs::parse_line, config::HttpLogConfig};
+
+    #[test]
+    fn test_background_activity() -> Result<()> {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(0, &Config::default(), tx)?;
+        assert_eq!(session.tab().title, "new");
+
+        session.observe(&Action::Insert("hello".to_string()), true);
+        assert_eq!(session.activity, Activity::None);
+        session.observe(&Action::Connected(true), false);
+        assert!(session.tab().connected);
+        session.observe(&Action::Insert("hello".to_string()), false);
+        assert_eq!(session.activity, Activity::Output);
+        session.observe(&Action::Error("oops".to_string()), false);
+        session.observe(&Action::Insert("hello".to_string()), false);
+        assert_eq!(session.activity, Activity::Error);
+        session.observe(&Action::Connected(false), true);
+        assert!(!session.tab().connected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_match_command() -> Result<()> {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let config = Config::default_config();
+        let mut session = Session::new(0, &config, tx)?;
+        assert!(session
+            .match_command("start nope", &config.matches)
+            .await
+            .unwrap_err()
+            .contains("competitive, wingman"));
+        session
+            .match_command("start", &config.matches)
+            .await
+            .unwrap();
+        assert_eq!(session.tab().status, Some("warmup".to_string()));
+        // Commands are offered rather than run.
+        let confirm = session.home.confirm.as_ref().unwrap();
+        assert_eq!(
+            confirm.action,
+            Action::RunCommands(vec!["exec warmup".to_string()])
+        );
+
+        assert!(session
+            .match_command("stay", &config.matches)
+            .await
+            .is_err());
+        assert!(session
+            .match_command("phase nowhere", &config.matches)
+            .await
+            .is_err());
+        session
+            .match_command("phase Live", &config.matches)
+            .await
+            .unwrap();
+        assert_eq!(session.controller.phase(), Phase::Live);
+        session
+            .match_command("stop", &config.matches)
+            .await
+            .unwrap();
+        assert_eq!(session.tab().status, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_match() -> Result<()> {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let config = Config::default_config();
+        let mut session = Session::new(0, &config, tx)?;
+        let log = |text: &str| {
+            let line = parse_line(&format!("10/18/2026 - 21:30:00.000 - {}", text)).unwrap();
+            Action::Log(Box::new(line))
+        };
+        assert!(session
+            .match_command("load nowhere.json5", &config.matches)
+            .await
+            .unwrap_err()
+            .starts_with("Failed to read nowhere.json5"));
+        session
+            .match_command("load fixtures/matches/cup_final.json5", &config.matches)
+            .await
+            .unwrap();
+        // The server has to change to the first map before anything starts.
+        assert!(session.series.as_ref().unwrap().loading);
+        for map in ["de_inferno", "de_nuke"] {
+            let started = log(&format!(r#"Started map "{}" (CRC "1")"#, map));
+            session.follow_series(&started, true).await?;
+        }
+        assert_eq!(session.controller.phase(), Phase::Warmup);
+        let confirm = session.home.confirm.as_ref().unwrap();
+        let Action::RunCommands(commands) = &confirm.action else {
+            panic!("{:?}", confirm.action);
+        };
+        assert_eq!(
+            commands[..2],
+            ["exec warmup", "mp_teamname_1 \"Team Alpha\""]
+        );
+
+        session.controller.force(Phase::Live);
+        let ct = log(r#"MatchStatus: Team playing "CT": Team Bravo"#);
+        session.follow_series(&ct, true).await?;
+        let over = log("Game Over: competitive mg_active de_nuke score 4:13 after 30 min");
+        session.controller.update(&over);
+        session.follow_series(&over, true).await?;
+        let series = session.series.as_ref().unwrap();
+        assert_eq!(
+            series.describe(),
+            "Map 2 of 3: de_inferno, loading, Team Alpha 1-0 Team Bravo"
+        );
+
+        session
+            .match_command("stop", &config.matches)
+            .await
+            .unwrap();
+        assert_eq!(session.series, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_backlog_follows_received() -> Result<()> {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(0, &Config::default(), tx)?;
+        session.feed.publish(
+            "10/18/2026 - 21:30:00.000 - World triggered \"Match_Start\" on \"de_nuke\"\n\
+             10/18/2026 - 21:30:01.000 - World triggered \"Round_Start\"",
+        );
+        // Lines still on the channel would be seen twice.
+        assert!(session.backlog().is_empty());
+        for action in session.received() {
+            session.observe(&action, true);
+        }
+        assert_eq!(session.backlog().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_game_over() -> Result<()> {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(0, &Config::default(), tx)?;
+        let dir = std::env::temp_dir().join(format!("rcon-session-report-{}", std::process::id()));
+        session
+            .feed
+            .publish(include_str!("../fixtures/logs/cs2_match.log"));
+        for action in session.received() {
+            session.observe(&action, true);
+            session.report_game_over(&action, &dir)?;
+        }
+        let mut files = std::fs::read_dir(&dir)?
+            .map(|entry| entry.map(|e| e.file_name().to_string_lossy().to_string()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        files.sort();
+        assert_eq!(files.len(), 3);
+        assert!(files[0].ends_with("-de_nuke-players.csv"));
+        assert!(files[2].ends_with("-de_nuke.json"));
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_log_url_has_token() -> Result<()> {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let config = LogConfig {
+            http: Some(HttpLogConfig {
+                listen: "127.0.0.1:0".to_string(),
+                url: "http://203.0.113.5:27500/logs/".to_string(),
+            }),
+            udp: None,
+        };
+        let mut receivers = Receivers::default();
+        let mut urls = Vec::new();
+        for id in 0..2 {
+            let mut session = Session::new(id, &Config::default(), tx.clone())?;
+            session.start_logs(&mut receivers, &config).await.unwrap();
+            urls.push(session.log_url.clone().unwrap());
+        }
+        let prefix = "http://203.0.113.5:27500/logs/0/";
+        assert!(urls[0].starts_with(prefix));
+        assert_eq!(urls[0].len(), prefix.len() + 32);
+        assert_ne!(urls[0][prefix.len()..], urls[1][prefix.len()..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_url_path() {
+        assert_eq!(url_path("http://203.0.113.5:27500/logs/2"), "/logs/2");
+        assert_eq!(url_path("http://203.0.113.5:27500"), "/");
+    }
+
+    #[test]
+    fn test_client_actions_stay_in_session() -> Result<()> {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut session = Session::new(0, &Config::default(), tx)?;
+        session
+            .client
+            .send_action(Action::Insert("status".to_string()));
+        session.client.send_action(Action::Connected(false));
+        assert_eq!(
+            session.received(),
+            vec![
+                Action::Insert("status".to_string()),
+                Action::Connected(false)
+            ]
+        );
+        assert!(session.received().is_empty());
+        Ok(())
+    }
+}
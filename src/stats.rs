@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+
+use crate::command::logs::{side, LogEvent, LogLine, LogPlayer, WinReason};
+
+/// Health everyone starts a round with.
+const FULL_HEALTH: u32 = 100;
+
+/// What a player did in the match so far.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PlayerStats {
+    /// [`LogPlayer::key`] of the player.
+    pub key: String,
+    pub name: String,
+    pub steamid: String,
+    /// `CT` or `T`, empty while not on a team.
+    pub side: String,
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+    /// Health taken off enemies, without what went past their last hit point.
+    pub damage: u32,
+    pub headshots: u32,
+}
+
+impl PlayerStats {
+    /// Average damage per round over `rounds` played.
+    pub fn adr(&self, rounds: usize) -> u32 {
+        self.damage / rounds.max(1) as u32
+    }
+
+    /// Share of kills that were headshots, in percent.
+    pub fn headshot_percentage(&self) -> u32 {
+        match self.kills {
+            0 => 0,
+            kills => self.headshots * 100 / kills,
+        }
+    }
+}
+
+/// How a round ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundResult {
+    /// `CT` or `T`.
+    pub winner: String,
+    /// Name of the team on the winning side then, the sides swap at halftime.
+    pub team: String,
+    pub reason: WinReason,
+    /// CT and T score after the round.
+    pub score: (u32, u32),
+}
+
+/// The score, rounds and player stats of a match, built from its log events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchStats {
+    pub map: String,
+    /// Names of the teams on CT and T, empty until the server says.
+    pub teams: (String, String),
+    /// Rounds won by CT and T.
+    pub score: (u32, u32),
+    pub rounds: Vec<RoundResult>,
+    pub game_over: bool,
+    players: Vec<PlayerStats>,
+    max_rounds: u32,
+    overtime_rounds: u32,
+    /// Health left of the players hit this round.
+    health: HashMap<String, u32>,
+}
+
+impl Default for MatchStats {
+    fn default() -> Self {
+        Self {
+            map: String::new(),
+            teams: Default::default(),
+            score: (0, 0),
+            rounds: Vec::new(),
+            game_over: false,
+            players: Vec::new(),
+            max_rounds: 24,
+            overtime_rounds: 6,
+            health: HashMap::new(),
+        }
+    }
+}
+
+impl MatchStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The stats of the lines from the start of a match on.
+    pub fn from_lines(lines: &[LogLine]) -> Self {
+        let mut stats = Self::new();
+        for line in lines {
+            stats.update(&line.event);
+        }
+        stats
+    }
+
+    /// Players on `side`, the most kills first.
+    pub fn players(&self, side: &str) -> Vec<&PlayerStats> {
+        let mut players = self
+            .players
+            .iter()
+            .filter(|p| p.side == side)
+            .collect::<Vec<_>>();
+        players.sort_by(|a, b| b.kills.cmp(&a.kills).then(a.deaths.cmp(&b.deaths)));
+        players
+    }
+
+    /// The round being played, or the last one once the game is over.
+    pub fn round(&self) -> usize {
+        match self.game_over {
+            true => self.rounds.len(),
+            false => self.rounds.len() + 1,
+        }
+    }
+
+    /// Which half or overtime the current round is in.
+    pub fn half(&self) -> String {
+        let round = self.round() as u32;
+        let max_rounds = self.max_rounds.max(1);
+        if self.game_over {
+            "game over".to_string()
+        } else if round <= max_rounds / 2 {
+            "first half".to_string()
+        } else if round <= max_rounds {
+            "second half".to_string()
+        } else {
+            let overtime = (round - max_rounds - 1) / self.overtime_rounds.max(1) + 1;
+            format!("overtime {}", overtime)
+        }
+    }
+
+    /// Rounds after which the teams swap sides, for the round history.
+    pub fn is_last_of_half(&self, round: usize) -> bool {
+        let round = round as u32;
+        let half = (self.max_rounds / 2).max(1);
+        let overtime_half = (self.overtime_rounds / 2).max(1);
+        match round <= self.max_rounds {
+            true => round.is_multiple_of(half),
+            false => (round - self.max_rounds).is_multiple_of(overtime_half),
+        }
+    }
+
+    fn player(&mut self, player: &LogPlayer) -> &mut PlayerStats {
+        let key = player.key();
+        let index = match self.players.iter().position(|p| p.key == key) {
+            Some(index) => index,
+            None => {
+                self.players.push(PlayerStats {
+                    key,
+                    steamid: player.steamid.clone(),
+                    ..PlayerStats::default()
+                });
+                self.players.len() - 1
+            }
+        };
+        let stats = &mut self.players[index];
+        stats.name.clone_from(&player.name);
+        if let Some(side) = player.side() {
+            stats.side = side.to_string();
+        }
+        stats
+    }
+
+    /// The events used here have to be kept in the backlog of the session, see
+    /// `receiver::replayed`.
+    pub fn update(&mut self, event: &LogEvent) {
+        match event {
+            LogEvent::MatchStart { map } => {
+                // The players stay on the board, but start over like the score.
+                let players = self
+                    .players
+                    .drain(..)
+                    .map(|p| PlayerStats {
+                        key: p.key,
+                        name: p.name,
+                        steamid: p.steamid,
+                        side: p.side,
+                        ..PlayerStats::default()
+                    })
+                    .collect();
+                *self = Self {
+                    map: map.clone(),
+                    teams: std::mem::take(&mut self.teams),
+                    players,
+                    max_rounds: self.max_rounds,
+                    overtime_rounds: self.overtime_rounds,
+                    ..Self::default()
+                };
+            }
+            LogEvent::TeamPlaying { team, name } => match side(team) {
+                Some("CT") => self.teams.0.clone_from(name),
+                Some(_) => self.teams.1.clone_from(name),
+                None => {}
+            },
+            LogEvent::Cvar { name, value } => match name.as_str() {
+                "mp_maxrounds" => self.max_rounds = value.parse().unwrap_or(self.max_rounds),
+                "mp_overtime_maxrounds" => {
+                    self.overtime_rounds = value.parse().unwrap_or(self.overtime_rounds)
+                }
+                _ => {}
+            },
+            LogEvent::SwitchedTeam { player, to, .. } => {
+                self.player(player).side = side(to).unwrap_or_default().to_string();
+            }
+            LogEvent::RoundStart => self.health.clear(),
+            LogEvent::Attacked {
+                attacker,
+                victim,
+                damage,
+                health,
+                ..
+            } => {
+                let left = self.health.insert(victim.key(), *health);
+                self.player(victim);
+                if attacker.side() != victim.side() {
+                    let dealt = (*damage).min(left.unwrap_or(FULL_HEALTH));
+                    self.player(attacker).damage += dealt;
+                }
+            }
+            LogEvent::Killed {
+                attacker,
+                victim,
+                headshot,
+                ..
+            } => {
+                self.player(victim).deaths += 1;
+                // Team kills are no kills.
+                if attacker.side() != victim.side() {
+                    let stats = self.player(attacker);
+                    stats.kills += 1;
+                    if *headshot {
+                        stats.headshots += 1;
+                    }
+                }
+            }
+            LogEvent::Suicide { player, .. } => self.player(player).deaths += 1,
+            LogEvent::Assist { assister, .. } => self.player(assister).assists += 1,
+            LogEvent::RoundWon {
+                team,
+                reason,
+                ct_score,
+                t_score,
+            } => {
+                let winner = side(team).unwrap_or(team).to_string();
+                self.rounds.push(RoundResult {
+                    team: match winner.as_str() {
+                        "CT" => self.teams.0.clone(),
+                        "T" => self.teams.1.clone(),
+                        _ => String::new(),
+                    },
+                    winner,
+                    reason: reason.clone(),
+                    score: (*ct_score, *t_score),
+                });
+                self.score = (*ct_score, *t_score);
+            }
+            // Comes after every round, and keeps the score right if a round was missed.
+            LogEvent::MatchStatus {
+                ct_score,
+                t_score,
+                map,
+                ..
+            } => {
+                self.score = (*ct_score, *t_score);
+                if self.map.is_empty() {
+                    self.map.clone_from(map);
+                }
+            }
+            LogEvent::GameOver { map, score, .. } => {
+                self.game_over = true;
+                self.score = *score;
+                self.map.clone_from(map);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::command::logs::parse_line;
+
+    const FIXTURE: &str = include_str!("../fixtures/logs/cs2_match.log");
+
+    fn lines(text: &str) -> Vec<LogLine> {
+        text.lines().filter_map(parse_line).collect()
+    }
+
+    /// Name, kills, deaths, assists, ADR and headshot percentage of the players on `side`.
+    fn board(stats: &MatchStats, side: &str) -> Vec<(String, u32, u32, u32, u32, u32)> {
+        stats
+            .players(side)
+            .into_iter()
+            .map(|p| {
+                (
+                    p.name.clone(),
+                    p.kills,
+                    p.deaths,
+                    p.assists,
+                    p.adr(stats.rounds.len()),
+                    p.headshot_percentage(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_match_fixture() {
+        let stats = MatchStats::from_lines(&lines(FIXTURE));
+        assert_eq!(stats.map, "de_nuke");
+        assert_eq!(
+            stats.teams,
+            ("Team Alpha".to_string(), "Team Bravo".to_string())
+        );
+        assert_eq!(stats.score, (2, 1));
+        assert!(stats.game_over);
+        assert_eq!(stats.round(), 3);
+        assert_eq!(stats.half(), "game over");
+        assert_eq!(
+            stats
+                .rounds
+                .iter()
+                .map(|r| (r.winner.as_str(), r.reason.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("CT", WinReason::Elimination),
+                ("T", WinReason::Bomb),
+                ("CT", WinReason::Defuse),
+            ]
+        );
+        assert_eq!(
+            board(&stats, "CT"),
+            vec![
+                ("Alice".to_string(), 4, 2, 0, 113, 50),
+                ("Carol".to_string(), 2, 2, 2, 33, 0),
+            ]
+        );
+        assert_eq!(
+            board(&stats, "T"),
+            vec![
+                ("Dave".to_string(), 3, 3, 0, 66, 33),
+                ("Bob".to_string(), 1, 4, 0, 33, 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_start_resets() {
+        let mut all = lines(FIXTURE);
+        all.extend(lines(
+            r#"10/18/2026 - 21:40:00.000 - World triggered "Match_Start" on "de_ancient"
+10/18/2026 - 21:40:01.000 - World triggered "Round_Start""#,
+        ));
+        let stats = MatchStats::from_lines(&all);
+        assert_eq!(stats.map, "de_ancient");
+        assert_eq!(stats.score, (0, 0));
+        assert_eq!(stats.round(), 1);
+        assert_eq!(stats.half(), "first half");
+        assert_eq!(stats.teams.0, "Team Alpha");
+        // The players are still there, with nothing to their name yet.
+        assert_eq!(board(&stats, "CT")[0], ("Alice".to_string(), 0, 0, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_halves() {
+        let mut stats = MatchStats::new();
+        for (ct, t) in [(1, 0), (1, 1), (2, 1)] {
+            stats.update(&LogEvent::RoundWon {
+                team: "CT".to_string(),
+                reason: WinReason::Time,
+                ct_score: ct,
+                t_score: t,
+            });
+        }
+        assert_eq!(stats.half(), "first half");
+        stats.update(&LogEvent::Cvar {
+            name: "mp_maxrounds".to_string(),
+            value: "4".to_string(),
+        });
+        assert_eq!(stats.half(), "second half");
+        assert!(stats.is_last_of_half(2));
+        assert!(!stats.is_last_of_half(3));
+        stats.update(&LogEvent::Cvar {
+            name: "mp_overtime_maxrounds".to_string(),
+            value: "2".to_string(),
+        });
+        stats.rounds.extend(stats.rounds.clone());
+        assert_eq!(stats.round(), 7);
+        assert_eq!(stats.half(), "overtime 2");
+        assert!(stats.is_last_of_half(5));
+    }
+}
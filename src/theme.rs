@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use ratatui::style::Style;
+
+use crate::{config::parse_style, utils::get_config_dir};
+
+pub const DEFAULT_THEME: &str = "dark";
+
+const BUNDLED: [(&str, &str); 4] = [
+    ("dark", include_str!("../.config/themes/dark.json5")),
+    ("light", include_str!("../.config/themes/light.json5")),
+    (
+        "high-contrast",
+        include_str!("../.config/themes/high-contrast.json5"),
+    ),
+    (
+        "colour-blind",
+        include_str!("../.config/themes/colour-blind.json5"),
+    ),
+];
+
+/// Named style slots (`output`, `error`, `border`, ...) that widgets take their styles from.
+///
+/// Themes are json5 files mapping slot names to style strings as understood by
+/// [`parse_style`]. User themes are read from `themes/<name>.json5` in the
+/// config directory and take precedence over the bundled ones.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Theme {
+    styles: HashMap<String, Style>,
+}
+
+impl Theme {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let parsed = json5::from_str::<HashMap<String, String>>(s).map_err(|e| e.to_string())?;
+        let styles = parsed
+            .into_iter()
+            .map(|(slot, style)| (slot, parse_style(&style)))
+            .collect();
+        Ok(Self { styles })
+    }
+
+    pub fn load(name: &str) -> Result<Self, String> {
+        let path = get_config_dir()
+            .join("themes")
+            .join(format!("{}.json5", name));
+        if path.exists() {
+            let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            return Self::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e));
+        }
+        match BUNDLED.iter().find(|(bundled, _)| *bundled == name) {
+            Some((_, contents)) => Self::parse(contents),
+            None => Err(format!("Unknown theme `{}`", name)),
+        }
+    }
+
+    /// Names of the bundled themes and any user themes in the config directory.
+    pub fn names() -> Vec<String> {
+        let mut names = BUNDLED
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        if let Ok(entries) = std::fs::read_dir(get_config_dir().join("themes")) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|e| e == "json5") {
+                    if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                        if !names.iter().any(|n| n == name) {
+                            names.push(name.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// Override slots with the styles set directly in the config.
+    pub fn with_styles(mut self, styles: Option<&HashMap<String, Style>>) -> Self {
+        if let Some(styles) = styles {
+            for (slot, style) in styles.iter() {
+                self.styles.insert(slot.clone(), *style);
+            }
+        }
+        self
+    }
+
+    pub fn get(&self, slot: &str) -> Style {
+        self.styles.get(slot).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::style::{Color, Modifier};
+
+    use super::*;
+
+    #[test]
+    fn test_bundled_themes() {
+        for (name, _) in BUNDLED {
+            let theme = Theme::load(name).unwrap();
+            assert_ne!(theme.get("error"), Style::default(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_with_styles() {
+        let theme = Theme::parse("{ error: 'red', output: 'cyan' }")
+            .unwrap()
+            .with_styles(Some(&HashMap::from([(
+                "error".to_string(),
+                parse_style("bold blue"),
+            )])));
+        assert_eq!(theme.get("output").fg, Some(Color::Indexed(6)));
+        assert_eq!(theme.get("error").fg, Some(Color::Indexed(4)));
+        assert!(theme.get("error").add_modifier.contains(Modifier::BOLD));
+        assert_eq!(theme.get("missing"), Style::default());
+    }
+}
@@ -21,7 +21,10 @@ const VERSION_MESSAGE: &str = concat!(
 lazy_static! {
     pub static ref PROJECT_NAME: String = env!("CARGO_CRATE_NAME").to_uppercase().to_string();
     pub static ref LOG_FOLDER: Option<PathBuf> = Some(PathBuf::from(".logs"));
-    pub static ref CONFIG_FOLDER: Option<PathBuf> = Some(PathBuf::from("/.config"));
+    pub static ref DATA_FOLDER: Option<PathBuf> =
+        std::env::var("RCON_DATA").ok().map(PathBuf::from);
+    pub static ref CONFIG_FOLDER: Option<PathBuf> =
+        std::env::var("RCON_CONFIG").ok().map(PathBuf::from);
     pub static ref LOG_ENV: String = "debug".to_string();
     pub static ref LOG_FILE: String = format!("{}.log", env!("CARGO_PKG_NAME"));
 }
@@ -118,6 +121,36 @@ pub fn get_log_dir() -> PathBuf {
     directory
 }
 
+pub fn get_data_dir() -> PathBuf {
+    let directory = if let Some(s) = DATA_FOLDER.clone() {
+        s
+    } else if let Some(proj_dirs) = project_directory() {
+        proj_dirs.data_local_dir().to_path_buf()
+    } else {
+        PathBuf::from(".").join(".data")
+    };
+    directory
+}
+
+pub fn get_config_dir() -> PathBuf {
+    let directory = if let Some(s) = CONFIG_FOLDER.clone() {
+        s
+    } else if let Some(proj_dirs) = project_directory() {
+        proj_dirs.config_local_dir().to_path_buf()
+    } else {
+        PathBuf::from(".").join(".config")
+    };
+    directory
+}
+
+pub fn get_system_config_dir() -> Option<PathBuf> {
+    if cfg!(unix) {
+        Some(PathBuf::from("/etc").join(env!("CARGO_PKG_NAME")))
+    } else {
+        None
+    }
+}
+
 /// Similar to the `std::dbg!` macro, but generates `tracing` events rather
 /// than printing to stdout.
 ///
@@ -0,0 +1,70 @@
+use std::{collections::HashMap, path::PathBuf, time::SystemTime};
+
+/// Detects changes to a set of files by polling their modification times.
+///
+/// Files that do not exist yet are tracked as well, so creating or removing
+/// one of them also counts as a change.
+#[derive(Debug, Default)]
+pub struct FileWatcher {
+    files: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl FileWatcher {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let modified = Self::modified(&path);
+                (path, modified)
+            })
+            .collect();
+        Self { files }
+    }
+
+    fn modified(path: &PathBuf) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Returns true if any of the files changed since the last call.
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, last) in self.files.iter_mut() {
+            let modified = Self::modified(path);
+            if modified != *last {
+                log::info!("{} changed", path.display());
+                *last = modified;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    #[test]
+    fn test_changed() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("rcon-watcher-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let file = dir.join("config.json5");
+        let mut watcher = FileWatcher::new(vec![file.clone()]);
+        assert!(!watcher.changed());
+        // Creating a file that was missing counts.
+        std::fs::write(&file, "{}")?;
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+        std::fs::File::options()
+            .write(true)
+            .open(&file)?
+            .set_modified(UNIX_EPOCH + Duration::from_secs(1))?;
+        assert!(watcher.changed());
+        std::fs::remove_file(&file)?;
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+        std::fs::remove_dir_all(dir)
+    }
+}